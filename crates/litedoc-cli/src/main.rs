@@ -5,15 +5,17 @@
 //!
 //! Commands:
 //!   parse     Parse and display document structure (default)
-//!   validate  Check document for errors
+//!   validate  Check document for errors, including unresolved references
 //!   stats     Show document statistics
+//!   query     Print the nodes matching a selector
 
 use std::env;
 use std::fs;
 use std::process;
 
+use litedoc_core::span::LineIndex;
+use litedoc_core::visit::{walk_block, Visit};
 use litedoc_core::{ast, Block, Document, Inline, Parser, Profile};
-use serde::Serialize;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -44,8 +46,12 @@ fn run(args: &[String]) -> Result<(), String> {
 
     match config.command {
         Command::Parse => cmd_parse(&mut parser, &input, &config),
-        Command::Validate => cmd_validate(&mut parser, &input, &config),
+        Command::Validate => {
+            let mut parser = parser.with_reference_checks(true);
+            cmd_validate(&mut parser, &input, &config)
+        }
         Command::Stats => cmd_stats(&mut parser, &input),
+        Command::Query(ref selector) => cmd_query(&mut parser, &input, selector, &config),
     }
 }
 
@@ -57,11 +63,12 @@ struct Config {
     verbose: bool,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 enum Command {
     Parse,
     Validate,
     Stats,
+    Query(String),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -74,7 +81,7 @@ fn parse_args(args: &[String]) -> Result<Config, String> {
     let mut command = Command::Parse;
     let mut format = OutputFormat::Text;
     let mut verbose = false;
-    let mut file = None;
+    let mut positional = Vec::new();
 
     let mut i = 1;
     while i < args.len() {
@@ -93,20 +100,28 @@ fn parse_args(args: &[String]) -> Result<Config, String> {
             "parse" => command = Command::Parse,
             "validate" => command = Command::Validate,
             "stats" => command = Command::Stats,
+            "query" => command = Command::Query(String::new()),
             _ if arg.starts_with('-') => {
                 return Err(format!("unknown option: {}", arg));
             }
-            _ => {
-                if file.is_some() {
-                    return Err("multiple files specified".to_string());
-                }
-                file = Some(arg.clone());
-            }
+            _ => positional.push(arg.clone()),
         }
         i += 1;
     }
 
-    let file = file.ok_or_else(|| "no input file specified".to_string())?;
+    // `query` takes its selector before the file.
+    let mut positional = positional.into_iter();
+    if let Command::Query(selector) = &mut command {
+        *selector = positional
+            .next()
+            .ok_or_else(|| "no selector specified".to_string())?;
+    }
+    let file = positional
+        .next()
+        .ok_or_else(|| "no input file specified".to_string())?;
+    if positional.next().is_some() {
+        return Err("multiple files specified".to_string());
+    }
 
     Ok(Config {
         command,
@@ -125,8 +140,11 @@ USAGE:
 
 COMMANDS:
     parse       Parse and display document structure (default)
-    validate    Check document for errors without output
+    validate    Check document for errors, including unresolved
+                references, without output
     stats       Show document statistics
+    query       Print the nodes matching a selector, e.g.
+                'callout[type=warning]' or 'heading:contains("Findings") + table'
 
 OPTIONS:
     -v, --verbose    Show detailed AST structure
@@ -140,6 +158,8 @@ EXAMPLES:
     ldcli -j document.ld        Output AST as JSON
     ldcli validate document.ld  Validate without output
     ldcli stats document.ld     Show document statistics
+    ldcli query 'list > item' document.ld
+                                Print the items of every list, nested or not
 "#
     );
 }
@@ -152,8 +172,9 @@ fn cmd_parse(parser: &mut Parser, input: &str, config: &Config) -> Result<(), St
     let result = parser.parse_with_recovery(input);
 
     // Report any errors
+    let index = LineIndex::new(input);
     for error in result.errors.iter() {
-        eprintln!("warning: {}", error);
+        eprintln!("warning: {}", error.describe(&index));
     }
 
     match config.format {
@@ -185,14 +206,18 @@ fn cmd_validate(parser: &mut Parser, input: &str, config: &Config) -> Result<(),
         }
         Ok(())
     } else {
+        let index = LineIndex::new(input);
         if matches!(config.format, OutputFormat::Json) {
             let errors: Vec<_> = result
                 .errors
                 .iter()
                 .map(|e| {
+                    let range = e.span.map(|s| index.range(s));
                     serde_json::json!({
                         "message": e.message,
                         "span": e.span.map(|s| serde_json::json!({"start": s.start, "end": s.end})),
+                        "start": range.map(|(start, _)| start),
+                        "end": range.map(|(_, end)| end),
                         "recoverable": e.recoverable
                     })
                 })
@@ -201,7 +226,7 @@ fn cmd_validate(parser: &mut Parser, input: &str, config: &Config) -> Result<(),
         } else {
             eprintln!("Invalid: {} error(s) found", result.errors.len());
             for error in result.errors.iter() {
-                eprintln!("  - {}", error);
+                eprintln!("  - {}", error.describe(&index));
             }
         }
         Err(format!("{} error(s) found", result.errors.len()))
@@ -271,295 +296,95 @@ impl DocumentStats {
             lines: input.lines().count(),
         };
 
-        stats.count_blocks(&doc.blocks);
+        stats.visit_document(doc);
         stats
     }
+}
 
-    fn count_blocks(&mut self, blocks: &[Block]) {
-        for block in blocks {
-            self.total_blocks += 1;
-            match block {
-                Block::Heading(_) => self.headings += 1,
-                Block::Paragraph(_) => self.paragraphs += 1,
-                Block::CodeBlock(_) => self.code_blocks += 1,
-                Block::List(l) => {
-                    self.lists += 1;
-                    for item in &l.items {
-                        self.count_blocks(&item.blocks);
-                    }
-                }
-                Block::Table(_) => self.tables += 1,
-                Block::Callout(c) => {
-                    self.callouts += 1;
-                    self.count_blocks(&c.blocks);
-                }
-                Block::Quote(q) => self.count_blocks(&q.blocks),
-                _ => {}
-            }
+impl<'ast> Visit<'ast> for DocumentStats {
+    fn visit_block(&mut self, block: &'ast Block<'ast>) {
+        self.total_blocks += 1;
+        match block {
+            Block::Heading(_) => self.headings += 1,
+            Block::Paragraph(_) => self.paragraphs += 1,
+            Block::CodeBlock(_) => self.code_blocks += 1,
+            Block::List(_) => self.lists += 1,
+            Block::Table(_) => self.tables += 1,
+            Block::Callout(_) => self.callouts += 1,
+            _ => {}
         }
+        walk_block(self, block);
     }
 }
 
 // =============================================================================
-// JSON Output
+// Query Command
 // =============================================================================
 
-#[derive(Serialize)]
-struct JsonDocument<'a> {
-    profile: &'a str,
-    modules: Vec<&'a str>,
-    metadata: Option<JsonMetadata<'a>>,
-    blocks: Vec<JsonBlock<'a>>,
-}
-
-#[derive(Serialize)]
-struct JsonMetadata<'a> {
-    entries: Vec<(&'a str, serde_json::Value)>,
-}
-
-#[derive(Serialize)]
-#[serde(tag = "type")]
-enum JsonBlock<'a> {
-    Heading {
-        level: u8,
-        content: Vec<JsonInline<'a>>,
-    },
-    Paragraph {
-        content: Vec<JsonInline<'a>>,
-    },
-    CodeBlock {
-        lang: &'a str,
-        content: &'a str,
-    },
-    List {
-        kind: &'a str,
-        items: Vec<Vec<JsonBlock<'a>>>,
-    },
-    Callout {
-        kind: &'a str,
-        title: Option<&'a str>,
-        blocks: Vec<JsonBlock<'a>>,
-    },
-    Quote {
-        blocks: Vec<JsonBlock<'a>>,
-    },
-    Table {
-        rows: Vec<JsonTableRow<'a>>,
-    },
-    Figure {
-        src: &'a str,
-        alt: &'a str,
-        caption: Option<&'a str>,
-    },
-    Math {
-        display: bool,
-        content: &'a str,
-    },
-    ThematicBreak,
-    Html {
-        content: &'a str,
-    },
-    Raw {
-        content: &'a str,
-    },
-    Footnotes {
-        defs: Vec<JsonFootnoteDef<'a>>,
-    },
-}
-
-#[derive(Serialize)]
-struct JsonTableRow<'a> {
-    header: bool,
-    cells: Vec<Vec<JsonInline<'a>>>,
-}
-
-#[derive(Serialize)]
-struct JsonFootnoteDef<'a> {
-    label: &'a str,
-    blocks: Vec<JsonBlock<'a>>,
-}
-
-#[derive(Serialize)]
-#[serde(tag = "type")]
-enum JsonInline<'a> {
-    Text {
-        content: &'a str,
-    },
-    Emphasis {
-        content: Vec<JsonInline<'a>>,
-    },
-    Strong {
-        content: Vec<JsonInline<'a>>,
-    },
-    CodeSpan {
-        content: &'a str,
-    },
-    Link {
-        label: Vec<JsonInline<'a>>,
-        url: &'a str,
-    },
-    AutoLink {
-        url: &'a str,
-    },
-    Strikethrough {
-        content: Vec<JsonInline<'a>>,
-    },
-    FootnoteRef {
-        label: &'a str,
-    },
-    HardBreak,
-    SoftBreak,
-}
-
-fn print_json(doc: &Document) {
-    let json_doc = convert_document(doc);
-    println!("{}", serde_json::to_string_pretty(&json_doc).unwrap());
-}
-
-fn convert_document<'a>(doc: &'a Document) -> JsonDocument<'a> {
-    JsonDocument {
-        profile: match doc.profile {
-            Profile::Litedoc => "litedoc",
-            Profile::Md => "md",
-            Profile::MdStrict => "md-strict",
-        },
-        modules: doc
-            .modules
-            .iter()
-            .map(|m| match m {
-                ast::Module::Tables => "tables",
-                ast::Module::Footnotes => "footnotes",
-                ast::Module::Math => "math",
-                ast::Module::Tasks => "tasks",
-                ast::Module::Strikethrough => "strikethrough",
-                ast::Module::Autolink => "autolink",
-                ast::Module::Html => "html",
-            })
-            .collect(),
-        metadata: doc.metadata.as_ref().map(|m| JsonMetadata {
-            entries: m
-                .entries
-                .iter()
-                .map(|(k, v)| (k.as_ref(), convert_attr_value(v)))
-                .collect(),
-        }),
-        blocks: doc.blocks.iter().map(convert_block).collect(),
-    }
-}
-
-fn convert_attr_value(value: &ast::AttrValue) -> serde_json::Value {
-    match value {
-        ast::AttrValue::Str(s) => serde_json::Value::String(s.to_string()),
-        ast::AttrValue::Bool(b) => serde_json::Value::Bool(*b),
-        ast::AttrValue::Int(i) => serde_json::Value::Number((*i).into()),
-        ast::AttrValue::Float(f) => serde_json::json!(*f),
-        ast::AttrValue::List(items) => {
-            serde_json::Value::Array(items.iter().map(convert_attr_value).collect())
-        }
+fn cmd_query(
+    parser: &mut Parser,
+    input: &str,
+    selector: &str,
+    config: &Config,
+) -> Result<(), String> {
+    let result = parser.parse_with_recovery(input);
+    let index = LineIndex::new(input);
+    for error in result.errors.iter() {
+        eprintln!("warning: {}", error.describe(&index));
     }
-}
 
-fn convert_block<'a>(block: &'a Block) -> JsonBlock<'a> {
-    match block {
-        Block::Heading(h) => JsonBlock::Heading {
-            level: h.level,
-            content: h.content.iter().map(convert_inline).collect(),
-        },
-        Block::Paragraph(p) => JsonBlock::Paragraph {
-            content: p.content.iter().map(convert_inline).collect(),
-        },
-        Block::CodeBlock(c) => JsonBlock::CodeBlock {
-            lang: &c.lang,
-            content: &c.content,
-        },
-        Block::List(l) => JsonBlock::List {
-            kind: match l.kind {
-                ast::ListKind::Ordered => "ordered",
-                ast::ListKind::Unordered => "unordered",
-            },
-            items: l
-                .items
-                .iter()
-                .map(|item| item.blocks.iter().map(convert_block).collect())
-                .collect(),
-        },
-        Block::Callout(c) => JsonBlock::Callout {
-            kind: &c.kind,
-            title: c.title.as_deref(),
-            blocks: c.blocks.iter().map(convert_block).collect(),
-        },
-        Block::Quote(q) => JsonBlock::Quote {
-            blocks: q.blocks.iter().map(convert_block).collect(),
-        },
-        Block::Table(t) => JsonBlock::Table {
-            rows: t
-                .rows
-                .iter()
-                .map(|row| JsonTableRow {
-                    header: row.header,
-                    cells: row
-                        .cells
-                        .iter()
-                        .map(|cell| cell.content.iter().map(convert_inline).collect())
-                        .collect(),
-                })
-                .collect(),
-        },
-        Block::Figure(f) => JsonBlock::Figure {
-            src: &f.src,
-            alt: &f.alt,
-            caption: f.caption.as_deref(),
-        },
-        Block::Math(m) => JsonBlock::Math {
-            display: m.display,
-            content: &m.content,
-        },
-        Block::ThematicBreak(_) => JsonBlock::ThematicBreak,
-        Block::Html(h) => JsonBlock::Html {
-            content: &h.content,
-        },
-        Block::Raw(r) => JsonBlock::Raw {
-            content: &r.content,
-        },
-        Block::Footnotes(f) => JsonBlock::Footnotes {
-            defs: f
-                .defs
+    let nodes = result
+        .document
+        .select(selector)
+        .map_err(|e| e.to_string())?;
+    match config.format {
+        OutputFormat::Json => {
+            let matches: Vec<_> = nodes
                 .iter()
-                .map(|def| JsonFootnoteDef {
-                    label: &def.label,
-                    blocks: def.blocks.iter().map(convert_block).collect(),
+                .map(|node| {
+                    serde_json::json!({
+                        "node": node.name(),
+                        "span": node.span(),
+                        "value": node,
+                    })
                 })
-                .collect(),
-        },
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&matches).unwrap());
+        }
+        OutputFormat::Text => {
+            for node in &nodes {
+                let span = node.span();
+                let text = node.text();
+                let preview: String = text.chars().take(60).collect();
+                let ellipsis = if text.chars().nth(60).is_some() {
+                    "..."
+                } else {
+                    ""
+                };
+                println!(
+                    "{}..{} {}: {}{}",
+                    span.start,
+                    span.end,
+                    node.name(),
+                    preview.replace('\n', "\\n"),
+                    ellipsis
+                );
+                if config.verbose {
+                    println!("{}", &input[span.start as usize..span.end as usize]);
+                }
+            }
+        }
     }
+
+    Ok(())
 }
 
-fn convert_inline<'a>(inline: &'a Inline) -> JsonInline<'a> {
-    match inline {
-        Inline::Text(t) => JsonInline::Text {
-            content: &t.content,
-        },
-        Inline::Emphasis(e) => JsonInline::Emphasis {
-            content: e.content.iter().map(convert_inline).collect(),
-        },
-        Inline::Strong(s) => JsonInline::Strong {
-            content: s.content.iter().map(convert_inline).collect(),
-        },
-        Inline::CodeSpan(c) => JsonInline::CodeSpan {
-            content: &c.content,
-        },
-        Inline::Link(l) => JsonInline::Link {
-            label: l.label.iter().map(convert_inline).collect(),
-            url: &l.url,
-        },
-        Inline::AutoLink(a) => JsonInline::AutoLink { url: &a.url },
-        Inline::Strikethrough(s) => JsonInline::Strikethrough {
-            content: s.content.iter().map(convert_inline).collect(),
-        },
-        Inline::FootnoteRef(f) => JsonInline::FootnoteRef { label: &f.label },
-        Inline::HardBreak(_) => JsonInline::HardBreak,
-        Inline::SoftBreak(_) => JsonInline::SoftBreak,
-    }
+// =============================================================================
+// JSON Output
+// =============================================================================
+
+fn print_json(doc: &Document) {
+    println!("{}", serde_json::to_string_pretty(doc).unwrap());
 }
 
 // =============================================================================
@@ -628,10 +453,12 @@ fn describe_block(block: &Block) -> String {
         Block::Figure(f) => format!("Figure (src: {})", f.src),
         Block::Table(t) => format!("Table ({} rows)", t.rows.len()),
         Block::Footnotes(f) => format!("Footnotes ({} defs)", f.defs.len()),
+        Block::Sources(s) => format!("Sources ({} entries)", s.entries.len()),
         Block::Math(m) => format!("Math (display: {})", m.display),
         Block::ThematicBreak(_) => "ThematicBreak".to_string(),
         Block::Html(_) => "Html".to_string(),
         Block::Raw(_) => "Raw".to_string(),
+        Block::Custom(c) => format!("Custom (name: {})", c.name),
     }
 }
 
@@ -647,7 +474,12 @@ fn print_block_verbose(block: &Block, indent: usize) {
         }
         Block::List(l) => {
             for (i, item) in l.items.iter().enumerate() {
-                println!("{}Item {}:", prefix, i + 1);
+                let task = match item.checked {
+                    Some(true) => " [x]",
+                    Some(false) => " [ ]",
+                    None => "",
+                };
+                println!("{}Item {}{}:", prefix, i + 1, task);
                 for block in &item.blocks {
                     print_block_verbose(block, indent + 1);
                 }
@@ -665,13 +497,13 @@ fn print_block_verbose(block: &Block, indent: usize) {
         }
         Block::Callout(c) => {
             for (i, block) in c.blocks.iter().enumerate() {
-                println!("{}Block {}:", prefix, i + 1);
+                println!("{}Block {}: {}", prefix, i + 1, describe_block(block));
                 print_block_verbose(block, indent + 1);
             }
         }
         Block::Quote(q) => {
             for (i, block) in q.blocks.iter().enumerate() {
-                println!("{}Block {}:", prefix, i + 1);
+                println!("{}Block {}: {}", prefix, i + 1, describe_block(block));
                 print_block_verbose(block, indent + 1);
             }
         }
@@ -700,10 +532,28 @@ fn print_block_verbose(block: &Block, indent: usize) {
                 }
             }
         }
+        Block::Sources(s) => {
+            for entry in &s.entries {
+                let title = entry.title.as_deref().unwrap_or("");
+                println!("{}@{}: {}", prefix, entry.id, title);
+            }
+        }
         Block::Math(m) => {
             let preview: String = m.content.chars().take(40).collect();
             println!("{}Content: {}", prefix, preview);
         }
+        Block::Custom(c) => match &c.body {
+            ast::CustomBody::Blocks(blocks) => {
+                for (i, block) in blocks.iter().enumerate() {
+                    println!("{}Block {}: {}", prefix, i + 1, describe_block(block));
+                    print_block_verbose(block, indent + 1);
+                }
+            }
+            ast::CustomBody::Content(content) => {
+                let preview: String = content.chars().take(40).collect();
+                println!("{}Content: {}", prefix, preview);
+            }
+        },
         _ => {}
     }
 }
@@ -750,6 +600,23 @@ fn format_inlines(inlines: &[Inline]) -> String {
                 result.push_str(&f.label);
                 result.push(']');
             }
+            Inline::Citation(c) => {
+                result.push_str("[@");
+                result.push_str(&c.key);
+                result.push(']');
+            }
+            Inline::Mention(m) => {
+                result.push_str("<@");
+                result.push_str(&m.kind);
+                result.push(':');
+                result.push_str(&m.id);
+                result.push('>');
+            }
+            Inline::Math(m) => {
+                result.push_str("\\(");
+                result.push_str(&m.content);
+                result.push_str("\\)");
+            }
             Inline::HardBreak(_) => result.push_str("\\n"),
             Inline::SoftBreak(_) => result.push(' '),
         }
@@ -1,6 +1,7 @@
 //! Benchmarks comparing LiteDoc parsing vs pulldown-cmark (Markdown)
 //!
 //! Run with: cargo bench -p litedoc-core
+//! Arena parsing: cargo bench -p litedoc-core --features arena -- arena
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use litedoc_core::{Parser, Profile};
@@ -230,10 +231,70 @@ fn bench_inline_parsing(c: &mut Criterion) {
     group.finish();
 }
 
+/// A short agent message, typical of high-volume parsing.
+#[cfg(feature = "arena")]
+const MESSAGE_SAMPLE: &str = r#"@modules tables
+
+# Result for task t-42
+
+Fetched **3** sources and extracted *12* records; see [[the log|https://ci.example/42]].
+
+::list
+- `orders.csv`: 8 records
+- `refunds.csv`: 4 records
+::
+
+::table
+| Source | Records |
+| --- | --- |
+| orders | 8 |
+| refunds | 4 |
+::
+"#;
+
+/// The default allocator path against an arena reset between documents.
+#[cfg(feature = "arena")]
+fn bench_arena(c: &mut Criterion) {
+    use litedoc_core::arena::Arena;
+
+    let mut group = c.benchmark_group("arena");
+
+    for (name, sample) in [("message", MESSAGE_SAMPLE), ("document", LITEDOC_SAMPLE)] {
+        group.throughput(Throughput::Bytes(sample.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("allocator", name), sample, |b, input| {
+            let mut parser = Parser::new(Profile::Litedoc);
+            b.iter(|| {
+                let doc = parser.parse(black_box(input)).unwrap();
+                black_box(doc.blocks.len())
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("arena", name), sample, |b, input| {
+            let mut parser = Parser::new(Profile::Litedoc);
+            let mut arena = Arena::new();
+            b.iter(|| {
+                let doc = parser.parse_in(black_box(input), &mut arena).unwrap();
+                let len = doc.blocks.len();
+                arena.reset(doc);
+                black_box(len)
+            })
+        });
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_litedoc_parse,
     bench_scaling,
     bench_inline_parsing
 );
+#[cfg(feature = "arena")]
+criterion_group!(arena, bench_arena);
+
+#[cfg(feature = "arena")]
+criterion_main!(benches, arena);
+#[cfg(not(feature = "arena"))]
 criterion_main!(benches);
@@ -0,0 +1,299 @@
+//! Reusing node storage across parses (`arena` feature).
+//!
+//! Parsing allocates a vector for every list of nodes in the tree: block
+//! and inline lists (including the children of each `Strong`, `Emphasis`
+//! and link label), list items, table rows, cells and alignments, footnote
+//! definitions, source entries, attribute maps and list-valued attributes.
+//! For a stream of short documents those allocations dominate. An
+//! [`Arena`] keeps every vector of the documents it is given back and lends
+//! them to the next parse, so a steady stream of similar documents stops
+//! allocating node storage after the first few.
+//!
+//! Documents parsed in an arena are ordinary [`Document`]s: they can be kept,
+//! converted with `into_owned()` or simply dropped. Only documents passed to
+//! [`Arena::reset`] return their storage.
+//!
+//! ```rust
+//! use litedoc_core::arena::Arena;
+//! use litedoc_core::{Parser, Profile};
+//!
+//! let mut parser = Parser::new(Profile::Litedoc);
+//! let mut arena = Arena::new();
+//! for message in ["# One\n\nFirst *message*.", "# Two\n\nSecond **message**."] {
+//!     let doc = parser.parse_in(message, &mut arena).unwrap();
+//!     assert_eq!(doc.blocks.len(), 2);
+//!     arena.reset(doc);
+//! }
+//! assert!(arena.spare_count() > 0);
+//! ```
+
+use std::alloc::Layout;
+use std::mem::ManuallyDrop;
+
+use crate::ast::{
+    Alignment, AttrMap, AttrValue, Block, CowStr, CustomBody, Document, FootnoteDef, Inline,
+    ListItem, Module, Source, TableCell, TableRow,
+};
+use crate::parser::{ParseResult, Parser, Spares};
+use crate::ParseError;
+
+/// Most spare vectors of each kind an arena keeps; further ones are freed.
+const MAX_SPARES: usize = 4096;
+
+/// Storage for node vectors, reused from one parse to the next.
+///
+/// The arena holds on to the storage of the largest documents it has been
+/// given, up to a few thousand vectors of each kind; drop it to release
+/// that memory.
+#[derive(Debug, Default)]
+pub struct Arena {
+    spares: Spares,
+}
+
+impl Arena {
+    /// Create an empty arena.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take back the node storage of `doc` for the next parse.
+    ///
+    /// Every vector in the tree is emptied and kept, with its capacity;
+    /// text owned by the document is freed as usual.
+    pub fn reset(&mut self, doc: Document<'_>) {
+        keep(&mut self.spares.modules, doc.modules);
+        if let Some(meta) = doc.metadata {
+            self.reclaim_attrs(meta.entries);
+        }
+        self.reclaim_blocks(doc.blocks);
+    }
+
+    /// Number of emptied node vectors waiting to be reused.
+    pub fn spare_count(&self) -> usize {
+        let Spares {
+            blocks,
+            inlines,
+            items,
+            rows,
+            cells,
+            alignments,
+            defs,
+            sources,
+            attrs,
+            values,
+            modules,
+        } = &self.spares;
+        blocks.len()
+            + inlines.len()
+            + items.len()
+            + rows.len()
+            + cells.len()
+            + alignments.len()
+            + defs.len()
+            + sources.len()
+            + attrs.len()
+            + values.len()
+            + modules.len()
+    }
+
+    fn reclaim_blocks(&mut self, mut blocks: Vec<Block<'_>>) {
+        for block in blocks.drain(..) {
+            self.reclaim_block(block);
+        }
+        keep(&mut self.spares.blocks, blocks);
+    }
+
+    fn reclaim_inlines(&mut self, mut inlines: Vec<Inline<'_>>) {
+        for inline in inlines.drain(..) {
+            self.reclaim_inline(inline);
+        }
+        keep(&mut self.spares.inlines, inlines);
+    }
+
+    fn reclaim_attrs(&mut self, mut attrs: AttrMap<'_>) {
+        for (_, value) in attrs.drain(..) {
+            self.reclaim_value(value);
+        }
+        keep(&mut self.spares.attrs, attrs);
+    }
+
+    fn reclaim_value(&mut self, value: AttrValue<'_>) {
+        if let AttrValue::List(mut items) = value {
+            for item in items.drain(..) {
+                self.reclaim_value(item);
+            }
+            keep(&mut self.spares.values, items);
+        }
+    }
+
+    fn reclaim_block(&mut self, block: Block<'_>) {
+        match block {
+            Block::Heading(h) => self.reclaim_inlines(h.content),
+            Block::Paragraph(p) => self.reclaim_inlines(p.content),
+            Block::List(l) => {
+                self.reclaim_attrs(l.attrs);
+                let mut items = l.items;
+                for item in items.drain(..) {
+                    self.reclaim_blocks(item.blocks);
+                }
+                keep(&mut self.spares.items, items);
+            }
+            Block::CodeBlock(c) => self.reclaim_attrs(c.attrs),
+            Block::Callout(c) => {
+                self.reclaim_attrs(c.attrs);
+                self.reclaim_blocks(c.blocks);
+            }
+            Block::Quote(q) => {
+                self.reclaim_attrs(q.attrs);
+                self.reclaim_blocks(q.blocks);
+            }
+            Block::Figure(f) => self.reclaim_attrs(f.attrs),
+            Block::Table(t) => {
+                self.reclaim_attrs(t.attrs);
+                keep(&mut self.spares.alignments, t.alignments);
+                let mut rows = t.rows;
+                for row in rows.drain(..) {
+                    let mut cells = row.cells;
+                    for cell in cells.drain(..) {
+                        self.reclaim_inlines(cell.content);
+                    }
+                    keep(&mut self.spares.cells, cells);
+                }
+                keep(&mut self.spares.rows, rows);
+            }
+            Block::Footnotes(f) => {
+                let mut defs = f.defs;
+                for def in defs.drain(..) {
+                    self.reclaim_blocks(def.blocks);
+                }
+                keep(&mut self.spares.defs, defs);
+            }
+            Block::Sources(s) => {
+                self.reclaim_attrs(s.attrs);
+                let mut entries = s.entries;
+                for entry in entries.drain(..) {
+                    self.reclaim_attrs(entry.attrs);
+                }
+                keep(&mut self.spares.sources, entries);
+            }
+            Block::Math(m) => self.reclaim_attrs(m.attrs),
+            Block::Raw(r) => self.reclaim_attrs(r.attrs),
+            Block::Custom(c) => {
+                self.reclaim_attrs(c.attrs);
+                if let CustomBody::Blocks(blocks) = c.body {
+                    self.reclaim_blocks(blocks);
+                }
+            }
+            Block::ThematicBreak(_) | Block::Html(_) => {}
+        }
+    }
+
+    fn reclaim_inline(&mut self, inline: Inline<'_>) {
+        match inline {
+            Inline::Emphasis(e) => self.reclaim_inlines(e.content),
+            Inline::Strong(s) => self.reclaim_inlines(s.content),
+            Inline::Strikethrough(s) => self.reclaim_inlines(s.content),
+            Inline::Link(l) => self.reclaim_inlines(l.label),
+            _ => {}
+        }
+    }
+}
+
+/// Node types whose emptied vectors an arena keeps.
+///
+/// # Safety
+///
+/// `Static` must be `Self` with every lifetime replaced by `'static`. Types
+/// that differ only in lifetimes have the same layout, which is what lets
+/// [`keep`] move an allocation from one to the other.
+unsafe trait Spare {
+    type Static;
+}
+
+// SAFETY: in each impl below, `Static` is the implementing type at `'static`.
+
+unsafe impl Spare for Block<'_> {
+    type Static = Block<'static>;
+}
+
+unsafe impl Spare for Inline<'_> {
+    type Static = Inline<'static>;
+}
+
+unsafe impl Spare for ListItem<'_> {
+    type Static = ListItem<'static>;
+}
+
+unsafe impl Spare for TableRow<'_> {
+    type Static = TableRow<'static>;
+}
+
+unsafe impl Spare for TableCell<'_> {
+    type Static = TableCell<'static>;
+}
+
+unsafe impl Spare for Alignment {
+    type Static = Alignment;
+}
+
+unsafe impl Spare for FootnoteDef<'_> {
+    type Static = FootnoteDef<'static>;
+}
+
+unsafe impl Spare for Source<'_> {
+    type Static = Source<'static>;
+}
+
+unsafe impl Spare for (CowStr<'_>, AttrValue<'_>) {
+    type Static = (CowStr<'static>, AttrValue<'static>);
+}
+
+unsafe impl Spare for AttrValue<'_> {
+    type Static = AttrValue<'static>;
+}
+
+unsafe impl Spare for Module {
+    type Static = Module;
+}
+
+/// Empty `vec` and keep its allocation in `pool`, if there is room.
+fn keep<T: Spare>(pool: &mut Vec<Vec<T::Static>>, mut vec: Vec<T>) {
+    debug_assert_eq!(Layout::new::<T>(), Layout::new::<T::Static>());
+    vec.clear();
+    if vec.capacity() == 0 || pool.len() >= MAX_SPARES {
+        return;
+    }
+    let mut vec = ManuallyDrop::new(vec);
+    // SAFETY: the allocation came from a `Vec<T>` of this capacity, and
+    // `T::Static` has the layout of `T` (see `Spare`), so it can be freed as
+    // a `Vec<T::Static>`. With a length of 0 no element is read, so nothing
+    // borrowed from the old input is reachable through the new vector.
+    let spare = unsafe { Vec::from_raw_parts(vec.as_mut_ptr().cast(), 0, vec.capacity()) };
+    pool.push(spare);
+}
+
+impl Parser {
+    /// Like [`Parser::parse`], taking node storage from `arena`.
+    pub fn parse_in<'a>(
+        &mut self,
+        input: &'a str,
+        arena: &mut Arena,
+    ) -> Result<Document<'a>, ParseError> {
+        std::mem::swap(&mut self.spares, &mut arena.spares);
+        let result = self.parse(input);
+        std::mem::swap(&mut self.spares, &mut arena.spares);
+        result
+    }
+
+    /// Like [`Parser::parse_with_recovery`], taking node storage from `arena`.
+    pub fn parse_with_recovery_in<'a>(
+        &mut self,
+        input: &'a str,
+        arena: &mut Arena,
+    ) -> ParseResult<'a> {
+        std::mem::swap(&mut self.spares, &mut arena.spares);
+        let result = self.parse_with_recovery(input);
+        std::mem::swap(&mut self.spares, &mut arena.spares);
+        result
+    }
+}
@@ -8,12 +8,15 @@
 //! - **Comprehensive**: Supports all LiteDoc and Markdown constructs
 
 use crate::span::Span;
+use crate::visit::Visit;
 
 /// Parsing profile that determines syntax rules.
 ///
 /// The profile affects how the parser interprets certain constructs
 /// and which features are enabled by default.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 pub enum Profile {
     /// Full LiteDoc syntax with explicit fencing.
     ///
@@ -29,6 +32,8 @@ pub enum Profile {
 ///
 /// Modules can be enabled via the `@modules` directive or parser configuration.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Module {
     /// GFM-style tables with `|` delimiters.
     Tables,
@@ -44,6 +49,24 @@ pub enum Module {
     Autolink,
     /// Raw HTML pass-through blocks.
     Html,
+    /// `<@kind:id>` mentions of agents, tasks, tools and other entities.
+    Mentions,
+}
+
+impl Module {
+    /// Name of the module as written in the `@modules` directive.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Module::Tables => "tables",
+            Module::Footnotes => "footnotes",
+            Module::Math => "math",
+            Module::Tasks => "tasks",
+            Module::Strikethrough => "strikethrough",
+            Module::Autolink => "autolink",
+            Module::Html => "html",
+            Module::Mentions => "mentions",
+        }
+    }
 }
 
 /// A parsed LiteDoc document.
@@ -52,6 +75,7 @@ pub enum Module {
 /// It preserves the parsing profile, enabled modules, optional metadata,
 /// and all content blocks.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Document<'a> {
     /// The parsing profile used (may differ from parser default if `@profile` directive present).
     pub profile: Profile,
@@ -65,11 +89,32 @@ pub struct Document<'a> {
     pub span: Span,
 }
 
+impl Document<'_> {
+    /// All mentions in the document, in source order.
+    ///
+    /// Mentions are collected from every block, including nested containers,
+    /// table cells and link labels.
+    pub fn mentions(&self) -> Vec<&Mention<'_>> {
+        struct Mentions<'ast>(Vec<&'ast Mention<'ast>>);
+
+        impl<'ast> Visit<'ast> for Mentions<'ast> {
+            fn visit_mention(&mut self, mention: &'ast Mention<'ast>) {
+                self.0.push(mention);
+            }
+        }
+
+        let mut mentions = Mentions(Vec::new());
+        mentions.visit_document(self);
+        mentions.0
+    }
+}
+
 /// Document metadata from the `--- meta` block.
 ///
 /// Metadata provides key-value pairs for document properties like
 /// title, author, date, tags, etc.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Metadata<'a> {
     /// Key-value entries in declaration order.
     pub entries: Vec<(CowStr<'a>, AttrValue<'a>)>,
@@ -86,6 +131,8 @@ pub struct Metadata<'a> {
 /// - Decimals → `Float`
 /// - `[a, b, c]` → `List`
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
 pub enum AttrValue<'a> {
     /// String value (quotes stripped).
     Str(CowStr<'a>),
@@ -99,11 +146,49 @@ pub enum AttrValue<'a> {
     List(Vec<AttrValue<'a>>),
 }
 
+impl<'a> From<&'a str> for AttrValue<'a> {
+    fn from(s: &'a str) -> Self {
+        AttrValue::Str(s.into())
+    }
+}
+
+impl From<String> for AttrValue<'_> {
+    fn from(s: String) -> Self {
+        AttrValue::Str(s.into())
+    }
+}
+
+impl From<bool> for AttrValue<'_> {
+    fn from(b: bool) -> Self {
+        AttrValue::Bool(b)
+    }
+}
+
+impl From<i64> for AttrValue<'_> {
+    fn from(i: i64) -> Self {
+        AttrValue::Int(i)
+    }
+}
+
+impl From<f64> for AttrValue<'_> {
+    fn from(f: f64) -> Self {
+        AttrValue::Float(f)
+    }
+}
+
+impl<'a> From<Vec<AttrValue<'a>>> for AttrValue<'a> {
+    fn from(items: Vec<AttrValue<'a>>) -> Self {
+        AttrValue::List(items)
+    }
+}
+
 /// Block-level AST nodes.
 ///
 /// Blocks are the primary structural elements of a document.
 /// Each variant represents a distinct block type with its own structure.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum Block<'a> {
     /// Section heading (levels 1-6).
     Heading(Heading<'a>),
@@ -123,21 +208,31 @@ pub enum Block<'a> {
     Table(Table<'a>),
     /// Footnote definitions.
     Footnotes(Footnotes<'a>),
+    /// Sources that `[@key]` citations refer to.
+    Sources(Sources<'a>),
     /// Mathematical equation (inline or display).
     Math(MathBlock<'a>),
     /// Horizontal rule / thematic break.
-    ThematicBreak(Span),
+    ThematicBreak(
+        #[cfg_attr(feature = "serde", serde(with = "crate::span::serde_span_field"))] Span,
+    ),
     /// Raw HTML content (when HTML module enabled).
     Html(HtmlBlock<'a>),
     /// Unparsed/unknown block content (error recovery).
     Raw(RawBlock<'a>),
+    /// User-defined block registered with `Parser::register_block`.
+    Custom(CustomBlock<'a>),
 }
 
 /// Section heading with level and inline content.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Heading<'a> {
     /// Heading level (1-6).
     pub level: u8,
+    /// Anchor id: an explicit `{#id}`, or else a unique slug of the text.
+    /// `None` when the text has nothing to slug.
+    pub id: Option<CowStr<'a>>,
     /// Inline content (may include formatting).
     pub content: Vec<Inline<'a>>,
     /// Source span.
@@ -146,6 +241,7 @@ pub struct Heading<'a> {
 
 /// Text paragraph containing inline elements.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Paragraph<'a> {
     /// Inline content with formatting.
     pub content: Vec<Inline<'a>>,
@@ -155,6 +251,8 @@ pub struct Paragraph<'a> {
 
 /// List ordering style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum ListKind {
     /// Numbered list (1. 2. 3.).
     Ordered,
@@ -164,11 +262,14 @@ pub enum ListKind {
 
 /// A list block containing multiple items.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct List<'a> {
     /// Ordered or unordered.
     pub kind: ListKind,
     /// Starting number for ordered lists.
     pub start: Option<u64>,
+    /// All attributes from the `::list` opener.
+    pub attrs: AttrMap<'a>,
     /// List items.
     pub items: Vec<ListItem<'a>>,
     /// Source span.
@@ -177,7 +278,11 @@ pub struct List<'a> {
 
 /// A single list item (may contain nested blocks).
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ListItem<'a> {
+    /// Task checkbox state: `Some(true)` for `[x]`, `Some(false)` for `[ ]`,
+    /// `None` for a plain item.
+    pub checked: Option<bool>,
     /// Content blocks within the item.
     pub blocks: Vec<Block<'a>>,
     /// Source span.
@@ -186,9 +291,12 @@ pub struct ListItem<'a> {
 
 /// Fenced code block with syntax highlighting hint.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CodeBlock<'a> {
     /// Language identifier (e.g., "rust", "python").
     pub lang: CowStr<'a>,
+    /// Info-string attributes after the language (e.g., `title="x.py" lines=3-5`).
+    pub attrs: AttrMap<'a>,
     /// Raw code content.
     pub content: CowStr<'a>,
     /// Source span.
@@ -197,11 +305,14 @@ pub struct CodeBlock<'a> {
 
 /// Callout/admonition block for notes, warnings, etc.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Callout<'a> {
     /// Callout type (note, warning, info, tip, etc.).
     pub kind: CowStr<'a>,
     /// Optional title override.
     pub title: Option<CowStr<'a>>,
+    /// All attributes from the `::callout` opener, including `type` and `title`.
+    pub attrs: AttrMap<'a>,
     /// Content blocks.
     pub blocks: Vec<Block<'a>>,
     /// Source span.
@@ -210,7 +321,10 @@ pub struct Callout<'a> {
 
 /// Block quotation.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Quote<'a> {
+    /// All attributes from the `::quote` opener.
+    pub attrs: AttrMap<'a>,
     /// Quoted content blocks.
     pub blocks: Vec<Block<'a>>,
     /// Source span.
@@ -219,6 +333,7 @@ pub struct Quote<'a> {
 
 /// Figure with image and optional caption.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Figure<'a> {
     /// Image source URL or path.
     pub src: CowStr<'a>,
@@ -226,21 +341,45 @@ pub struct Figure<'a> {
     pub alt: CowStr<'a>,
     /// Optional figure caption.
     pub caption: Option<CowStr<'a>>,
+    /// All attributes from the `::figure` opener, including `src`, `alt` and `caption`.
+    pub attrs: AttrMap<'a>,
     /// Source span.
     pub span: Span,
 }
 
 /// Data table with header and body rows.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Table<'a> {
+    /// Per-column alignment from the separator row (empty if there is none).
+    pub alignments: Vec<Alignment>,
+    /// All attributes from the `::table` opener.
+    pub attrs: AttrMap<'a>,
     /// All table rows (first may be header).
     pub rows: Vec<TableRow<'a>>,
     /// Source span.
     pub span: Span,
 }
 
+/// Column alignment from a table separator row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Alignment {
+    /// No alignment given (`---`).
+    #[default]
+    None,
+    /// Left aligned (`:---`).
+    Left,
+    /// Centered (`:---:`).
+    Center,
+    /// Right aligned (`---:`).
+    Right,
+}
+
 /// A single table row.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableRow<'a> {
     /// Cells in this row.
     pub cells: Vec<TableCell<'a>>,
@@ -252,6 +391,7 @@ pub struct TableRow<'a> {
 
 /// A single table cell.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TableCell<'a> {
     /// Cell content (inline elements).
     pub content: Vec<Inline<'a>>,
@@ -261,6 +401,7 @@ pub struct TableCell<'a> {
 
 /// Container for footnote definitions.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Footnotes<'a> {
     /// Footnote definitions.
     pub defs: Vec<FootnoteDef<'a>>,
@@ -270,6 +411,7 @@ pub struct Footnotes<'a> {
 
 /// A single footnote definition.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FootnoteDef<'a> {
     /// Footnote label (e.g., "1", "note").
     pub label: CowStr<'a>,
@@ -279,11 +421,42 @@ pub struct FootnoteDef<'a> {
     pub span: Span,
 }
 
+/// A `::sources` block listing the sources cited in the document.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sources<'a> {
+    /// All attributes from the `::sources` opener.
+    pub attrs: AttrMap<'a>,
+    /// Source entries, in source order.
+    pub entries: Vec<Source<'a>>,
+    /// Source span.
+    pub span: Span,
+}
+
+/// A single `- id key=value ...` source entry.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Source<'a> {
+    /// Key that citations use (`ipcc2023` for `[@ipcc2023]`).
+    pub id: CowStr<'a>,
+    /// Title, from the `title` attribute.
+    pub title: Option<CowStr<'a>>,
+    /// Location, from the `url` attribute.
+    pub url: Option<CowStr<'a>>,
+    /// All attributes of the entry, including `title` and `url`.
+    pub attrs: AttrMap<'a>,
+    /// Source span.
+    pub span: Span,
+}
+
 /// Mathematical equation block (LaTeX).
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MathBlock<'a> {
     /// Whether this is display math (vs inline).
     pub display: bool,
+    /// All attributes from the `::math` opener.
+    pub attrs: AttrMap<'a>,
     /// LaTeX content.
     pub content: CowStr<'a>,
     /// Source span.
@@ -292,6 +465,7 @@ pub struct MathBlock<'a> {
 
 /// Raw HTML block content.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HtmlBlock<'a> {
     /// Raw HTML content.
     pub content: CowStr<'a>,
@@ -301,17 +475,49 @@ pub struct HtmlBlock<'a> {
 
 /// Unparsed block content (for error recovery).
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawBlock<'a> {
+    /// Block name from the opener (e.g., "plan"); empty for a bare `::`.
+    pub name: CowStr<'a>,
+    /// Attributes from the opener, after the block name.
+    pub attrs: AttrMap<'a>,
     /// Raw unparsed content.
     pub content: CowStr<'a>,
     /// Source span.
     pub span: Span,
 }
 
+/// A user-defined `::name` block produced by a registered block handler.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomBlock<'a> {
+    /// Block name from the opener (e.g., "tool_call").
+    pub name: CowStr<'a>,
+    /// All attributes from the opener.
+    pub attrs: AttrMap<'a>,
+    /// Parsed body.
+    pub body: CustomBody<'a>,
+    /// Source span.
+    pub span: Span,
+}
+
+/// Body of a [`CustomBlock`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CustomBody<'a> {
+    /// Body parsed as nested blocks.
+    Blocks(Vec<Block<'a>>),
+    /// Body kept as raw text.
+    Content(CowStr<'a>),
+}
+
 /// Inline-level AST nodes (within paragraphs, headings, etc.).
 ///
 /// Inline elements represent text-level formatting and can be nested.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type"))]
 pub enum Inline<'a> {
     /// Plain text content.
     Text(Text<'a>),
@@ -329,14 +535,21 @@ pub enum Inline<'a> {
     Strikethrough(Strikethrough<'a>),
     /// Footnote reference ([^label]).
     FootnoteRef(FootnoteRef<'a>),
+    /// Citation of a source (`[@key]`).
+    Citation(Citation<'a>),
+    /// Mention of an entity (<@kind:id>), when the mentions module is enabled.
+    Mention(Mention<'a>),
+    /// Inline math (`\( ... \)`), when the math module is enabled.
+    Math(InlineMath<'a>),
     /// Hard line break (explicit).
-    HardBreak(Span),
+    HardBreak(#[cfg_attr(feature = "serde", serde(with = "crate::span::serde_span_field"))] Span),
     /// Soft line break (newline in source).
-    SoftBreak(Span),
+    SoftBreak(#[cfg_attr(feature = "serde", serde(with = "crate::span::serde_span_field"))] Span),
 }
 
 /// Plain text content.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Text<'a> {
     /// The text content.
     pub content: CowStr<'a>,
@@ -346,6 +559,7 @@ pub struct Text<'a> {
 
 /// Emphasized (italic) text.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Emphasis<'a> {
     /// Nested inline content.
     pub content: Vec<Inline<'a>>,
@@ -355,6 +569,7 @@ pub struct Emphasis<'a> {
 
 /// Strong (bold) text.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Strong<'a> {
     /// Nested inline content.
     pub content: Vec<Inline<'a>>,
@@ -364,6 +579,7 @@ pub struct Strong<'a> {
 
 /// Strikethrough text.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Strikethrough<'a> {
     /// Nested inline content.
     pub content: Vec<Inline<'a>>,
@@ -373,6 +589,7 @@ pub struct Strikethrough<'a> {
 
 /// Inline code span.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CodeSpan<'a> {
     /// Code content (not parsed for formatting).
     pub content: CowStr<'a>,
@@ -382,19 +599,80 @@ pub struct CodeSpan<'a> {
 
 /// Hyperlink with label and destination.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Link<'a> {
     /// Link text (may contain nested formatting).
     pub label: Vec<Inline<'a>>,
     /// Link destination URL.
     pub url: CowStr<'a>,
+    /// What kind of destination `url` is.
+    pub kind: LinkKind,
     /// Optional title (for tooltips).
     pub title: Option<CowStr<'a>>,
     /// Source span.
     pub span: Span,
 }
 
+/// Classification of a link destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum LinkKind {
+    /// URL with a scheme (`https://example.com`).
+    Absolute,
+    /// Path relative to the current document (`guide/intro.ld`).
+    Relative,
+    /// Anchor within the current document (`#install`).
+    Fragment,
+    /// Email address (`mailto:team@example.com`).
+    Email,
+}
+
+impl LinkKind {
+    /// Classify a link destination by its syntax alone.
+    pub fn of(url: &str) -> Self {
+        if url.starts_with('#') {
+            LinkKind::Fragment
+        } else if url
+            .get(..7)
+            .is_some_and(|s| s.eq_ignore_ascii_case("mailto:"))
+        {
+            LinkKind::Email
+        } else if url_scheme(url).is_some() {
+            LinkKind::Absolute
+        } else {
+            LinkKind::Relative
+        }
+    }
+
+    /// Lowercase name of the kind.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LinkKind::Absolute => "absolute",
+            LinkKind::Relative => "relative",
+            LinkKind::Fragment => "fragment",
+            LinkKind::Email => "email",
+        }
+    }
+}
+
+/// The scheme of `url`, if it starts with one (`scheme:`).
+///
+/// Schemes are at least two characters so that a Windows drive such as
+/// `C:` reads as a relative path.
+fn url_scheme(url: &str) -> Option<&str> {
+    let colon = url.find(':')?;
+    let scheme = &url[..colon];
+    let mut bytes = scheme.bytes();
+    let valid = scheme.len() >= 2
+        && bytes.next().is_some_and(|b| b.is_ascii_alphabetic())
+        && bytes.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'));
+    valid.then_some(scheme)
+}
+
 /// Automatically detected URL.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AutoLink<'a> {
     /// The URL.
     pub url: CowStr<'a>,
@@ -404,6 +682,7 @@ pub struct AutoLink<'a> {
 
 /// Reference to a footnote.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FootnoteRef<'a> {
     /// Footnote label being referenced.
     pub label: CowStr<'a>,
@@ -411,5 +690,528 @@ pub struct FootnoteRef<'a> {
     pub span: Span,
 }
 
+/// Citation of an entry in a `::sources` block.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Citation<'a> {
+    /// Id of the cited source.
+    pub key: CowStr<'a>,
+    /// Source span.
+    pub span: Span,
+}
+
+/// Mention of an agent, task, tool or other entity.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mention<'a> {
+    /// Entity kind, such as `agent` or `task`.
+    pub kind: CowStr<'a>,
+    /// Entity id within its kind.
+    pub id: CowStr<'a>,
+    /// Source span (including delimiters).
+    pub span: Span,
+}
+
+/// Inline LaTeX math.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InlineMath<'a> {
+    /// Raw LaTeX between the delimiters.
+    pub content: CowStr<'a>,
+    /// Source span (including delimiters).
+    pub span: Span,
+}
+
 /// Borrowed or owned string type for zero-copy parsing.
 pub type CowStr<'a> = std::borrow::Cow<'a, str>;
+
+/// Ordered `key=value` attributes from a block opener, in source order.
+pub type AttrMap<'a> = Vec<(CowStr<'a>, AttrValue<'a>)>;
+
+// Owned conversions: every node can drop its borrow of the input, so parsed
+// documents can be cached or sent across threads after the source is gone.
+
+impl Document<'_> {
+    /// Convert into a document that owns all of its text.
+    ///
+    /// Borrowed strings are copied; spans still refer to the original input.
+    pub fn into_owned(self) -> Document<'static> {
+        Document {
+            profile: self.profile,
+            modules: self.modules,
+            metadata: self.metadata.map(Metadata::into_owned),
+            blocks: owned_blocks(self.blocks),
+            span: self.span,
+        }
+    }
+}
+
+impl Metadata<'_> {
+    /// Convert into metadata that owns all of its text.
+    pub fn into_owned(self) -> Metadata<'static> {
+        Metadata {
+            entries: owned_attrs(self.entries),
+            span: self.span,
+        }
+    }
+}
+
+impl AttrValue<'_> {
+    /// Convert into a value that owns all of its text.
+    pub fn into_owned(self) -> AttrValue<'static> {
+        match self {
+            AttrValue::Str(s) => AttrValue::Str(owned(s)),
+            AttrValue::Bool(b) => AttrValue::Bool(b),
+            AttrValue::Int(i) => AttrValue::Int(i),
+            AttrValue::Float(f) => AttrValue::Float(f),
+            AttrValue::List(items) => {
+                AttrValue::List(items.into_iter().map(AttrValue::into_owned).collect())
+            }
+        }
+    }
+}
+
+impl Block<'_> {
+    /// Source span of the block.
+    pub fn span(&self) -> Span {
+        match self {
+            Block::Heading(h) => h.span,
+            Block::Paragraph(p) => p.span,
+            Block::List(l) => l.span,
+            Block::CodeBlock(c) => c.span,
+            Block::Callout(c) => c.span,
+            Block::Quote(q) => q.span,
+            Block::Figure(f) => f.span,
+            Block::Table(t) => t.span,
+            Block::Footnotes(f) => f.span,
+            Block::Sources(s) => s.span,
+            Block::Math(m) => m.span,
+            Block::ThematicBreak(span) => *span,
+            Block::Html(h) => h.span,
+            Block::Raw(r) => r.span,
+            Block::Custom(c) => c.span,
+        }
+    }
+
+    /// Convert into a block that owns all of its text.
+    pub fn into_owned(self) -> Block<'static> {
+        match self {
+            Block::Heading(h) => Block::Heading(h.into_owned()),
+            Block::Paragraph(p) => Block::Paragraph(p.into_owned()),
+            Block::List(l) => Block::List(l.into_owned()),
+            Block::CodeBlock(c) => Block::CodeBlock(c.into_owned()),
+            Block::Callout(c) => Block::Callout(c.into_owned()),
+            Block::Quote(q) => Block::Quote(q.into_owned()),
+            Block::Figure(f) => Block::Figure(f.into_owned()),
+            Block::Table(t) => Block::Table(t.into_owned()),
+            Block::Footnotes(f) => Block::Footnotes(f.into_owned()),
+            Block::Sources(s) => Block::Sources(s.into_owned()),
+            Block::Math(m) => Block::Math(m.into_owned()),
+            Block::ThematicBreak(span) => Block::ThematicBreak(span),
+            Block::Html(h) => Block::Html(h.into_owned()),
+            Block::Raw(r) => Block::Raw(r.into_owned()),
+            Block::Custom(c) => Block::Custom(c.into_owned()),
+        }
+    }
+}
+
+impl Heading<'_> {
+    /// Convert into a heading that owns all of its text.
+    pub fn into_owned(self) -> Heading<'static> {
+        Heading {
+            level: self.level,
+            id: self.id.map(owned),
+            content: owned_inlines(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl Paragraph<'_> {
+    /// Convert into a paragraph that owns all of its text.
+    pub fn into_owned(self) -> Paragraph<'static> {
+        Paragraph {
+            content: owned_inlines(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl List<'_> {
+    /// Convert into a list that owns all of its text.
+    pub fn into_owned(self) -> List<'static> {
+        List {
+            kind: self.kind,
+            start: self.start,
+            attrs: owned_attrs(self.attrs),
+            items: self.items.into_iter().map(ListItem::into_owned).collect(),
+            span: self.span,
+        }
+    }
+}
+
+impl ListItem<'_> {
+    /// Convert into a list item that owns all of its text.
+    pub fn into_owned(self) -> ListItem<'static> {
+        ListItem {
+            checked: self.checked,
+            blocks: owned_blocks(self.blocks),
+            span: self.span,
+        }
+    }
+}
+
+impl CodeBlock<'_> {
+    /// Convert into a code block that owns all of its text.
+    pub fn into_owned(self) -> CodeBlock<'static> {
+        CodeBlock {
+            lang: owned(self.lang),
+            attrs: owned_attrs(self.attrs),
+            content: owned(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl Callout<'_> {
+    /// Convert into a callout that owns all of its text.
+    pub fn into_owned(self) -> Callout<'static> {
+        Callout {
+            kind: owned(self.kind),
+            title: self.title.map(owned),
+            attrs: owned_attrs(self.attrs),
+            blocks: owned_blocks(self.blocks),
+            span: self.span,
+        }
+    }
+}
+
+impl Quote<'_> {
+    /// Convert into a quote that owns all of its text.
+    pub fn into_owned(self) -> Quote<'static> {
+        Quote {
+            attrs: owned_attrs(self.attrs),
+            blocks: owned_blocks(self.blocks),
+            span: self.span,
+        }
+    }
+}
+
+impl Figure<'_> {
+    /// Convert into a figure that owns all of its text.
+    pub fn into_owned(self) -> Figure<'static> {
+        Figure {
+            src: owned(self.src),
+            alt: owned(self.alt),
+            caption: self.caption.map(owned),
+            attrs: owned_attrs(self.attrs),
+            span: self.span,
+        }
+    }
+}
+
+impl Table<'_> {
+    /// Convert into a table that owns all of its text.
+    pub fn into_owned(self) -> Table<'static> {
+        Table {
+            alignments: self.alignments,
+            attrs: owned_attrs(self.attrs),
+            rows: self.rows.into_iter().map(TableRow::into_owned).collect(),
+            span: self.span,
+        }
+    }
+}
+
+impl TableRow<'_> {
+    /// Convert into a row that owns all of its text.
+    pub fn into_owned(self) -> TableRow<'static> {
+        TableRow {
+            cells: self.cells.into_iter().map(TableCell::into_owned).collect(),
+            header: self.header,
+            span: self.span,
+        }
+    }
+}
+
+impl TableCell<'_> {
+    /// Convert into a cell that owns all of its text.
+    pub fn into_owned(self) -> TableCell<'static> {
+        TableCell {
+            content: owned_inlines(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl Footnotes<'_> {
+    /// Convert into footnotes that own all of their text.
+    pub fn into_owned(self) -> Footnotes<'static> {
+        Footnotes {
+            defs: self.defs.into_iter().map(FootnoteDef::into_owned).collect(),
+            span: self.span,
+        }
+    }
+}
+
+impl FootnoteDef<'_> {
+    /// Convert into a definition that owns all of its text.
+    pub fn into_owned(self) -> FootnoteDef<'static> {
+        FootnoteDef {
+            label: owned(self.label),
+            blocks: owned_blocks(self.blocks),
+            span: self.span,
+        }
+    }
+}
+
+impl Sources<'_> {
+    /// Convert into sources that own all of their text.
+    pub fn into_owned(self) -> Sources<'static> {
+        Sources {
+            attrs: owned_attrs(self.attrs),
+            entries: self.entries.into_iter().map(Source::into_owned).collect(),
+            span: self.span,
+        }
+    }
+}
+
+impl Source<'_> {
+    /// Convert into a source entry that owns all of its text.
+    pub fn into_owned(self) -> Source<'static> {
+        Source {
+            id: owned(self.id),
+            title: self.title.map(owned),
+            url: self.url.map(owned),
+            attrs: owned_attrs(self.attrs),
+            span: self.span,
+        }
+    }
+}
+
+impl MathBlock<'_> {
+    /// Convert into a math block that owns all of its text.
+    pub fn into_owned(self) -> MathBlock<'static> {
+        MathBlock {
+            display: self.display,
+            attrs: owned_attrs(self.attrs),
+            content: owned(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl HtmlBlock<'_> {
+    /// Convert into an HTML block that owns all of its text.
+    pub fn into_owned(self) -> HtmlBlock<'static> {
+        HtmlBlock {
+            content: owned(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl RawBlock<'_> {
+    /// Convert into a raw block that owns all of its text.
+    pub fn into_owned(self) -> RawBlock<'static> {
+        RawBlock {
+            name: owned(self.name),
+            attrs: owned_attrs(self.attrs),
+            content: owned(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl CustomBlock<'_> {
+    /// Convert into a custom block that owns all of its text.
+    pub fn into_owned(self) -> CustomBlock<'static> {
+        CustomBlock {
+            name: owned(self.name),
+            attrs: owned_attrs(self.attrs),
+            body: self.body.into_owned(),
+            span: self.span,
+        }
+    }
+}
+
+impl CustomBody<'_> {
+    /// Convert into a body that owns all of its text.
+    pub fn into_owned(self) -> CustomBody<'static> {
+        match self {
+            CustomBody::Blocks(blocks) => CustomBody::Blocks(owned_blocks(blocks)),
+            CustomBody::Content(content) => CustomBody::Content(owned(content)),
+        }
+    }
+}
+
+impl Inline<'_> {
+    /// Source span of the inline node.
+    pub fn span(&self) -> Span {
+        match self {
+            Inline::Text(t) => t.span,
+            Inline::Emphasis(e) => e.span,
+            Inline::Strong(s) => s.span,
+            Inline::CodeSpan(c) => c.span,
+            Inline::Link(l) => l.span,
+            Inline::AutoLink(a) => a.span,
+            Inline::Strikethrough(s) => s.span,
+            Inline::FootnoteRef(f) => f.span,
+            Inline::Citation(c) => c.span,
+            Inline::Mention(m) => m.span,
+            Inline::Math(m) => m.span,
+            Inline::HardBreak(span) | Inline::SoftBreak(span) => *span,
+        }
+    }
+
+    /// Convert into an inline node that owns all of its text.
+    pub fn into_owned(self) -> Inline<'static> {
+        match self {
+            Inline::Text(t) => Inline::Text(t.into_owned()),
+            Inline::Emphasis(e) => Inline::Emphasis(e.into_owned()),
+            Inline::Strong(s) => Inline::Strong(s.into_owned()),
+            Inline::CodeSpan(c) => Inline::CodeSpan(c.into_owned()),
+            Inline::Link(l) => Inline::Link(l.into_owned()),
+            Inline::AutoLink(a) => Inline::AutoLink(a.into_owned()),
+            Inline::Strikethrough(s) => Inline::Strikethrough(s.into_owned()),
+            Inline::FootnoteRef(f) => Inline::FootnoteRef(f.into_owned()),
+            Inline::Citation(c) => Inline::Citation(c.into_owned()),
+            Inline::Mention(m) => Inline::Mention(m.into_owned()),
+            Inline::Math(m) => Inline::Math(m.into_owned()),
+            Inline::HardBreak(span) => Inline::HardBreak(span),
+            Inline::SoftBreak(span) => Inline::SoftBreak(span),
+        }
+    }
+}
+
+impl Text<'_> {
+    /// Convert into text that owns its content.
+    pub fn into_owned(self) -> Text<'static> {
+        Text {
+            content: owned(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl Emphasis<'_> {
+    /// Convert into emphasis that owns all of its text.
+    pub fn into_owned(self) -> Emphasis<'static> {
+        Emphasis {
+            content: owned_inlines(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl Strong<'_> {
+    /// Convert into strong text that owns all of its text.
+    pub fn into_owned(self) -> Strong<'static> {
+        Strong {
+            content: owned_inlines(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl Strikethrough<'_> {
+    /// Convert into strikethrough that owns all of its text.
+    pub fn into_owned(self) -> Strikethrough<'static> {
+        Strikethrough {
+            content: owned_inlines(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl CodeSpan<'_> {
+    /// Convert into a code span that owns its content.
+    pub fn into_owned(self) -> CodeSpan<'static> {
+        CodeSpan {
+            content: owned(self.content),
+            span: self.span,
+        }
+    }
+}
+
+impl Link<'_> {
+    /// Convert into a link that owns all of its text.
+    pub fn into_owned(self) -> Link<'static> {
+        Link {
+            label: owned_inlines(self.label),
+            url: owned(self.url),
+            kind: self.kind,
+            title: self.title.map(owned),
+            span: self.span,
+        }
+    }
+}
+
+impl AutoLink<'_> {
+    /// Convert into an autolink that owns its URL.
+    pub fn into_owned(self) -> AutoLink<'static> {
+        AutoLink {
+            url: owned(self.url),
+            span: self.span,
+        }
+    }
+}
+
+impl FootnoteRef<'_> {
+    /// Convert into a footnote reference that owns its label.
+    pub fn into_owned(self) -> FootnoteRef<'static> {
+        FootnoteRef {
+            label: owned(self.label),
+            span: self.span,
+        }
+    }
+}
+
+impl Citation<'_> {
+    /// Convert into a citation that owns its key.
+    pub fn into_owned(self) -> Citation<'static> {
+        Citation {
+            key: owned(self.key),
+            span: self.span,
+        }
+    }
+}
+
+impl Mention<'_> {
+    /// Convert into a mention that owns its kind and id.
+    pub fn into_owned(self) -> Mention<'static> {
+        Mention {
+            kind: owned(self.kind),
+            id: owned(self.id),
+            span: self.span,
+        }
+    }
+}
+
+impl InlineMath<'_> {
+    /// Convert into inline math that owns its content.
+    pub fn into_owned(self) -> InlineMath<'static> {
+        InlineMath {
+            content: owned(self.content),
+            span: self.span,
+        }
+    }
+}
+
+fn owned(s: CowStr<'_>) -> CowStr<'static> {
+    std::borrow::Cow::Owned(s.into_owned())
+}
+
+fn owned_attrs(attrs: AttrMap<'_>) -> AttrMap<'static> {
+    attrs
+        .into_iter()
+        .map(|(key, value)| (owned(key), value.into_owned()))
+        .collect()
+}
+
+fn owned_blocks(blocks: Vec<Block<'_>>) -> Vec<Block<'static>> {
+    blocks.into_iter().map(Block::into_owned).collect()
+}
+
+fn owned_inlines(inlines: Vec<Inline<'_>>) -> Vec<Inline<'static>> {
+    inlines.into_iter().map(Inline::into_owned).collect()
+}
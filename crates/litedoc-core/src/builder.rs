@@ -0,0 +1,601 @@
+//! Programmatic construction of documents.
+//!
+//! [`DocumentBuilder`] assembles a [`Document<'static>`] from plain strings,
+//! for callers that produce LiteDoc rather than parse it. Every node gets
+//! [`Span::SYNTHETIC`], headings get the same unique slug ids the parser
+//! assigns, and the modules the content needs (tables, tasks, math, ...) are
+//! enabled, so the result can go straight to [`format_document`] and parses
+//! back into the same tree.
+//!
+//! ```rust
+//! use litedoc_core::builder::{BlockBuilder, DocumentBuilder, InlineBuilder};
+//! use litedoc_core::{format_document, FormatOptions};
+//!
+//! let doc = DocumentBuilder::new()
+//!     .meta("title", "Weekly report")
+//!     .heading(1, "Summary")
+//!     .paragraph(InlineBuilder::new().text("Found ").strong("3").text(" issues."))
+//!     .table([["Issue", "Owner"], ["Flaky test", "ci"]])
+//!     .callout("warning", BlockBuilder::new().paragraph("Deploys are frozen."))
+//!     .build();
+//!
+//! let text = format_document(&doc, &FormatOptions::default());
+//! assert!(text.starts_with("@profile litedoc\n@modules tables\n"));
+//! assert!(text.contains("# Summary\n\nFound **3** issues.\n"));
+//! ```
+//!
+//! [`format_document`]: crate::format_document
+
+use std::collections::HashSet;
+
+use crate::ast::{
+    Alignment, AttrValue, Block, Callout, CodeBlock, CodeSpan, CowStr, Document, Emphasis, Heading,
+    Inline, InlineMath, Link, LinkKind, List, ListItem, ListKind, Mention, Metadata, Module,
+    Paragraph, Profile, Quote, Strikethrough, Strong, Table, TableCell, TableRow, Text,
+};
+use crate::resolve::unique_slug;
+use crate::span::Span;
+use crate::visit::{walk_block, walk_heading_mut, walk_inline, walk_list_item, Visit, VisitMut};
+
+/// Builder for a whole [`Document`].
+///
+/// Block methods append to the top level of the document and mirror those
+/// of [`BlockBuilder`].
+#[derive(Debug, Clone)]
+pub struct DocumentBuilder {
+    profile: Profile,
+    modules: Vec<Module>,
+    metadata: Vec<(CowStr<'static>, AttrValue<'static>)>,
+    blocks: BlockBuilder,
+}
+
+impl Default for DocumentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentBuilder {
+    /// Create an empty LiteDoc-profile document.
+    pub fn new() -> Self {
+        Self {
+            profile: Profile::Litedoc,
+            modules: Vec::new(),
+            metadata: Vec::new(),
+            blocks: BlockBuilder::new(),
+        }
+    }
+
+    /// Set the profile recorded on the document.
+    pub fn with_profile(mut self, profile: Profile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Enable a module even if no content needs it.
+    pub fn with_module(mut self, module: Module) -> Self {
+        if !self.modules.contains(&module) {
+            self.modules.push(module);
+        }
+        self
+    }
+
+    /// Add a metadata entry.
+    pub fn meta(
+        mut self,
+        key: impl Into<CowStr<'static>>,
+        value: impl Into<AttrValue<'static>>,
+    ) -> Self {
+        self.metadata.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add a heading; see [`BlockBuilder::heading`].
+    pub fn heading(self, level: u8, content: impl Into<InlineBuilder>) -> Self {
+        self.with_blocks(|b| b.heading(level, content))
+    }
+
+    /// Add a heading with an explicit id; see [`BlockBuilder::heading_with_id`].
+    pub fn heading_with_id(
+        self,
+        level: u8,
+        content: impl Into<InlineBuilder>,
+        id: impl Into<CowStr<'static>>,
+    ) -> Self {
+        self.with_blocks(|b| b.heading_with_id(level, content, id))
+    }
+
+    /// Add a paragraph; see [`BlockBuilder::paragraph`].
+    pub fn paragraph(self, content: impl Into<InlineBuilder>) -> Self {
+        self.with_blocks(|b| b.paragraph(content))
+    }
+
+    /// Add a bulleted list; see [`BlockBuilder::list`].
+    pub fn list<I: Into<InlineBuilder>>(self, items: impl IntoIterator<Item = I>) -> Self {
+        self.with_blocks(|b| b.list(items))
+    }
+
+    /// Add a numbered list; see [`BlockBuilder::ordered_list`].
+    pub fn ordered_list<I: Into<InlineBuilder>>(self, items: impl IntoIterator<Item = I>) -> Self {
+        self.with_blocks(|b| b.ordered_list(items))
+    }
+
+    /// Add a task list; see [`BlockBuilder::task_list`].
+    pub fn task_list<I: Into<InlineBuilder>>(
+        self,
+        items: impl IntoIterator<Item = (bool, I)>,
+    ) -> Self {
+        self.with_blocks(|b| b.task_list(items))
+    }
+
+    /// Add a list of multi-block items; see [`BlockBuilder::list_of`].
+    pub fn list_of(self, kind: ListKind, items: impl IntoIterator<Item = BlockBuilder>) -> Self {
+        self.with_blocks(|b| b.list_of(kind, items))
+    }
+
+    /// Add a code block; see [`BlockBuilder::code_block`].
+    pub fn code_block(
+        self,
+        lang: impl Into<CowStr<'static>>,
+        content: impl Into<CowStr<'static>>,
+    ) -> Self {
+        self.with_blocks(|b| b.code_block(lang, content))
+    }
+
+    /// Add a callout; see [`BlockBuilder::callout`].
+    pub fn callout(self, kind: impl Into<CowStr<'static>>, body: BlockBuilder) -> Self {
+        self.with_blocks(|b| b.callout(kind, body))
+    }
+
+    /// Add a titled callout; see [`BlockBuilder::titled_callout`].
+    pub fn titled_callout(
+        self,
+        kind: impl Into<CowStr<'static>>,
+        title: impl Into<CowStr<'static>>,
+        body: BlockBuilder,
+    ) -> Self {
+        self.with_blocks(|b| b.titled_callout(kind, title, body))
+    }
+
+    /// Add a block quote; see [`BlockBuilder::quote`].
+    pub fn quote(self, body: BlockBuilder) -> Self {
+        self.with_blocks(|b| b.quote(body))
+    }
+
+    /// Add a table; see [`BlockBuilder::table`].
+    pub fn table<R, C>(self, rows: impl IntoIterator<Item = R>) -> Self
+    where
+        R: IntoIterator<Item = C>,
+        C: Into<InlineBuilder>,
+    {
+        self.with_blocks(|b| b.table(rows))
+    }
+
+    /// Add a thematic break.
+    pub fn thematic_break(self) -> Self {
+        self.with_blocks(BlockBuilder::thematic_break)
+    }
+
+    /// Add an already constructed block.
+    pub fn block(self, block: Block<'static>) -> Self {
+        self.with_blocks(|b| b.block(block))
+    }
+
+    fn with_blocks(mut self, f: impl FnOnce(BlockBuilder) -> BlockBuilder) -> Self {
+        self.blocks = f(self.blocks);
+        self
+    }
+
+    /// Finish the document.
+    ///
+    /// Headings without an explicit id get a unique slug, in document order,
+    /// and every module the content uses is enabled.
+    pub fn build(self) -> Document<'static> {
+        let mut doc = Document {
+            profile: self.profile,
+            modules: self.modules,
+            metadata: (!self.metadata.is_empty()).then_some(Metadata {
+                entries: self.metadata,
+                span: Span::SYNTHETIC,
+            }),
+            blocks: self.blocks.build(),
+            span: Span::SYNTHETIC,
+        };
+
+        HeadingIds(HashSet::new()).visit_document_mut(&mut doc);
+        let mut used = UsedModules(doc.modules.clone());
+        used.visit_document(&doc);
+        doc.modules = used.0;
+        doc
+    }
+}
+
+/// Builder for a sequence of blocks, such as the body of a callout or of a
+/// list item.
+#[derive(Debug, Clone, Default)]
+pub struct BlockBuilder {
+    blocks: Vec<Block<'static>>,
+}
+
+impl BlockBuilder {
+    /// Create an empty sequence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a heading. Levels are clamped to 1-6.
+    pub fn heading(self, level: u8, content: impl Into<InlineBuilder>) -> Self {
+        self.push_heading(level, content.into(), None)
+    }
+
+    /// Add a heading with an explicit `{#id}` anchor.
+    pub fn heading_with_id(
+        self,
+        level: u8,
+        content: impl Into<InlineBuilder>,
+        id: impl Into<CowStr<'static>>,
+    ) -> Self {
+        self.push_heading(level, content.into(), Some(id.into()))
+    }
+
+    fn push_heading(self, level: u8, content: InlineBuilder, id: Option<CowStr<'static>>) -> Self {
+        self.block(Block::Heading(Heading {
+            level: level.clamp(1, 6),
+            id,
+            content: content.build(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a paragraph.
+    pub fn paragraph(self, content: impl Into<InlineBuilder>) -> Self {
+        self.block(Block::Paragraph(paragraph(content.into())))
+    }
+
+    /// Add a bulleted list with one paragraph per item.
+    pub fn list<I: Into<InlineBuilder>>(self, items: impl IntoIterator<Item = I>) -> Self {
+        let items = items.into_iter().map(|i| (None, i.into()));
+        self.push_list(ListKind::Unordered, simple_items(items))
+    }
+
+    /// Add a numbered list with one paragraph per item.
+    pub fn ordered_list<I: Into<InlineBuilder>>(self, items: impl IntoIterator<Item = I>) -> Self {
+        let items = items.into_iter().map(|i| (None, i.into()));
+        self.push_list(ListKind::Ordered, simple_items(items))
+    }
+
+    /// Add a bulleted list of `(checked, text)` task items.
+    pub fn task_list<I: Into<InlineBuilder>>(
+        self,
+        items: impl IntoIterator<Item = (bool, I)>,
+    ) -> Self {
+        let items = items.into_iter().map(|(c, i)| (Some(c), i.into()));
+        self.push_list(ListKind::Unordered, simple_items(items))
+    }
+
+    /// Add a list whose items hold any blocks, including nested lists.
+    pub fn list_of(self, kind: ListKind, items: impl IntoIterator<Item = BlockBuilder>) -> Self {
+        let items = items
+            .into_iter()
+            .map(|blocks| ListItem {
+                checked: None,
+                blocks: blocks.build(),
+                span: Span::SYNTHETIC,
+            })
+            .collect();
+        self.push_list(kind, items)
+    }
+
+    fn push_list(self, kind: ListKind, items: Vec<ListItem<'static>>) -> Self {
+        let attrs = match kind {
+            ListKind::Ordered => vec![("ordered".into(), AttrValue::Bool(true))],
+            ListKind::Unordered => Vec::new(),
+        };
+        self.block(Block::List(List {
+            kind,
+            start: None,
+            attrs,
+            items,
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a fenced code block. `lang` may be empty.
+    pub fn code_block(
+        self,
+        lang: impl Into<CowStr<'static>>,
+        content: impl Into<CowStr<'static>>,
+    ) -> Self {
+        self.block(Block::CodeBlock(CodeBlock {
+            lang: lang.into(),
+            attrs: Vec::new(),
+            content: content.into(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a callout of the given type (`note`, `warning`, ...).
+    pub fn callout(self, kind: impl Into<CowStr<'static>>, body: BlockBuilder) -> Self {
+        self.push_callout(kind.into(), None, body)
+    }
+
+    /// Add a callout with a title.
+    pub fn titled_callout(
+        self,
+        kind: impl Into<CowStr<'static>>,
+        title: impl Into<CowStr<'static>>,
+        body: BlockBuilder,
+    ) -> Self {
+        self.push_callout(kind.into(), Some(title.into()), body)
+    }
+
+    fn push_callout(
+        self,
+        kind: CowStr<'static>,
+        title: Option<CowStr<'static>>,
+        body: BlockBuilder,
+    ) -> Self {
+        let mut attrs = vec![("type".into(), AttrValue::Str(kind.clone()))];
+        if let Some(title) = &title {
+            attrs.push(("title".into(), AttrValue::Str(title.clone())));
+        }
+        self.block(Block::Callout(Callout {
+            kind,
+            title,
+            attrs,
+            blocks: body.build(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a block quote.
+    pub fn quote(self, body: BlockBuilder) -> Self {
+        self.block(Block::Quote(Quote {
+            attrs: Vec::new(),
+            blocks: body.build(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a table. The first row is the header; rows shorter than the
+    /// widest row are padded with empty cells.
+    pub fn table<R, C>(self, rows: impl IntoIterator<Item = R>) -> Self
+    where
+        R: IntoIterator<Item = C>,
+        C: Into<InlineBuilder>,
+    {
+        let mut rows: Vec<TableRow<'static>> = rows
+            .into_iter()
+            .enumerate()
+            .map(|(i, cells)| TableRow {
+                cells: cells
+                    .into_iter()
+                    .map(|c| TableCell {
+                        content: c.into().build(),
+                        span: Span::SYNTHETIC,
+                    })
+                    .collect(),
+                header: i == 0,
+                span: Span::SYNTHETIC,
+            })
+            .collect();
+        let columns = rows.iter().map(|r| r.cells.len()).max().unwrap_or(0);
+        for row in &mut rows {
+            row.cells.resize_with(columns, || TableCell {
+                content: Vec::new(),
+                span: Span::SYNTHETIC,
+            });
+        }
+        self.block(Block::Table(Table {
+            alignments: vec![Alignment::None; columns],
+            attrs: Vec::new(),
+            rows,
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a thematic break.
+    pub fn thematic_break(self) -> Self {
+        self.block(Block::ThematicBreak(Span::SYNTHETIC))
+    }
+
+    /// Add an already constructed block.
+    pub fn block(mut self, block: Block<'static>) -> Self {
+        self.blocks.push(block);
+        self
+    }
+
+    /// Finish the sequence.
+    pub fn build(self) -> Vec<Block<'static>> {
+        self.blocks
+    }
+}
+
+/// Builder for a run of inline content.
+///
+/// Plain strings convert into a builder holding a single text node, so
+/// methods taking `impl Into<InlineBuilder>` accept either.
+#[derive(Debug, Clone, Default)]
+pub struct InlineBuilder {
+    inlines: Vec<Inline<'static>>,
+}
+
+impl InlineBuilder {
+    /// Create empty inline content.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add literal text; the formatter escapes it as needed.
+    pub fn text(self, text: impl Into<CowStr<'static>>) -> Self {
+        self.inline(Inline::Text(Text {
+            content: text.into(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add `*emphasis*`.
+    pub fn emphasis(self, content: impl Into<InlineBuilder>) -> Self {
+        self.inline(Inline::Emphasis(Emphasis {
+            content: content.into().build(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add `**strong**` text.
+    pub fn strong(self, content: impl Into<InlineBuilder>) -> Self {
+        self.inline(Inline::Strong(Strong {
+            content: content.into().build(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add `~~strikethrough~~` text.
+    pub fn strikethrough(self, content: impl Into<InlineBuilder>) -> Self {
+        self.inline(Inline::Strikethrough(Strikethrough {
+            content: content.into().build(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a code span.
+    pub fn code(self, code: impl Into<CowStr<'static>>) -> Self {
+        self.inline(Inline::CodeSpan(CodeSpan {
+            content: code.into(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a `[[label|url]]` link.
+    pub fn link(self, label: impl Into<InlineBuilder>, url: impl Into<CowStr<'static>>) -> Self {
+        let url = url.into();
+        self.inline(Inline::Link(Link {
+            label: label.into().build(),
+            kind: LinkKind::of(&url),
+            url,
+            title: None,
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add inline `\( math \)`.
+    pub fn math(self, latex: impl Into<CowStr<'static>>) -> Self {
+        self.inline(Inline::Math(InlineMath {
+            content: latex.into(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a `<@kind:id>` mention.
+    pub fn mention(self, kind: impl Into<CowStr<'static>>, id: impl Into<CowStr<'static>>) -> Self {
+        self.inline(Inline::Mention(Mention {
+            kind: kind.into(),
+            id: id.into(),
+            span: Span::SYNTHETIC,
+        }))
+    }
+
+    /// Add a hard line break.
+    pub fn hard_break(self) -> Self {
+        self.inline(Inline::HardBreak(Span::SYNTHETIC))
+    }
+
+    /// Add an already constructed inline node.
+    pub fn inline(mut self, inline: Inline<'static>) -> Self {
+        self.inlines.push(inline);
+        self
+    }
+
+    /// Finish the inline content.
+    pub fn build(self) -> Vec<Inline<'static>> {
+        self.inlines
+    }
+}
+
+impl From<&str> for InlineBuilder {
+    fn from(text: &str) -> Self {
+        InlineBuilder::new().text(text.to_string())
+    }
+}
+
+impl From<String> for InlineBuilder {
+    fn from(text: String) -> Self {
+        InlineBuilder::new().text(text)
+    }
+}
+
+fn paragraph(content: InlineBuilder) -> Paragraph<'static> {
+    Paragraph {
+        content: content.build(),
+        span: Span::SYNTHETIC,
+    }
+}
+
+fn simple_items(
+    items: impl Iterator<Item = (Option<bool>, InlineBuilder)>,
+) -> Vec<ListItem<'static>> {
+    items
+        .map(|(checked, content)| ListItem {
+            checked,
+            blocks: vec![Block::Paragraph(paragraph(content))],
+            span: Span::SYNTHETIC,
+        })
+        .collect()
+}
+
+/// Assigns slug ids to headings without one, as the parser does.
+struct HeadingIds(HashSet<String>);
+
+impl VisitMut<'static> for HeadingIds {
+    fn visit_heading_mut(&mut self, heading: &mut Heading<'static>) {
+        match &heading.id {
+            Some(id) => {
+                self.0.insert(id.to_string());
+            }
+            None => heading.id = unique_slug(&heading.content, &mut self.0).map(CowStr::Owned),
+        }
+        walk_heading_mut(self, heading);
+    }
+}
+
+/// Collects the modules a document's content needs, after any already set.
+struct UsedModules(Vec<Module>);
+
+impl UsedModules {
+    fn add(&mut self, module: Module) {
+        if !self.0.contains(&module) {
+            self.0.push(module);
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UsedModules {
+    fn visit_block(&mut self, block: &'ast Block<'ast>) {
+        match block {
+            Block::Table(_) => self.add(Module::Tables),
+            Block::Footnotes(_) => self.add(Module::Footnotes),
+            Block::Math(_) => self.add(Module::Math),
+            Block::Html(_) => self.add(Module::Html),
+            _ => {}
+        }
+        walk_block(self, block);
+    }
+
+    fn visit_list_item(&mut self, item: &'ast ListItem<'ast>) {
+        if item.checked.is_some() {
+            self.add(Module::Tasks);
+        }
+        walk_list_item(self, item);
+    }
+
+    fn visit_inline(&mut self, inline: &'ast Inline<'ast>) {
+        match inline {
+            Inline::Strikethrough(_) => self.add(Module::Strikethrough),
+            Inline::FootnoteRef(_) => self.add(Module::Footnotes),
+            Inline::Math(_) => self.add(Module::Math),
+            Inline::Mention(_) => self.add(Module::Mentions),
+            _ => {}
+        }
+        walk_inline(self, inline);
+    }
+}
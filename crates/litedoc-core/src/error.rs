@@ -1,4 +1,4 @@
-use crate::span::Span;
+use crate::span::{LineIndex, Span};
 use std::fmt;
 
 /// Error kinds for categorizing parse errors.
@@ -14,6 +14,12 @@ pub enum ParseErrorKind {
     UnknownDirective,
     /// Malformed metadata
     InvalidMetadata,
+    /// Syntax that belongs to a module the document has not enabled
+    DisabledModule,
+    /// Reference to an id that nothing in the document defines
+    UnresolvedReference,
+    /// Definition that nothing in the document refers to
+    UnusedDefinition,
     /// Generic parse error
     Other,
 }
@@ -82,6 +88,46 @@ impl ParseError {
         }
     }
 
+    /// Create an error for syntax whose module is not enabled.
+    pub fn disabled_module(module: &str, span: Option<Span>) -> Self {
+        Self {
+            message: format!("module not enabled: {}", module),
+            span,
+            kind: ParseErrorKind::DisabledModule,
+            recoverable: true,
+        }
+    }
+
+    /// Create an error for a reference that does not resolve.
+    pub fn unresolved_reference(target: &str, span: Option<Span>) -> Self {
+        Self {
+            message: format!("unresolved reference: {}", target),
+            span,
+            kind: ParseErrorKind::UnresolvedReference,
+            recoverable: true,
+        }
+    }
+
+    /// Create an error for a definition that is never referenced.
+    pub fn unused_definition(target: &str, span: Option<Span>) -> Self {
+        Self {
+            message: format!("unused definition: {}", target),
+            span,
+            kind: ParseErrorKind::UnusedDefinition,
+            recoverable: true,
+        }
+    }
+
+    /// Create an error for a malformed query selector.
+    pub fn invalid_selector(reason: &str, span: Option<Span>) -> Self {
+        Self {
+            message: format!("invalid selector: {}", reason),
+            span,
+            kind: ParseErrorKind::InvalidSyntax,
+            recoverable: true,
+        }
+    }
+
     /// Set the error kind.
     pub fn with_kind(mut self, kind: ParseErrorKind) -> Self {
         self.kind = kind;
@@ -93,6 +139,23 @@ impl ParseError {
         self.recoverable = false;
         self
     }
+
+    /// Describe the error with the line and column of its start in the
+    /// input `index` was built from, instead of byte offsets.
+    ///
+    /// Columns count characters, as an editor shows them.
+    pub fn describe(&self, index: &LineIndex<'_>) -> String {
+        match self.span {
+            Some(span) => {
+                let start = index.line_col(span.start);
+                format!(
+                    "{} at line {}, column {}",
+                    self.message, start.line, start.char_column
+                )
+            }
+            None => self.message.clone(),
+        }
+    }
 }
 
 impl fmt::Display for ParseError {
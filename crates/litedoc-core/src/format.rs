@@ -0,0 +1,859 @@
+//! Canonical formatter that turns a `Document` back into LiteDoc text.
+//!
+//! The formatter is the inverse of the parser: for any document produced by
+//! [`Parser`](crate::Parser), formatting and re-parsing yields the same AST
+//! (ignoring spans), and formatting is idempotent.
+//!
+//! Output is native LiteDoc syntax with explicit `::block` fences, regardless
+//! of the profile the document was parsed with. The exception is lists in
+//! `md` documents that have no opener attributes: these are written with
+//! CommonMark markers, as `::list` could only mark them ordered with one.
+//! Text in `md` documents is also escaped wherever CommonMark would start a
+//! block.
+
+use crate::ast::{
+    Alignment, AttrMap, AttrValue, Block, Callout, CodeBlock, CustomBlock, CustomBody, Document,
+    Figure, Footnotes, Heading, Inline, List, ListKind, MathBlock, Metadata, Profile, Quote,
+    RawBlock, Sources, Table, TableRow,
+};
+use crate::parser::{is_md_thematic_break, md_list_marker, split_heading_id};
+use crate::resolve::slugify;
+
+/// Options controlling canonical output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Emit the `@profile` and `@modules` directives (default: true).
+    pub emit_directives: bool,
+    /// Pad table cells so that columns line up (default: false).
+    pub align_tables: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            emit_directives: true,
+            align_tables: false,
+        }
+    }
+}
+
+/// Format a document as canonical LiteDoc text.
+///
+/// # Example
+///
+/// ```rust
+/// use litedoc_core::{format_document, FormatOptions, Parser, Profile};
+///
+/// let input = "@profile litedoc\n\n# Hello\n\nSome **bold** text.\n";
+/// let doc = Parser::new(Profile::Litedoc).parse(input).unwrap();
+/// assert_eq!(format_document(&doc, &FormatOptions::default()), input);
+/// ```
+pub fn format_document(doc: &Document, options: &FormatOptions) -> String {
+    let mut f = Formatter::new(*options, doc.profile);
+    f.write_document(doc);
+    f.out
+}
+
+struct Formatter {
+    out: String,
+    options: FormatOptions,
+    profile: Profile,
+}
+
+impl Formatter {
+    fn new(options: FormatOptions, profile: Profile) -> Self {
+        Self {
+            out: String::with_capacity(1024),
+            options,
+            profile,
+        }
+    }
+
+    fn write_document(&mut self, doc: &Document) {
+        let mut sections: Vec<String> = Vec::with_capacity(3);
+
+        if self.options.emit_directives {
+            let mut directives = String::new();
+            directives.push_str("@profile ");
+            directives.push_str(profile_name(doc.profile));
+            if !doc.modules.is_empty() {
+                directives.push_str("\n@modules ");
+                let names: Vec<&str> = doc.modules.iter().map(|m| m.name()).collect();
+                directives.push_str(&names.join(", "));
+            }
+            sections.push(directives);
+        }
+
+        if let Some(meta) = &doc.metadata {
+            sections.push(format_metadata(meta));
+        }
+
+        let blocks = self.format_blocks(&doc.blocks);
+        if !blocks.is_empty() {
+            sections.push(blocks);
+        }
+
+        self.out.push_str(&sections.join("\n\n"));
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+    }
+
+    /// Context for paragraph and heading text, which depends on the block
+    /// starters of the document's profile.
+    fn flow(&self) -> TextContext {
+        match self.profile {
+            Profile::Litedoc => TextContext::Flow,
+            Profile::Md | Profile::MdStrict => TextContext::MdFlow,
+        }
+    }
+
+    /// Format a sequence of blocks separated by blank lines.
+    fn format_blocks(&self, blocks: &[Block]) -> String {
+        // Adjacent CommonMark lists of the same kind only stay apart if
+        // their markers differ, so every other one uses the alternate marker.
+        let mut previous: Option<(ListKind, bool)> = None;
+        let formatted: Vec<String> = blocks
+            .iter()
+            .map(|block| match block {
+                Block::List(l) if self.md_list_syntax(l) => {
+                    let alternate = previous.is_some_and(|(kind, alt)| kind == l.kind && !alt);
+                    previous = Some((l.kind, alternate));
+                    self.format_md_list(l, alternate)
+                }
+                _ => {
+                    previous = None;
+                    self.format_block(block)
+                }
+            })
+            .collect();
+        formatted.join("\n\n")
+    }
+
+    fn format_block(&self, block: &Block) -> String {
+        match block {
+            Block::Heading(h) => format_heading(h, self.flow()),
+            Block::Paragraph(p) => {
+                let mut out = TextWriter::new();
+                write_inlines(&mut out, &p.content, self.flow());
+                out.text
+            }
+            Block::List(l) => self.format_list(l),
+            Block::CodeBlock(c) => format_code_block(c),
+            Block::Callout(c) => self.format_callout(c),
+            Block::Quote(q) => self.format_quote(q),
+            Block::Figure(f) => format_figure(f),
+            Block::Table(t) => self.format_table(t),
+            Block::Footnotes(f) => self.format_footnotes(f),
+            Block::Sources(s) => format_sources(s),
+            Block::Math(m) => format_math(m),
+            Block::ThematicBreak(_) => "---".to_string(),
+            Block::Html(h) => fenced("::html", &h.content),
+            Block::Raw(r) => format_raw(r),
+            Block::Custom(c) => self.format_custom(c),
+        }
+    }
+
+    /// Whether `list` is written with CommonMark markers, which is how an
+    /// `md` document reads back a list without opener attributes.
+    fn md_list_syntax(&self, list: &List) -> bool {
+        let start = match list.kind {
+            ListKind::Ordered => list.start.is_some_and(|n| n <= MAX_MD_LIST_NUMBER),
+            ListKind::Unordered => list.start.is_none(),
+        };
+        self.flow() == TextContext::MdFlow
+            && list.attrs.is_empty()
+            && !list.items.is_empty()
+            && start
+    }
+
+    /// Format a list with CommonMark markers, continuing each item on lines
+    /// indented to its content. `alternate` picks `*` or `)` as the marker.
+    fn format_md_list(&self, list: &List, alternate: bool) -> String {
+        let start = list.start.unwrap_or(0);
+        let mut out = String::new();
+        for (i, item) in list.items.iter().enumerate() {
+            let mut marker = match list.kind {
+                ListKind::Ordered => {
+                    let number = start
+                        .checked_add(i as u64)
+                        .filter(|&n| n <= MAX_MD_LIST_NUMBER)
+                        .unwrap_or(start);
+                    format!("{}{}", number, if alternate { ')' } else { '.' })
+                }
+                ListKind::Unordered => String::from(if alternate { "*" } else { "-" }),
+            };
+            let indent = " ".repeat(marker.len() + 1);
+            match item.checked {
+                Some(true) => marker.push_str(" [x]"),
+                Some(false) => marker.push_str(" [ ]"),
+                None => {}
+            }
+
+            let body = self.format_blocks(&item.blocks);
+            out.push_str(&marker);
+            for (i, line) in body.lines().enumerate() {
+                if i == 0 {
+                    out.push(' ');
+                } else {
+                    out.push('\n');
+                    if !line.is_empty() {
+                        out.push_str(&indent);
+                    }
+                }
+                out.push_str(line);
+            }
+            out.push('\n');
+        }
+        out.pop();
+        out
+    }
+
+    fn format_list(&self, list: &List) -> String {
+        let mut out = String::from("::list");
+        let start = ("start", list.start.map(|n| n.to_string()));
+        let fields = match list.kind {
+            ListKind::Ordered => vec![("ordered", Some(String::new())), ("unordered", None), start],
+            ListKind::Unordered => vec![("ordered", None), start],
+        };
+        push_attrs(&mut out, &list.attrs, &fields);
+        out.push('\n');
+
+        for item in &list.items {
+            let marker = match item.checked {
+                Some(true) => "- [x] ",
+                Some(false) => "- [ ] ",
+                None => "- ",
+            };
+            // The first line follows the marker; the rest of the item is
+            // continued with `| `, and blank lines inside it become a bare `|`.
+            let body = self.format_blocks(&item.blocks);
+            if body.is_empty() {
+                out.push_str(marker.trim_end());
+                out.push('\n');
+                continue;
+            }
+            for (i, line) in body.lines().enumerate() {
+                if i == 0 {
+                    out.push_str(marker);
+                } else if line.is_empty() {
+                    out.push('|');
+                } else {
+                    out.push_str("| ");
+                }
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+
+        out.push_str("::");
+        out
+    }
+
+    fn format_callout(&self, callout: &Callout) -> String {
+        let mut open = String::from("::callout");
+        push_attrs(
+            &mut open,
+            &callout.attrs,
+            &[
+                (
+                    "type",
+                    (callout.kind != "note" || callout.attrs.iter().any(|(k, _)| k == "type"))
+                        .then(|| format_attr_str(&callout.kind)),
+                ),
+                ("title", callout.title.as_deref().map(quote)),
+            ],
+        );
+        fenced(&open, &self.format_blocks(&callout.blocks))
+    }
+
+    fn format_quote(&self, q: &Quote) -> String {
+        let mut open = String::from("::quote");
+        push_attrs(&mut open, &q.attrs, &[]);
+        fenced(&open, &self.format_blocks(&q.blocks))
+    }
+
+    fn format_custom(&self, custom: &CustomBlock) -> String {
+        let mut open = format!("::{}", custom.name);
+        push_attrs(&mut open, &custom.attrs, &[]);
+        match &custom.body {
+            CustomBody::Blocks(blocks) => fenced(&open, &self.format_blocks(blocks)),
+            CustomBody::Content(content) => fenced(&open, content),
+        }
+    }
+
+    fn format_table(&self, table: &Table) -> String {
+        let rows: Vec<Vec<String>> = table
+            .rows
+            .iter()
+            .map(|row| {
+                row.cells
+                    .iter()
+                    .map(|c| {
+                        let mut out = TextWriter::new();
+                        write_inlines(&mut out, &c.content, TextContext::Cell);
+                        out.text
+                    })
+                    .collect()
+            })
+            .collect();
+        let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        let widths: Vec<usize> = if self.options.align_tables {
+            (0..columns)
+                .map(|col| {
+                    rows.iter()
+                        .filter_map(|r| r.get(col))
+                        .map(|c| c.chars().count())
+                        .max()
+                        .unwrap_or(0)
+                        .max(3)
+                })
+                .collect()
+        } else {
+            vec![3; columns]
+        };
+
+        let header_count = table.rows.iter().take_while(|r| r.header).count();
+        let separator_cols = if table.alignments.is_empty() {
+            table
+                .rows
+                .iter()
+                .find(|r| r.header)
+                .map_or(columns, |r: &TableRow| r.cells.len())
+                .max(1)
+        } else {
+            table.alignments.len()
+        };
+
+        let mut out = String::from("::table");
+        push_attrs(&mut out, &table.attrs, &[]);
+        out.push('\n');
+        if header_count == 0 {
+            out.push_str(&separator_row(&widths, &table.alignments, separator_cols));
+        }
+        for (i, cells) in rows.iter().enumerate() {
+            out.push('|');
+            for (col, cell) in cells.iter().enumerate() {
+                out.push(' ');
+                out.push_str(cell);
+                if self.options.align_tables {
+                    let pad = widths[col].saturating_sub(cell.chars().count());
+                    out.push_str(&" ".repeat(pad));
+                }
+                out.push_str(" |");
+            }
+            out.push('\n');
+            if header_count > 0 && i + 1 == header_count {
+                out.push_str(&separator_row(&widths, &table.alignments, separator_cols));
+            }
+        }
+        out.push_str("::");
+        out
+    }
+
+    fn format_footnotes(&self, footnotes: &Footnotes) -> String {
+        let mut out = String::from("::footnotes\n");
+        for def in &footnotes.defs {
+            out.push_str("[^");
+            out.push_str(&def.label);
+            out.push_str("]: ");
+            out.push_str(&self.format_blocks(&def.blocks));
+            out.push('\n');
+        }
+        out.push_str("::");
+        out
+    }
+}
+
+fn format_heading(heading: &Heading, ctx: TextContext) -> String {
+    let mut out = "#".repeat(heading.level as usize);
+    let mut writer = TextWriter::new();
+    write_inlines(&mut writer, &heading.content, ctx);
+    let mut content = writer.text;
+    // Text that happens to end like `{#id}` must not read back as an id.
+    if let (rest, Some(_)) = split_heading_id(&content) {
+        let brace = content[rest.len()..].find('{').unwrap() + rest.len();
+        content.insert(brace, '\\');
+    }
+    // Slugs are derived again on parse; only other ids are written out.
+    let id = heading
+        .id
+        .as_deref()
+        .filter(|id| *id != slugify(&heading.content));
+    if let Some(id) = id {
+        if !content.is_empty() {
+            content.push(' ');
+        }
+        content.push_str("{#");
+        content.push_str(id);
+        content.push('}');
+    }
+    if !content.is_empty() {
+        out.push(' ');
+        out.push_str(&content);
+    }
+    out
+}
+
+fn format_code_block(code: &CodeBlock) -> String {
+    // The fence must be longer than any backtick-only line in the content.
+    let longest = code
+        .content
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && l.bytes().all(|b| b == b'`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+
+    let mut out = fence.clone();
+    out.push_str(&code.lang);
+    push_attrs(&mut out, &code.attrs, &[]);
+    out.push('\n');
+    if !code.content.is_empty() {
+        out.push_str(&code.content);
+        out.push('\n');
+    }
+    out.push_str(&fence);
+    out
+}
+
+fn format_figure(figure: &Figure) -> String {
+    let mut out = String::from("::figure");
+    push_attrs(
+        &mut out,
+        &figure.attrs,
+        &[
+            ("src", Some(quote(&figure.src))),
+            ("alt", (!figure.alt.is_empty()).then(|| quote(&figure.alt))),
+            ("caption", figure.caption.as_deref().map(quote)),
+        ],
+    );
+    out.push_str("\n::");
+    out
+}
+
+fn format_sources(sources: &Sources) -> String {
+    let mut out = String::from("::sources");
+    push_attrs(&mut out, &sources.attrs, &[]);
+    out.push('\n');
+    for entry in &sources.entries {
+        out.push_str("- ");
+        out.push_str(&entry.id);
+        push_attrs(
+            &mut out,
+            &entry.attrs,
+            &[
+                ("title", entry.title.as_deref().map(quote)),
+                ("url", entry.url.as_deref().map(format_attr_str)),
+            ],
+        );
+        out.push('\n');
+    }
+    out.push_str("::");
+    out
+}
+
+fn format_math(math: &MathBlock) -> String {
+    // Keep whichever spelling of the display flag the source used.
+    let fields = if !math.display {
+        vec![("block", None), ("display", None)]
+    } else if math.attrs.iter().any(|(k, _)| k == "display") {
+        vec![("display", Some(String::new()))]
+    } else {
+        vec![("block", Some(String::new()))]
+    };
+    let mut open = String::from("::math");
+    push_attrs(&mut open, &math.attrs, &fields);
+    fenced(&open, &math.content)
+}
+
+fn format_metadata(meta: &Metadata) -> String {
+    let mut out = String::from("--- meta ---\n");
+    for (key, value) in &meta.entries {
+        out.push_str(key);
+        out.push_str(": ");
+        out.push_str(&format_attr_value(value));
+        out.push('\n');
+    }
+    out.push_str("---");
+    out
+}
+
+/// Append ` key=value` opener attributes.
+///
+/// `fields` holds the keys backed by typed node fields, with their formatted
+/// value (empty for a flag, `None` to omit). These take precedence over the
+/// stored attribute and keep its position; any not present in `attrs` are
+/// appended. Other attributes set to `true` are written as a bare flag.
+fn push_attrs(out: &mut String, attrs: &AttrMap, fields: &[(&str, Option<String>)]) {
+    let mut written = vec![false; fields.len()];
+    let push = |out: &mut String, key: &str, value: &str| {
+        out.push(' ');
+        out.push_str(key);
+        if !value.is_empty() {
+            out.push('=');
+            out.push_str(value);
+        }
+    };
+
+    for (key, value) in attrs {
+        match fields.iter().position(|(k, _)| k == key) {
+            Some(i) => {
+                if let (Some(v), false) = (&fields[i].1, written[i]) {
+                    push(out, key, v);
+                }
+                written[i] = true;
+            }
+            None if *value == AttrValue::Bool(true) => push(out, key, ""),
+            None => push(out, key, &format_attr_value(value)),
+        }
+    }
+    for ((key, value), done) in fields.iter().zip(written) {
+        if let (Some(v), false) = (value, done) {
+            push(out, key, v);
+        }
+    }
+}
+
+/// Write a raw block back with the directive name and attributes it had.
+fn format_raw(raw: &RawBlock) -> String {
+    // An anonymous block without attributes comes out as a bare `::`, which
+    // inside a container would close it instead. The parser only produces
+    // such blocks at the top level, where a `::` cannot close anything.
+    let mut open = format!("::{}", raw.name);
+    push_attrs(&mut open, &raw.attrs, &[]);
+    fenced(&open, &raw.content)
+}
+
+/// Write a fenced block: opener line, body (if any), closing `::`.
+fn fenced(open: &str, body: &str) -> String {
+    let mut out = String::with_capacity(open.len() + body.len() + 4);
+    out.push_str(open);
+    out.push('\n');
+    if !body.is_empty() {
+        out.push_str(body);
+        out.push('\n');
+    }
+    out.push_str("::");
+    out
+}
+
+fn separator_row(widths: &[usize], alignments: &[Alignment], columns: usize) -> String {
+    let mut out = String::from("|");
+    for col in 0..columns {
+        let width = widths.get(col).copied().unwrap_or(3);
+        let (left, right) = match alignments.get(col).copied().unwrap_or_default() {
+            Alignment::None => ("", ""),
+            Alignment::Left => (":", ""),
+            Alignment::Center => (":", ":"),
+            Alignment::Right => ("", ":"),
+        };
+        let dashes = width - left.len() - right.len();
+        out.push(' ');
+        out.push_str(left);
+        out.push_str(&"-".repeat(dashes));
+        out.push_str(right);
+        out.push_str(" |");
+    }
+    out.push('\n');
+    out
+}
+
+/// Format inline content as LiteDoc inline syntax.
+pub fn format_inlines(inlines: &[Inline]) -> String {
+    let mut out = TextWriter::new();
+    write_inlines(&mut out, inlines, TextContext::Flow);
+    out.text
+}
+
+/// Inline output that keeps track of whether only indentation has been
+/// written since the last line break, so escaping block starters does not
+/// rescan the line for every character.
+struct TextWriter {
+    text: String,
+    line_start: bool,
+}
+
+impl TextWriter {
+    fn new() -> Self {
+        Self {
+            text: String::new(),
+            line_start: true,
+        }
+    }
+
+    fn push(&mut self, c: char) {
+        match c {
+            '\n' => self.line_start = true,
+            ' ' | '\t' => {}
+            _ => self.line_start = false,
+        }
+        self.text.push(c);
+    }
+
+    fn push_str(&mut self, s: &str) {
+        let (last_line, had_break) = match s.rfind('\n') {
+            Some(pos) => (&s[pos + 1..], true),
+            None => (s, false),
+        };
+        let blank = last_line.bytes().all(|b| b == b' ' || b == b'\t');
+        self.line_start = blank && (had_break || self.line_start);
+        self.text.push_str(s);
+    }
+}
+
+/// Where inline text is written, which decides what else must be escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextContext {
+    /// Paragraphs and headings, where a line may also start a block.
+    Flow,
+    /// Paragraphs and headings of `md` documents, where CommonMark block
+    /// starters must be escaped as well.
+    MdFlow,
+    /// Table cells, where `|` separates columns.
+    Cell,
+    /// Link labels, where `|` ends the label and `]]` the link.
+    Label,
+}
+
+/// Largest number a CommonMark ordered list marker can hold (nine digits).
+const MAX_MD_LIST_NUMBER: u64 = 999_999_999;
+
+/// Characters that are escaped wherever they appear in text, because they
+/// can open inline syntax.
+const INLINE_ESCAPES: &[char] = &['\\', '`', '*', '[', '~', '<'];
+
+/// Write text content, escaping every character that would otherwise parse
+/// as markup. The output always parses back to the same text.
+fn write_text(out: &mut TextWriter, text: &str, ctx: TextContext) {
+    // A later character on the line that must be escaped, such as the `.`
+    // of an ordered list marker.
+    let mut escape_at = None;
+    for (i, c) in text.char_indices() {
+        let escape = INLINE_ESCAPES.contains(&c)
+            || escape_at == Some(i)
+            || match ctx {
+                TextContext::Flow => out.line_start && starts_block(c, &text[i..]),
+                TextContext::MdFlow if out.line_start => {
+                    // Indentation is dropped on parse, and four columns of it
+                    // would start a code block.
+                    if c == ' ' || c == '\t' {
+                        continue;
+                    }
+                    let line = text[i..].split('\n').next().unwrap_or("");
+                    escape_at = md_block_escape(line).map(|offset| i + offset);
+                    starts_block(c, &text[i..]) || escape_at == Some(i)
+                }
+                TextContext::MdFlow => false,
+                TextContext::Cell => c == '|',
+                TextContext::Label => c == '|' || c == ']',
+            };
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// Whether a line beginning with `c` (followed by `rest`) would start a block
+/// or break a paragraph. Code fences start with an inline escape already.
+fn starts_block(c: char, rest: &str) -> bool {
+    match c {
+        '#' | ':' => true,
+        '-' => rest
+            .split('\n')
+            .next()
+            .is_some_and(|l| l.trim_end() == "---"),
+        _ => false,
+    }
+}
+
+/// Offset of the character to escape so that an `md` line starting with
+/// `line` is not read as a list item, quote, thematic break or setext
+/// underline.
+fn md_block_escape(line: &str) -> Option<usize> {
+    if md_list_marker(line).is_some() {
+        // Bullets are escaped themselves, ordered markers at the `.` or `)`.
+        return Some(line.bytes().take_while(u8::is_ascii_digit).count());
+    }
+    let underline = line.trim_end();
+    let setext = !underline.is_empty()
+        && (underline.bytes().all(|b| b == b'=') || underline.bytes().all(|b| b == b'-'));
+    (line.starts_with('>') || setext || is_md_thematic_break(underline)).then_some(0)
+}
+
+fn write_inlines(out: &mut TextWriter, inlines: &[Inline], ctx: TextContext) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) => write_text(out, &t.content, ctx),
+            Inline::Emphasis(e) => {
+                out.push('*');
+                write_inlines(out, &e.content, ctx);
+                out.push('*');
+            }
+            Inline::Strong(s) => {
+                out.push_str("**");
+                write_inlines(out, &s.content, ctx);
+                out.push_str("**");
+            }
+            Inline::Strikethrough(s) => {
+                out.push_str("~~");
+                write_inlines(out, &s.content, ctx);
+                out.push_str("~~");
+            }
+            Inline::CodeSpan(c) => write_code_span(out, &c.content),
+            Inline::Math(m) => {
+                out.push_str("\\(");
+                out.push_str(&m.content);
+                out.push_str("\\)");
+            }
+            Inline::Link(l) => {
+                out.push_str("[[");
+                let implicit =
+                    matches!(l.label.as_slice(), [Inline::Text(t)] if t.content == l.url);
+                if !implicit {
+                    write_inlines(out, &l.label, TextContext::Label);
+                    out.push('|');
+                }
+                out.push_str(&l.url);
+                if let Some(title) = &l.title {
+                    out.push_str(" \"");
+                    for c in title.chars() {
+                        if matches!(c, '\\' | '"' | '|' | ']') {
+                            out.push('\\');
+                        }
+                        out.push(c);
+                    }
+                    out.push('"');
+                }
+                out.push_str("]]");
+            }
+            Inline::AutoLink(a) => {
+                out.push('<');
+                out.push_str(&a.url);
+                out.push('>');
+            }
+            Inline::FootnoteRef(f) => {
+                out.push_str("[^");
+                out.push_str(&f.label);
+                out.push(']');
+            }
+            Inline::Citation(c) => {
+                out.push_str("[@");
+                out.push_str(&c.key);
+                out.push(']');
+            }
+            Inline::Mention(m) => {
+                out.push_str("<@");
+                out.push_str(&m.kind);
+                out.push(':');
+                out.push_str(&m.id);
+                out.push('>');
+            }
+            Inline::HardBreak(_) => out.push_str("\\\n"),
+            Inline::SoftBreak(_) => out.push('\n'),
+        }
+    }
+}
+
+/// Write a code span with the shortest fence that no backtick run in
+/// `content` matches, padding with spaces where the parser would otherwise
+/// misread or strip the edges.
+fn write_code_span(out: &mut TextWriter, content: &str) {
+    let mut runs = Vec::new();
+    let mut run = 0;
+    for b in content.bytes().chain([b' ']) {
+        if b == b'`' {
+            run += 1;
+        } else if run > 0 {
+            runs.push(run);
+            run = 0;
+        }
+    }
+    let fence_len = (1..).find(|n| !runs.contains(n)).unwrap_or(1);
+    let fence = "`".repeat(fence_len);
+
+    let is_padding = |c: char| c == ' ' || c == '\n';
+    let pad = content.starts_with('`')
+        || content.ends_with('`')
+        || (content.starts_with(is_padding)
+            && content.ends_with(is_padding)
+            && !content.chars().all(is_padding));
+
+    out.push_str(&fence);
+    if pad {
+        out.push(' ');
+    }
+    out.push_str(content);
+    if pad {
+        out.push(' ');
+    }
+    out.push_str(&fence);
+}
+
+/// Format a typed attribute value, quoting strings only when required.
+pub fn format_attr_value(value: &AttrValue) -> String {
+    match value {
+        AttrValue::Str(s) => format_attr_str(s),
+        AttrValue::Bool(b) => b.to_string(),
+        AttrValue::Int(i) => i.to_string(),
+        AttrValue::Float(f) => {
+            let s = f.to_string();
+            // Keep the decimal point so the value re-parses as a float.
+            if s.contains('.') || !f.is_finite() {
+                s
+            } else {
+                format!("{}.0", s)
+            }
+        }
+        AttrValue::List(items) => {
+            let formatted: Vec<String> = items.iter().map(format_attr_value).collect();
+            format!("[{}]", formatted.join(", "))
+        }
+    }
+}
+
+/// Format a string value bare if it re-parses as the same string, quoted otherwise.
+fn format_attr_str(s: &str) -> String {
+    if is_bare_str(s) {
+        s.to_string()
+    } else {
+        quote(s)
+    }
+}
+
+fn is_bare_str(s: &str) -> bool {
+    !s.is_empty()
+        && !s.chars().any(|c| {
+            c.is_whitespace() || matches!(c, ':' | '#' | '[' | ']' | ',' | '"' | '\'' | '\\' | '=')
+        })
+        && s != "true"
+        && s != "false"
+        && s.parse::<i64>().is_err()
+        && s.parse::<f64>().is_err()
+}
+
+/// Quote a string, escaping `"` and `\`.
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+fn profile_name(profile: Profile) -> &'static str {
+    match profile {
+        Profile::Litedoc => "litedoc",
+        Profile::Md => "md",
+        Profile::MdStrict => "md-strict",
+    }
+}
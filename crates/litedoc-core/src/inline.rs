@@ -8,19 +8,218 @@ use std::borrow::Cow;
 use memchr::{memchr, memchr3};
 
 use crate::ast::{
-    AutoLink, CodeSpan, Emphasis, FootnoteRef, Inline, Link, Strikethrough, Strong, Text,
+    AutoLink, Citation, CodeSpan, Emphasis, FootnoteRef, Inline, InlineMath, Link, LinkKind,
+    Mention, Module, Strikethrough, Strong, Text,
 };
 use crate::span::Span;
 
+/// Remove stripped container prefixes from the text of parsed inlines.
+///
+/// `holes` are the byte ranges of `| `, `> ` or indentation prefixes that were
+/// stripped from continuation lines of a multi-line paragraph. Spans keep
+/// pointing at the original source; only content switches to owned strings,
+/// and only for nodes that actually contain a hole.
+pub(crate) fn remove_holes<'a>(inlines: &mut [Inline<'a>], input: &'a str, holes: &[Span]) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(Text { content, span }) => {
+                // Escaped text is already owned, so rebuild it from its span.
+                if let Cow::Owned(_) = content {
+                    *content = unescape(crate::lexer::slice_without(
+                        input, span.start, span.end, holes,
+                    ));
+                } else if let Cow::Borrowed(s) = content {
+                    let start = (s.as_ptr() as usize - input.as_ptr() as usize) as u32;
+                    let end = start + s.len() as u32;
+                    *content = crate::lexer::slice_without(input, start, end, holes);
+                }
+            }
+            Inline::CodeSpan(CodeSpan { content, .. })
+            | Inline::Math(InlineMath { content, .. }) => {
+                if let Cow::Borrowed(s) = content {
+                    let start = (s.as_ptr() as usize - input.as_ptr() as usize) as u32;
+                    let end = start + s.len() as u32;
+                    *content = crate::lexer::slice_without(input, start, end, holes);
+                }
+            }
+            Inline::Emphasis(Emphasis { content, .. })
+            | Inline::Strong(Strong { content, .. })
+            | Inline::Strikethrough(Strikethrough { content, .. }) => {
+                remove_holes(content, input, holes)
+            }
+            Inline::Link(link) => remove_holes(&mut link.label, input, holes),
+            _ => {}
+        }
+    }
+}
+
+/// Whether `c` may follow a backslash as an escape.
+///
+/// Any ASCII punctuation character can be escaped; the backslash is dropped
+/// and the character is kept as literal text. A backslash before anything
+/// else is itself literal.
+#[inline]
+pub fn is_escapable(c: u8) -> bool {
+    c.is_ascii_punctuation()
+}
+
+/// Drop the backslash from every escape sequence in `s`.
+///
+/// Borrowed input stays borrowed unless it actually contains an escape.
+pub(crate) fn unescape(s: Cow<'_, str>) -> Cow<'_, str> {
+    let bytes = s.as_bytes();
+    let mut out: Option<String> = None;
+    let mut copied = 0;
+    let mut i = 0;
+    while let Some(offset) = memchr(b'\\', &bytes[i..]) {
+        let slash = i + offset;
+        if bytes.get(slash + 1).is_some_and(|&c| is_escapable(c)) {
+            // Keep the escaped character, which starts the next copied run.
+            let buf = out.get_or_insert_with(|| String::with_capacity(s.len()));
+            buf.push_str(&s[copied..slash]);
+            copied = slash + 1;
+            i = slash + 2;
+        } else {
+            i = slash + 1;
+        }
+    }
+
+    match out {
+        Some(mut buf) => {
+            buf.push_str(&s[copied..]);
+            Cow::Owned(buf)
+        }
+        None => s,
+    }
+}
+
+/// Whether `b` may appear in a citation or source key: ASCII letters, digits
+/// and `_ - : . /`.
+#[inline]
+pub fn is_citation_key_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b':' | b'.' | b'/')
+}
+
+/// Length of the run of backticks at the start of `bytes`.
+#[inline]
+fn backtick_run(bytes: &[u8]) -> usize {
+    bytes.iter().take_while(|&&b| b == b'`').count()
+}
+
+/// Strip one space or line ending from each end of code span content when
+/// both ends have one and the content is not all spaces. The padding lets
+/// code start or end with a backtick without touching the fence.
+#[inline]
+fn strip_code_padding(content: &str) -> &str {
+    let bytes = content.as_bytes();
+    let padding = |b: &u8| matches!(b, b' ' | b'\n');
+    if bytes.len() >= 2
+        && bytes.first().is_some_and(padding)
+        && bytes.last().is_some_and(padding)
+        && !bytes.iter().all(padding)
+    {
+        &content[1..content.len() - 1]
+    } else {
+        content
+    }
+}
+
+/// Whether `b` may appear in a mention kind: lowercase ASCII letters, digits,
+/// `_` and `-`.
+#[inline]
+pub fn is_mention_kind_byte(b: u8) -> bool {
+    b.is_ascii_lowercase() || b.is_ascii_digit() || matches!(b, b'_' | b'-')
+}
+
+/// Split a link destination into its URL and optional `"title"`.
+///
+/// The title is separated from the URL by whitespace and may use backslash
+/// escapes. Without a well-formed title the whole trimmed destination is the
+/// URL, so stray whitespace is left for URL validation to report.
+fn split_title(dest: &str) -> (&str, Option<Cow<'_, str>>) {
+    let dest = dest.trim();
+    if let Some(ws) = dest.find(char::is_whitespace) {
+        let rest = dest[ws..].trim_start();
+        let closing_escaped = rest[..rest.len().saturating_sub(1)]
+            .bytes()
+            .rev()
+            .take_while(|&b| b == b'\\')
+            .count()
+            % 2
+            == 1;
+        if rest.len() >= 2 && rest.starts_with('"') && rest.ends_with('"') && !closing_escaped {
+            let title = unescape(Cow::Borrowed(&rest[1..rest.len() - 1]));
+            return (&dest[..ws], Some(title));
+        }
+    }
+    (dest, None)
+}
+
+/// Module-gated inline syntax that the parser may switch off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct InlineOptions {
+    /// Recognize `~~strikethrough~~`.
+    pub strikethrough: bool,
+    /// Recognize `<scheme://autolinks>`.
+    pub autolink: bool,
+    /// Recognize `[^label]` footnote references.
+    pub footnotes: bool,
+    /// Recognize `\( ... \)` inline math (otherwise `\(` is an escape).
+    pub math: bool,
+    /// Recognize `<@kind:id>` mentions.
+    pub mentions: bool,
+    /// Also end a line with a hard break after two or more trailing spaces,
+    /// as CommonMark does. A trailing backslash always makes a hard break.
+    pub space_breaks: bool,
+}
+
+impl Default for InlineOptions {
+    fn default() -> Self {
+        Self {
+            strikethrough: true,
+            autolink: true,
+            footnotes: true,
+            math: true,
+            mentions: true,
+            space_breaks: false,
+        }
+    }
+}
+
 /// Parse inline elements from text content - zero allocation version
 #[inline]
 pub fn parse_inlines<'a>(text: &'a str, base_offset: u32, _input: &'a str) -> Vec<Inline<'a>> {
+    parse_inlines_with(
+        text,
+        base_offset,
+        InlineOptions::default(),
+        &mut Vec::new(),
+        &mut Vec::new(),
+    )
+}
+
+/// Parse inline elements with some syntax switched off.
+///
+/// Disabled constructs are kept as literal text, and the module and span of
+/// each one found are appended to `disabled` so the caller can report them.
+/// Inline lists are taken from `spares` while it has any.
+#[inline]
+pub(crate) fn parse_inlines_with<'a>(
+    text: &'a str,
+    base_offset: u32,
+    options: InlineOptions,
+    disabled: &mut Vec<(Module, Span)>,
+    spares: &mut Vec<Vec<Inline<'static>>>,
+) -> Vec<Inline<'a>> {
     if text.is_empty() {
         return Vec::new();
     }
 
-    let mut parser = InlineParser::new(text, base_offset);
-    parser.parse()
+    let mut parser = InlineParser::new(text, base_offset, options, std::mem::take(spares));
+    let inlines = parser.parse();
+    disabled.append(&mut parser.disabled);
+    *spares = parser.spares;
+    inlines
 }
 
 struct InlineParser<'a> {
@@ -28,21 +227,69 @@ struct InlineParser<'a> {
     bytes: &'a [u8],
     pos: usize,
     base_offset: u32,
+    options: InlineOptions,
+    /// Disabled constructs that were left as text.
+    disabled: Vec<(Module, Span)>,
+    /// Emptied inline lists to fill before allocating new ones.
+    spares: Vec<Vec<Inline<'static>>>,
 }
 
 impl<'a> InlineParser<'a> {
     #[inline]
-    fn new(text: &'a str, base_offset: u32) -> Self {
+    fn new(
+        text: &'a str,
+        base_offset: u32,
+        options: InlineOptions,
+        spares: Vec<Vec<Inline<'static>>>,
+    ) -> Self {
         Self {
             text,
             bytes: text.as_bytes(),
             pos: 0,
             base_offset,
+            options,
+            disabled: Vec::new(),
+            spares,
         }
     }
 
+    /// Parse nested content (emphasis, strong, strikethrough) with the same options.
+    #[inline]
+    fn parse_nested(&mut self, content: &'a str, content_start: usize) -> Vec<Inline<'a>> {
+        let mut inner_parser = InlineParser::new(
+            content,
+            self.base_offset + content_start as u32,
+            self.options,
+            std::mem::take(&mut self.spares),
+        );
+        let inner = inner_parser.parse();
+        self.disabled.append(&mut inner_parser.disabled);
+        self.spares = inner_parser.spares;
+        inner
+    }
+
+    /// An empty inline list, reusing spare storage when there is some.
+    #[inline]
+    fn new_inlines(&mut self) -> Vec<Inline<'a>> {
+        self.spares.pop().unwrap_or_else(|| Vec::with_capacity(8))
+    }
+
+    /// Record a disabled construct spanning `start..end` and skip over it as text.
+    #[inline]
+    fn skip_disabled(&mut self, module: Module, start: usize, end: usize) -> bool {
+        self.disabled.push((
+            module,
+            Span::new(
+                self.base_offset + start as u32,
+                self.base_offset + end as u32,
+            ),
+        ));
+        self.pos = end;
+        true
+    }
+
     fn parse(&mut self) -> Vec<Inline<'a>> {
-        let mut inlines = Vec::with_capacity(8);
+        let mut inlines = self.new_inlines();
         let mut text_start = 0;
 
         while self.pos < self.bytes.len() {
@@ -57,12 +304,13 @@ impl<'a> InlineParser<'a> {
             let c = self.bytes[self.pos];
 
             let parsed = match c {
-                b'\\' => self.try_parse_escape(),
+                b'\\' => self.try_parse_backslash(&mut inlines, &mut text_start),
                 b'`' => self.try_parse_code_span(&mut inlines, &mut text_start),
                 b'[' => self.try_parse_bracket(&mut inlines, &mut text_start),
                 b'*' => self.try_parse_asterisk(&mut inlines, &mut text_start),
                 b'~' => self.try_parse_tilde(&mut inlines, &mut text_start),
-                b'<' => self.try_parse_autolink(&mut inlines, &mut text_start),
+                b'<' => self.try_parse_angle(&mut inlines, &mut text_start),
+                b'\n' => self.parse_line_break(&mut inlines, &mut text_start),
                 _ => false,
             };
 
@@ -83,24 +331,39 @@ impl<'a> InlineParser<'a> {
     fn find_next_special(&self) -> usize {
         let remaining = &self.bytes[self.pos..];
 
+        // Markers never need to be found past the end of the current line.
+        let line_end = memchr(b'\n', remaining).unwrap_or(remaining.len());
+        let line = &remaining[..line_end];
+
         // Search for both groups of special characters and take the minimum position.
         // memchr3 is SIMD-accelerated so two calls are still fast.
-        let common = memchr3(b'*', b'`', b'[', remaining);
-        let rare = memchr3(b'\\', b'~', b'<', remaining);
+        let common = memchr3(b'*', b'`', b'[', line);
+        let rare = memchr3(b'\\', b'~', b'<', line);
 
         match (common, rare) {
             (Some(a), Some(b)) => self.pos + a.min(b),
             (Some(a), None) => self.pos + a,
             (None, Some(b)) => self.pos + b,
-            (None, None) => self.bytes.len(),
+            (None, None) => self.pos + line_end,
         }
     }
 
-    /// Create text node borrowing directly from input - ZERO ALLOCATION
+    /// Whether the byte at `pos` is escaped by an odd run of backslashes.
+    #[inline]
+    fn is_escaped(&self, pos: usize) -> bool {
+        let slashes = self.bytes[..pos]
+            .iter()
+            .rev()
+            .take_while(|&&b| b == b'\\')
+            .count();
+        slashes % 2 == 1
+    }
+
+    /// Create a text node, borrowing from input unless it contains an escape.
     #[inline(always)]
     fn make_text_borrowed(&self, start: usize, end: usize) -> Inline<'a> {
         Inline::Text(Text {
-            content: Cow::Borrowed(&self.text[start..end]),
+            content: unescape(Cow::Borrowed(&self.text[start..end])),
             span: Span::new(
                 self.base_offset + start as u32,
                 self.base_offset + end as u32,
@@ -116,16 +379,122 @@ impl<'a> InlineParser<'a> {
         *text_start = self.pos;
     }
 
+    #[inline]
+    fn try_parse_backslash(
+        &mut self,
+        inlines: &mut Vec<Inline<'a>>,
+        text_start: &mut usize,
+    ) -> bool {
+        let math = self.options.math && self.bytes.get(self.pos + 1) == Some(&b'(');
+        (math && self.try_parse_math(inlines, text_start)) || self.try_parse_escape()
+    }
+
+    /// Parse `\( ... \)`. The content is raw: like a code span, no inline
+    /// formatting applies inside it. Escaped pairs such as `\\` are skipped
+    /// so they cannot close the span.
+    #[inline]
+    fn try_parse_math(&mut self, inlines: &mut Vec<Inline<'a>>, text_start: &mut usize) -> bool {
+        let start = self.pos;
+        let content_start = start + 2;
+
+        let mut i = content_start;
+        while let Some(offset) = memchr(b'\\', &self.bytes[i..]) {
+            let slash = i + offset;
+            match self.bytes.get(slash + 1) {
+                Some(b')') => {
+                    self.flush_text(inlines, text_start);
+
+                    inlines.push(Inline::Math(InlineMath {
+                        content: Cow::Borrowed(&self.text[content_start..slash]),
+                        span: Span::new(
+                            self.base_offset + start as u32,
+                            self.base_offset + slash as u32 + 2,
+                        ),
+                    }));
+
+                    self.pos = slash + 2;
+                    *text_start = self.pos;
+                    return true;
+                }
+                Some(_) => i = slash + 2,
+                None => break,
+            }
+        }
+
+        false
+    }
+
+    /// Skip an escape pair so the escaped character is never parsed as a
+    /// marker. The backslash itself is dropped when the text is flushed.
     #[inline]
     fn try_parse_escape(&mut self) -> bool {
-        if self.pos + 1 < self.bytes.len() {
-            self.pos += 1;
+        if self
+            .bytes
+            .get(self.pos + 1)
+            .is_some_and(|&c| is_escapable(c))
+        {
+            self.pos += 2;
             true
         } else {
             false
         }
     }
 
+    /// Turn a line ending into a `SoftBreak`, or a `HardBreak` when the line
+    /// ends with an unescaped backslash (or trailing spaces, with
+    /// `space_breaks`). Spaces around the break are not kept as text.
+    #[inline]
+    fn parse_line_break(&mut self, inlines: &mut Vec<Inline<'a>>, text_start: &mut usize) -> bool {
+        let newline = self.pos;
+        let mut next = newline + 1;
+        while next < self.bytes.len() && matches!(self.bytes[next], b' ' | b'\t') {
+            next += 1;
+        }
+
+        let floor = *text_start;
+        let mut eol = newline;
+        if eol > floor && self.bytes[eol - 1] == b'\r' {
+            eol -= 1;
+        }
+        let trim = |mut end: usize| {
+            while end > floor && matches!(self.bytes[end - 1], b' ' | b'\t') {
+                end -= 1;
+            }
+            end
+        };
+
+        let (hard, break_start, text_end) =
+            if eol > floor && self.bytes[eol - 1] == b'\\' && !self.is_escaped(eol - 1) {
+                (true, eol - 1, trim(eol - 1))
+            } else {
+                let end = trim(eol);
+                let spaces = self.bytes[end..eol].iter().filter(|&&b| b == b' ').count();
+                let hard = self.options.space_breaks && spaces >= 2;
+                (hard, if hard { end } else { eol }, end)
+            };
+
+        if *text_start < text_end {
+            inlines.push(self.make_text_borrowed(*text_start, text_end));
+        }
+
+        // A line ending at the very end of the text is not a break.
+        if next < self.bytes.len() {
+            let span = Span::new(
+                self.base_offset + break_start as u32,
+                self.base_offset + newline as u32 + 1,
+            );
+            inlines.push(if hard {
+                Inline::HardBreak(span)
+            } else {
+                Inline::SoftBreak(span)
+            });
+        }
+
+        self.pos = next;
+        *text_start = next;
+        true
+    }
+
     #[inline]
     fn try_parse_code_span(
         &mut self,
@@ -133,28 +502,39 @@ impl<'a> InlineParser<'a> {
         text_start: &mut usize,
     ) -> bool {
         let start = self.pos;
+        let fence = backtick_run(&self.bytes[start..]);
+        let content_start = start + fence;
+
+        // The span closes at the next backtick run of exactly the same length.
+        let mut i = content_start;
+        let close = loop {
+            let Some(offset) = memchr(b'`', &self.bytes[i..]) else {
+                // Unmatched: the whole opening run is literal text.
+                self.pos = content_start;
+                return true;
+            };
+            let run_start = i + offset;
+            let run = backtick_run(&self.bytes[run_start..]);
+            if run == fence {
+                break run_start;
+            }
+            i = run_start + run;
+        };
 
-        if let Some(close_offset) = memchr(b'`', &self.bytes[start + 1..]) {
-            let close = start + 1 + close_offset;
-
-            self.flush_text(inlines, text_start);
+        self.flush_text(inlines, text_start);
 
-            // Borrow directly from input - ZERO ALLOCATION
-            let content = &self.text[start + 1..close];
-            inlines.push(Inline::CodeSpan(CodeSpan {
-                content: Cow::Borrowed(content),
-                span: Span::new(
-                    self.base_offset + start as u32,
-                    self.base_offset + close as u32 + 1,
-                ),
-            }));
+        // Borrow directly from input - ZERO ALLOCATION
+        inlines.push(Inline::CodeSpan(CodeSpan {
+            content: Cow::Borrowed(strip_code_padding(&self.text[content_start..close])),
+            span: Span::new(
+                self.base_offset + start as u32,
+                self.base_offset + (close + fence) as u32,
+            ),
+        }));
 
-            self.pos = close + 1;
-            *text_start = self.pos;
-            true
-        } else {
-            false
-        }
+        self.pos = close + fence;
+        *text_start = self.pos;
+        true
     }
 
     #[inline]
@@ -166,6 +546,7 @@ impl<'a> InlineParser<'a> {
         match self.bytes[self.pos + 1] {
             b'[' => self.try_parse_link(inlines, text_start),
             b'^' => self.try_parse_footnote_ref(inlines, text_start),
+            b'@' => self.try_parse_citation(inlines, text_start),
             _ => false,
         }
     }
@@ -179,30 +560,46 @@ impl<'a> InlineParser<'a> {
         let mut search_pos = 0;
         while let Some(offset) = memchr(b']', &remaining[search_pos..]) {
             let abs_pos = search_start + search_pos + offset;
-            if abs_pos + 1 < self.bytes.len() && self.bytes[abs_pos + 1] == b']' {
+            if abs_pos + 1 < self.bytes.len()
+                && self.bytes[abs_pos + 1] == b']'
+                && !self.is_escaped(abs_pos)
+            {
                 let content = &self.text[search_start..abs_pos];
 
                 self.flush_text(inlines, text_start);
 
-                // Parse label|url - borrow directly
-                let (label_text, url) = if let Some(pipe_pos) = content.find('|') {
-                    (&content[..pipe_pos], &content[pipe_pos + 1..])
-                } else {
-                    (content, content)
+                // Split label|dest at the first unescaped pipe.
+                let pipe = (search_start..abs_pos)
+                    .find(|&i| self.bytes[i] == b'|' && !self.is_escaped(i))
+                    .map(|i| i - search_start);
+                let dest = match pipe {
+                    Some(pipe_pos) => &content[pipe_pos + 1..],
+                    None => content,
+                };
+                let (url, title) = split_title(dest);
+
+                let label = match pipe {
+                    Some(pipe_pos) => self.parse_nested(&content[..pipe_pos], search_start),
+                    // The implicit label is the URL itself, never parsed as markup.
+                    None => {
+                        let url_start = url.as_ptr() as usize - self.text.as_ptr() as usize;
+                        let mut label = self.new_inlines();
+                        label.push(Inline::Text(Text {
+                            content: Cow::Borrowed(url),
+                            span: Span::new(
+                                self.base_offset + url_start as u32,
+                                self.base_offset + (url_start + url.len()) as u32,
+                            ),
+                        }));
+                        label
+                    }
                 };
-
-                let label = vec![Inline::Text(Text {
-                    content: Cow::Borrowed(label_text),
-                    span: Span::new(
-                        self.base_offset + search_start as u32,
-                        self.base_offset + (search_start + label_text.len()) as u32,
-                    ),
-                })];
 
                 inlines.push(Inline::Link(Link {
                     label,
                     url: Cow::Borrowed(url),
-                    title: None,
+                    kind: LinkKind::of(url),
+                    title,
                     span: Span::new(
                         self.base_offset + start as u32,
                         self.base_offset + abs_pos as u32 + 2,
@@ -232,6 +629,10 @@ impl<'a> InlineParser<'a> {
             let close = label_start + offset;
             let label = &self.text[label_start..close];
 
+            if !self.options.footnotes {
+                return self.skip_disabled(Module::Footnotes, start, close + 1);
+            }
+
             self.flush_text(inlines, text_start);
 
             inlines.push(Inline::FootnoteRef(FootnoteRef {
@@ -250,6 +651,39 @@ impl<'a> InlineParser<'a> {
         }
     }
 
+    /// Parse `[@key]`. Anything that is not a well-formed key stays text.
+    #[inline]
+    fn try_parse_citation(
+        &mut self,
+        inlines: &mut Vec<Inline<'a>>,
+        text_start: &mut usize,
+    ) -> bool {
+        let start = self.pos;
+        let key_start = start + 2;
+        let key_len = self.bytes[key_start..]
+            .iter()
+            .take_while(|&&b| is_citation_key_byte(b))
+            .count();
+        let close = key_start + key_len;
+        if key_len == 0 || self.bytes.get(close) != Some(&b']') {
+            return false;
+        }
+
+        self.flush_text(inlines, text_start);
+
+        inlines.push(Inline::Citation(Citation {
+            key: Cow::Borrowed(&self.text[key_start..close]),
+            span: Span::new(
+                self.base_offset + start as u32,
+                self.base_offset + close as u32 + 1,
+            ),
+        }));
+
+        self.pos = close + 1;
+        *text_start = self.pos;
+        true
+    }
+
     #[inline]
     fn try_parse_asterisk(
         &mut self,
@@ -282,15 +716,14 @@ impl<'a> InlineParser<'a> {
                 && self.bytes[abs_pos + 1] == b'*'
                 && abs_pos > content_start
                 && self.bytes[abs_pos - 1] != b' '
+                && !self.is_escaped(abs_pos)
             {
                 let content = &self.text[content_start..abs_pos];
 
                 self.flush_text(inlines, text_start);
 
                 // Recursively parse inner content
-                let mut inner_parser =
-                    InlineParser::new(content, self.base_offset + content_start as u32);
-                let inner = inner_parser.parse();
+                let inner = self.parse_nested(content, content_start);
 
                 inlines.push(Inline::Strong(Strong {
                     content: inner,
@@ -335,14 +768,15 @@ impl<'a> InlineParser<'a> {
                 continue;
             }
 
-            if abs_pos > content_start && self.bytes[abs_pos - 1] != b' ' {
+            if abs_pos > content_start
+                && self.bytes[abs_pos - 1] != b' '
+                && !self.is_escaped(abs_pos)
+            {
                 let content = &self.text[content_start..abs_pos];
 
                 self.flush_text(inlines, text_start);
 
-                let mut inner_parser =
-                    InlineParser::new(content, self.base_offset + content_start as u32);
-                let inner = inner_parser.parse();
+                let inner = self.parse_nested(content, content_start);
 
                 inlines.push(Inline::Emphasis(Emphasis {
                     content: inner,
@@ -385,14 +819,17 @@ impl<'a> InlineParser<'a> {
                 && self.bytes[abs_pos + 1] == b'~'
                 && abs_pos > content_start
                 && self.bytes[abs_pos - 1] != b' '
+                && !self.is_escaped(abs_pos)
             {
+                if !self.options.strikethrough {
+                    return self.skip_disabled(Module::Strikethrough, start, abs_pos + 2);
+                }
+
                 let content = &self.text[content_start..abs_pos];
 
                 self.flush_text(inlines, text_start);
 
-                let mut inner_parser =
-                    InlineParser::new(content, self.base_offset + content_start as u32);
-                let inner = inner_parser.parse();
+                let inner = self.parse_nested(content, content_start);
 
                 inlines.push(Inline::Strikethrough(Strikethrough {
                     content: inner,
@@ -412,6 +849,57 @@ impl<'a> InlineParser<'a> {
         false
     }
 
+    #[inline]
+    fn try_parse_angle(&mut self, inlines: &mut Vec<Inline<'a>>, text_start: &mut usize) -> bool {
+        let mention = self.options.mentions && self.bytes.get(self.pos + 1) == Some(&b'@');
+        (mention && self.try_parse_mention(inlines, text_start))
+            || self.try_parse_autolink(inlines, text_start)
+    }
+
+    /// Parse `<@kind:id>`. The kind is not checked here; the parser validates
+    /// it against its configured mention kinds.
+    #[inline]
+    fn try_parse_mention(&mut self, inlines: &mut Vec<Inline<'a>>, text_start: &mut usize) -> bool {
+        let start = self.pos;
+        let kind_start = start + 2;
+        let kind_len = self.bytes[kind_start..]
+            .iter()
+            .take_while(|&&b| is_mention_kind_byte(b))
+            .count();
+        let colon = kind_start + kind_len;
+        if kind_len == 0
+            || !self.bytes[kind_start].is_ascii_lowercase()
+            || self.bytes.get(colon) != Some(&b':')
+        {
+            return false;
+        }
+
+        let id_start = colon + 1;
+        let id_len = self.bytes[id_start..]
+            .iter()
+            .take_while(|&&b| is_citation_key_byte(b))
+            .count();
+        let close = id_start + id_len;
+        if id_len == 0 || self.bytes.get(close) != Some(&b'>') {
+            return false;
+        }
+
+        self.flush_text(inlines, text_start);
+
+        inlines.push(Inline::Mention(Mention {
+            kind: Cow::Borrowed(&self.text[kind_start..colon]),
+            id: Cow::Borrowed(&self.text[id_start..close]),
+            span: Span::new(
+                self.base_offset + start as u32,
+                self.base_offset + close as u32 + 1,
+            ),
+        }));
+
+        self.pos = close + 1;
+        *text_start = self.pos;
+        true
+    }
+
     #[inline]
     fn try_parse_autolink(
         &mut self,
@@ -428,6 +916,10 @@ impl<'a> InlineParser<'a> {
                 && !url.contains(' ')
                 && !url.contains('\n')
             {
+                if !self.options.autolink {
+                    return self.skip_disabled(Module::Autolink, start, close + 1);
+                }
+
                 self.flush_text(inlines, text_start);
 
                 inlines.push(Inline::AutoLink(AutoLink {
@@ -9,6 +9,9 @@
 //! - SIMD-accelerated newline scanning via `memchr`
 //! - Peek/consume API for lookahead without allocations
 
+use std::borrow::Cow;
+
+use crate::ast::CowStr;
 use crate::span::Span;
 use memchr::memchr;
 
@@ -45,21 +48,69 @@ impl<'a> Line<'a> {
     pub fn strip_prefix(&self, prefix: &str) -> Option<&'a str> {
         self.text.strip_prefix(prefix)
     }
+
+    /// Width of the leading indentation in columns (tabs count to the next multiple of 4).
+    #[inline]
+    pub fn indent(&self) -> usize {
+        let mut width = 0;
+        for b in self.text.bytes() {
+            match b {
+                b' ' => width += 1,
+                b'\t' => width += 4 - (width % 4),
+                _ => break,
+            }
+        }
+        width
+    }
+
+    /// Drop the first `bytes` bytes of the line, keeping the span in sync.
+    ///
+    /// `bytes` must fall on a character boundary and be at most the line length.
+    #[inline]
+    pub fn skip(&self, bytes: usize) -> Line<'a> {
+        Line {
+            text: &self.text[bytes..],
+            span: Span::new(self.span.start + bytes as u32, self.span.end),
+        }
+    }
+
+    /// Remove up to `columns` columns of leading indentation.
+    #[inline]
+    pub fn strip_indent(&self, columns: usize) -> Line<'a> {
+        let mut width = 0;
+        let mut bytes = 0;
+        for b in self.text.bytes() {
+            if width >= columns {
+                break;
+            }
+            match b {
+                b' ' => width += 1,
+                b'\t' => width += 4 - (width % 4),
+                _ => break,
+            }
+            bytes += 1;
+        }
+        self.skip(bytes)
+    }
 }
 
 /// Line-based lexer for the block parser.
 ///
 /// Provides peek/consume access to lines with efficient SIMD-accelerated
-/// newline scanning.
+/// newline scanning. A lexer can also replay a pre-split list of lines
+/// (see [`Lexer::from_lines`]), which is how container blocks hand their
+/// prefix-stripped content back to the block parser.
 pub struct Lexer<'a> {
     /// The complete input text.
     input: &'a str,
     /// Input as bytes for efficient scanning.
     bytes: &'a [u8],
-    /// Current byte offset.
+    /// Current byte offset (or line index when replaying `lines`).
     offset: usize,
     /// Peeked line (for lookahead).
     peeked: Option<Line<'a>>,
+    /// Pre-split container lines, if this lexer replays them instead of scanning.
+    lines: Option<Vec<Line<'a>>>,
 }
 
 impl<'a> Lexer<'a> {
@@ -71,19 +122,69 @@ impl<'a> Lexer<'a> {
             bytes: input.as_bytes(),
             offset: 0,
             peeked: None,
+            lines: None,
+        }
+    }
+
+    /// Create a lexer that replays already-split lines of `input`.
+    ///
+    /// Line spans keep pointing into `input`, so nodes parsed from container
+    /// content (list items, quotes) still map back to the original source even
+    /// when a prefix such as `| ` or `> ` has been stripped from each line.
+    #[inline]
+    pub fn from_lines(input: &'a str, lines: Vec<Line<'a>>) -> Self {
+        Self {
+            input,
+            bytes: input.as_bytes(),
+            offset: 0,
+            peeked: None,
+            lines: Some(lines),
         }
     }
 
+    /// Whether this lexer replays container lines rather than scanning input.
+    #[inline(always)]
+    pub fn is_nested(&self) -> bool {
+        self.lines.is_some()
+    }
+
+    /// Bytes of the raw source line stripped before `line` started.
+    ///
+    /// Returns `None` for top-level lexers, whose lines always start at the
+    /// beginning of a source line.
+    #[inline]
+    pub fn stripped_prefix(&self, line: &Line) -> Option<Span> {
+        self.lines.as_ref()?;
+        let line_start = line.span.start as usize;
+        let raw_start = memchr::memrchr(b'\n', &self.bytes[..line_start]).map_or(0, |p| p + 1);
+        (raw_start < line_start).then(|| Span::new(raw_start as u32, line.span.start))
+    }
+
     /// Get the current byte offset.
     #[inline(always)]
     pub fn offset(&self) -> u32 {
-        self.offset as u32
+        self.byte_offset() as u32
+    }
+
+    /// Byte offset of the next unread line in the input.
+    #[inline(always)]
+    fn byte_offset(&self) -> usize {
+        match &self.lines {
+            Some(lines) => lines
+                .get(self.offset)
+                .map_or(self.bytes.len(), |l| l.span.start as usize),
+            None => self.offset,
+        }
     }
 
     /// Check if all input has been consumed.
     #[inline(always)]
     pub fn is_eof(&self) -> bool {
-        self.peeked.is_none() && self.offset >= self.bytes.len()
+        let len = match &self.lines {
+            Some(lines) => lines.len(),
+            None => self.bytes.len(),
+        };
+        self.peeked.is_none() && self.offset >= len
     }
 
     /// Peek at the next line without consuming it.
@@ -97,6 +198,19 @@ impl<'a> Lexer<'a> {
         self.peeked.as_ref()
     }
 
+    /// Peek at the line after the next one without consuming either.
+    #[inline]
+    pub fn peek_second(&mut self) -> Option<Line<'a>> {
+        self.peek_line()?;
+        if let Some(lines) = &self.lines {
+            return lines.get(self.offset).copied();
+        }
+        let saved = self.offset;
+        let line = self.read_line();
+        self.offset = saved;
+        line
+    }
+
     /// Consume and return the next line.
     ///
     /// Returns `None` if at end of input.
@@ -127,6 +241,14 @@ impl<'a> Lexer<'a> {
     /// Uses SIMD-accelerated newline scanning via `memchr`.
     #[inline(always)]
     fn read_line(&mut self) -> Option<Line<'a>> {
+        if let Some(lines) = &self.lines {
+            let line = lines.get(self.offset).copied();
+            if line.is_some() {
+                self.offset += 1;
+            }
+            return line;
+        }
+
         if self.offset >= self.bytes.len() {
             return None;
         }
@@ -178,8 +300,41 @@ impl<'a> Lexer<'a> {
     /// Get the remaining unconsumed input.
     #[inline(always)]
     pub fn remaining(&self) -> &'a str {
-        // SAFETY: self.offset is always valid - initialized to 0 and only advanced
-        // by read_line() which sets it to positions after newlines (valid boundaries).
-        unsafe { self.input.get_unchecked(self.offset..) }
+        // SAFETY: byte_offset() is always valid - either a scan offset that is
+        // initialized to 0 and only advanced by read_line() to positions after
+        // newlines, or the start of a replayed line's span (a char boundary).
+        unsafe { self.input.get_unchecked(self.byte_offset()..) }
+    }
+}
+
+/// Slice `start..end` of `input`, leaving out the byte ranges in `holes`.
+///
+/// Holes are the container prefixes (`| `, `> `, indentation) reported by
+/// [`Lexer::stripped_prefix`]. Borrows the input when no hole intersects the range.
+pub(crate) fn slice_without<'a>(
+    input: &'a str,
+    start: u32,
+    end: u32,
+    holes: &[Span],
+) -> CowStr<'a> {
+    let mut inside = holes
+        .iter()
+        .filter(|h| h.start < end && h.end > start)
+        .peekable();
+    if inside.peek().is_none() {
+        return Cow::Borrowed(&input[start as usize..end as usize]);
+    }
+
+    let mut out = String::with_capacity((end - start) as usize);
+    let mut pos = start;
+    for hole in inside {
+        if hole.start > pos {
+            out.push_str(&input[pos as usize..hole.start as usize]);
+        }
+        pos = pos.max(hole.end);
+    }
+    if pos < end {
+        out.push_str(&input[pos as usize..end as usize]);
     }
+    Cow::Owned(out)
 }
@@ -32,19 +32,152 @@
 //! println!("Blocks: {}, Errors: {}", result.document.blocks.len(), result.errors.len());
 //! ```
 //!
+//! ## Modules
+//!
+//! Modules can be preset on the parser and enforced, so that syntax from
+//! modules the document has not enabled is reported instead of parsed:
+//!
+//! ```rust
+//! use litedoc_core::ast::Module;
+//! use litedoc_core::{ParseErrorKind, Parser, Profile};
+//!
+//! let mut parser = Parser::new(Profile::Litedoc)
+//!     .with_modules([Module::Tables])
+//!     .with_module_enforcement(true);
+//! let result = parser.parse_with_recovery("::math\nx^2\n::");
+//! assert_eq!(result.errors.iter().next().unwrap().kind, ParseErrorKind::DisabledModule);
+//! ```
+//!
+//! ## Custom Blocks
+//!
+//! Applications can register their own `::name` blocks. Registered blocks are
+//! parsed into `Block::Custom` instead of being reported as unknown:
+//!
+//! ```rust
+//! use litedoc_core::{Block, BlockHandler, BodyKind, Parser, Profile};
+//!
+//! struct ToolCall;
+//!
+//! impl BlockHandler for ToolCall {
+//!     fn body(&self) -> BodyKind {
+//!         BodyKind::Content
+//!     }
+//! }
+//!
+//! let mut parser = Parser::new(Profile::Litedoc);
+//! parser.register_block("tool_call", ToolCall);
+//! let doc = parser.parse("::tool_call name=search\n{}\n::").unwrap();
+//! assert!(matches!(&doc.blocks[0], Block::Custom(c) if c.name == "tool_call"));
+//! ```
+//!
+//! ## Formatting
+//!
+//! Documents can be written back out as canonical LiteDoc text:
+//!
+//! ```rust
+//! use litedoc_core::{format_document, FormatOptions, Parser, Profile};
+//!
+//! let mut parser = Parser::new(Profile::Litedoc);
+//! let doc = parser.parse("::list\n- one\n- two\n::").unwrap();
+//! let text = format_document(&doc, &FormatOptions::default());
+//! assert!(text.ends_with("::list\n- one\n- two\n::\n"));
+//! ```
+//!
+//! ## Traversal
+//!
+//! The [`visit`] module walks every block and inline node. A pass overrides
+//! only the nodes it cares about:
+//!
+//! ```rust
+//! use litedoc_core::ast::Link;
+//! use litedoc_core::visit::{walk_link_mut, VisitMut};
+//! use litedoc_core::{format_document, FormatOptions, Parser, Profile};
+//!
+//! struct Upgrade;
+//!
+//! impl<'a> VisitMut<'a> for Upgrade {
+//!     fn visit_link_mut(&mut self, link: &mut Link<'a>) {
+//!         if let Some(rest) = link.url.strip_prefix("http://") {
+//!             link.url = format!("https://{rest}").into();
+//!         }
+//!         walk_link_mut(self, link);
+//!     }
+//! }
+//!
+//! let mut parser = Parser::new(Profile::Litedoc);
+//! let mut doc = parser.parse("::footnotes\n[^1]: [[Docs|http://example.com]]\n::").unwrap();
+//! Upgrade.visit_document_mut(&mut doc);
+//! let text = format_document(&doc, &FormatOptions::default());
+//! assert!(text.contains("[[Docs|https://example.com]]"));
+//! ```
+//!
+//! ## Building Documents
+//!
+//! [`DocumentBuilder`] constructs a `Document<'static>` in code, ready to
+//! format. Built nodes carry [`Span::SYNTHETIC`](span::Span::SYNTHETIC):
+//!
+//! ```rust
+//! use litedoc_core::builder::InlineBuilder;
+//! use litedoc_core::{format_document, DocumentBuilder, FormatOptions};
+//!
+//! let doc = DocumentBuilder::new()
+//!     .heading(1, "Status")
+//!     .paragraph(InlineBuilder::new().text("All ").strong("green").text("."))
+//!     .list(["parse", "format"])
+//!     .build();
+//! let text = format_document(&doc, &FormatOptions::default());
+//! assert!(text.ends_with("# Status\n\nAll **green**.\n\n::list\n- parse\n- format\n::\n"));
+//! ```
+//!
+//! ## Queries
+//!
+//! [`Document::select`] finds nodes with CSS-like selectors, described in the
+//! [`query`] module:
+//!
+//! ```rust
+//! use litedoc_core::{Parser, Profile};
+//!
+//! let mut parser = Parser::new(Profile::Litedoc);
+//! let doc = parser.parse("::callout type=warning\nDisk full.\n::\n\n::list\n- a\n- b\n::").unwrap();
+//! assert_eq!(doc.select("callout[type=warning]").unwrap()[0].text(), "Disk full.");
+//! assert_eq!(doc.select("list > item").unwrap().len(), 2);
+//! ```
+//!
+//! ## Reusing Storage
+//!
+//! With the `arena` feature, `Parser::parse_in` takes node vectors from an
+//! `arena::Arena`, and `Arena::reset` takes back those of a finished
+//! document, so parsing many short documents stops allocating node storage.
+//!
+//! ## Serialization
+//!
+//! With the `serde` feature, [`Document`] and every node implement
+//! `Serialize` and `Deserialize` in the canonical JSON shape described in
+//! `LITEDOC_AST.md`. Deserialized documents own their text, so JSON can be
+//! loaded straight into a `Document<'static>`.
+//!
 //! ## Profiles
 //!
 //! - `Profile::Litedoc` - Full native syntax with explicit fencing
 //! - `Profile::Md` - CommonMark + GFM subset
 //! - `Profile::MdStrict` - CommonMark core only
 
+#[cfg(feature = "arena")]
+pub mod arena;
 pub mod ast;
+pub mod builder;
 pub mod error;
+pub mod format;
 pub mod inline;
 pub mod lexer;
 pub mod parser;
+pub mod query;
+pub mod resolve;
 pub mod span;
+pub mod visit;
 
 pub use ast::{Block, Document, Inline, Profile};
+pub use builder::DocumentBuilder;
 pub use error::{ParseError, ParseErrorKind, ParseErrors};
-pub use parser::{ParseResult, Parser};
+pub use format::{format_document, FormatOptions};
+pub use parser::{BlockContext, BlockHandler, BodyKind, ParseResult, Parser};
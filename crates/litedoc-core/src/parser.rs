@@ -4,14 +4,18 @@
 //! Features graceful error recovery to continue parsing after errors.
 
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use crate::ast::{
-    AttrValue, Block, Callout, CodeBlock, CowStr, Document, Figure, FootnoteDef, Footnotes,
-    Heading, HtmlBlock, List, ListItem, ListKind, MathBlock, Metadata, Module, Paragraph, Profile,
-    Quote, RawBlock, Table, TableCell, TableRow,
+    Alignment, AttrMap, AttrValue, Block, Callout, CodeBlock, CowStr, CustomBlock, CustomBody,
+    Document, Figure, FootnoteDef, Footnotes, Heading, HtmlBlock, Inline, LinkKind, List, ListItem,
+    ListKind, MathBlock, Metadata, Module, Paragraph, Profile, Quote, RawBlock, Source, Sources,
+    Table, TableCell, TableRow,
 };
 use crate::error::{ParseError, ParseErrors};
-use crate::lexer::Lexer;
+use crate::inline::InlineOptions;
+use crate::lexer::{slice_without, Lexer, Line};
 use crate::span::Span;
 
 /// Result type for parsing that includes recovered errors.
@@ -35,25 +39,142 @@ impl<'a> ParseResult<'a> {
     }
 }
 
+/// How the body of a registered block is read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyKind {
+    /// Body holds nested blocks; nested fences keep their own closers.
+    #[default]
+    Blocks,
+    /// Body is opaque text up to the first bare `::`.
+    Content,
+}
+
+/// Handler for a user-defined `::name` block.
+///
+/// Register handlers with [`Parser::register_block`]. The default
+/// implementation parses the body according to [`BlockHandler::body`], so a
+/// unit struct with an empty `impl` is enough to accept a block.
+pub trait BlockHandler: Send + Sync {
+    /// How the body is read. Defaults to nested blocks.
+    fn body(&self) -> BodyKind {
+        BodyKind::Blocks
+    }
+
+    /// Build the body of one block.
+    ///
+    /// Handlers may inspect or rewrite `ctx.attrs`; the node keeps whatever is
+    /// left there. Returning `Err(message)` records an `InvalidSyntax` error
+    /// and keeps the block as a `Block::Raw`.
+    fn parse<'a>(&self, ctx: &mut BlockContext<'a, '_>) -> Result<CustomBody<'a>, String> {
+        Ok(match self.body() {
+            BodyKind::Blocks => CustomBody::Blocks(ctx.parse_blocks()),
+            BodyKind::Content => CustomBody::Content(ctx.content()),
+        })
+    }
+}
+
+/// A registered block being parsed, as seen by its [`BlockHandler`].
+pub struct BlockContext<'a, 'p> {
+    /// Block name from the opener.
+    pub name: &'a str,
+    /// Attributes from the opener.
+    pub attrs: AttrMap<'a>,
+    /// Source span of the whole block, including its closer.
+    pub span: Span,
+    input: &'a str,
+    open: Span,
+    lines: Vec<Line<'a>>,
+    holes: Vec<Span>,
+    parser: &'p mut Parser,
+}
+
+impl<'a> BlockContext<'a, '_> {
+    /// Body lines, with any container prefixes already stripped.
+    pub fn lines(&self) -> &[Line<'a>] {
+        &self.lines
+    }
+
+    /// Body as a single string, borrowed from the input when possible.
+    pub fn content(&self) -> CowStr<'a> {
+        let Some(last) = self.lines.last() else {
+            return Cow::Borrowed("");
+        };
+        let start = (self.open.end + 1).min(last.span.end);
+        slice_without(self.input, start, last.span.end, &self.holes)
+    }
+
+    /// Parse the body as nested blocks, with the same parser settings.
+    pub fn parse_blocks(&mut self) -> Vec<Block<'a>> {
+        self.parser
+            .parse_nested_blocks(self.input, self.lines.clone())
+    }
+}
+
 /// LiteDoc parser with configurable profile and error recovery.
 pub struct Parser {
     profile: Profile,
+    /// Profile in effect for the current parse (`@profile` directive or `profile`).
+    active_profile: Profile,
+    /// Modules in effect for the current parse (`@modules` directive or `preset_modules`).
     modules: Vec<Module>,
+    /// Modules enabled when the document has no `@modules` directive.
+    preset_modules: Vec<Module>,
+    /// Whether syntax from disabled modules is rejected.
+    enforce_modules: bool,
+    /// Whether links and citations are resolved after parsing.
+    check_references: bool,
     /// Errors collected during parsing (for recovery mode).
     errors: ParseErrors,
     /// Whether to attempt recovery on errors.
     recover_on_error: bool,
+    /// Handlers for user-defined `::name` blocks.
+    block_handlers: HashMap<String, Arc<dyn BlockHandler>>,
+    /// Heading ids assigned so far in the current parse.
+    heading_ids: HashSet<String>,
+    /// Entity kinds accepted in `<@kind:id>` mentions.
+    mention_kinds: Vec<String>,
+    /// Emptied node vectors to reuse, lent by an arena for one parse.
+    pub(crate) spares: Spares,
+}
+
+/// Emptied node vectors that keep their capacity for the next parse.
+#[derive(Debug, Default)]
+pub(crate) struct Spares {
+    pub(crate) blocks: Vec<Vec<Block<'static>>>,
+    pub(crate) inlines: Vec<Vec<Inline<'static>>>,
+    pub(crate) items: Vec<Vec<ListItem<'static>>>,
+    pub(crate) rows: Vec<Vec<TableRow<'static>>>,
+    pub(crate) cells: Vec<Vec<TableCell<'static>>>,
+    pub(crate) alignments: Vec<Vec<Alignment>>,
+    pub(crate) defs: Vec<Vec<FootnoteDef<'static>>>,
+    pub(crate) sources: Vec<Vec<Source<'static>>>,
+    /// Attribute maps and metadata entries.
+    pub(crate) attrs: Vec<AttrMap<'static>>,
+    /// Items of list-valued attributes.
+    pub(crate) values: Vec<Vec<AttrValue<'static>>>,
+    pub(crate) modules: Vec<Vec<Module>>,
 }
 
+/// Mention kinds accepted by a new parser.
+pub const DEFAULT_MENTION_KINDS: [&str; 3] = ["agent", "task", "tool"];
+
 impl Parser {
     /// Create a new parser with the given profile.
     #[inline]
     pub fn new(profile: Profile) -> Self {
         Self {
             profile,
+            active_profile: profile,
             modules: Vec::new(),
+            preset_modules: Vec::new(),
+            enforce_modules: false,
+            check_references: false,
             errors: ParseErrors::new(),
             recover_on_error: true,
+            block_handlers: HashMap::new(),
+            heading_ids: HashSet::new(),
+            mention_kinds: DEFAULT_MENTION_KINDS.map(String::from).to_vec(),
+            spares: Spares::default(),
         }
     }
 
@@ -67,6 +188,67 @@ impl Parser {
         self
     }
 
+    /// Enable modules for documents that have no `@modules` directive.
+    ///
+    /// A `@modules` directive in the document replaces this set, just as
+    /// `@profile` overrides the parser profile.
+    pub fn with_modules(mut self, modules: impl IntoIterator<Item = Module>) -> Self {
+        self.preset_modules = modules.into_iter().collect();
+        self
+    }
+
+    /// Enforce module gating.
+    ///
+    /// When enabled, syntax that belongs to a module the document has not
+    /// enabled (`::table`, `::math`, `::footnotes`, `::html`, task checkboxes,
+    /// `~~strikethrough~~`, `<autolinks>`, `[^footnote]` references) is kept as
+    /// a raw block or literal text and a `DisabledModule` error is recorded.
+    /// When disabled (default), only HTML and task checkboxes are gated and
+    /// no errors are recorded for them.
+    pub fn with_module_enforcement(mut self, enforce: bool) -> Self {
+        self.enforce_modules = enforce;
+        self
+    }
+
+    /// Check references after parsing.
+    ///
+    /// When enabled, the errors of [`resolve_references`] (internal links and
+    /// citations that do not resolve, sources that are never cited or listed
+    /// twice) are recorded with the parse errors, so `parse` fails on them.
+    /// Disabled by default: references are diagnostics, not syntax.
+    ///
+    /// [`resolve_references`]: crate::resolve::resolve_references
+    pub fn with_reference_checks(mut self, check: bool) -> Self {
+        self.check_references = check;
+        self
+    }
+
+    /// Set the entity kinds accepted in `<@kind:id>` mentions.
+    ///
+    /// Replaces [`DEFAULT_MENTION_KINDS`]. A mention of any other kind is
+    /// still parsed, and an `InvalidSyntax` error is recorded for it.
+    pub fn with_mention_kinds<S: Into<String>>(
+        mut self,
+        kinds: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.mention_kinds = kinds.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Register a handler for a user-defined `::name` block.
+    ///
+    /// Registered blocks are parsed into `Block::Custom` instead of being
+    /// reported as unknown directives. Built-in block names cannot be
+    /// overridden; registering the same name twice replaces the handler.
+    pub fn register_block(
+        &mut self,
+        name: impl Into<String>,
+        handler: impl BlockHandler + 'static,
+    ) -> &mut Self {
+        self.block_handlers.insert(name.into(), Arc::new(handler));
+        self
+    }
+
     /// Parse with error recovery, returning both document and errors.
     #[inline]
     pub fn parse_with_recovery<'a>(&mut self, input: &'a str) -> ParseResult<'a> {
@@ -94,12 +276,21 @@ impl Parser {
     #[inline]
     fn parse_internal<'a>(&mut self, input: &'a str) -> Document<'a> {
         let mut lexer = Lexer::new(input);
+        self.heading_ids.clear();
 
         lexer.skip_blank_lines();
 
         let profile = self.parse_profile_directive(&mut lexer);
-        let modules = self.parse_modules_directive(&mut lexer);
-        self.modules = modules.clone();
+        self.active_profile = profile.unwrap_or(self.profile);
+        let modules = match self.parse_modules_directive(&mut lexer) {
+            Some(modules) => modules,
+            None => {
+                let mut modules = spare(&mut self.spares.modules, 0);
+                modules.extend_from_slice(&self.preset_modules);
+                modules
+            }
+        };
+        self.modules.clone_from(&modules);
 
         lexer.skip_blank_lines();
 
@@ -109,13 +300,19 @@ impl Parser {
 
         let blocks = self.parse_blocks(&mut lexer, input);
 
-        Document {
-            profile: profile.unwrap_or(self.profile),
+        let doc = Document {
+            profile: self.active_profile,
             modules,
             metadata,
             blocks,
             span: Span::new(0, input.len() as u32),
+        };
+        if self.check_references {
+            for error in crate::resolve::resolve_references(&doc) {
+                self.record_error(error);
+            }
         }
+        doc
     }
 
     /// Record an error during parsing.
@@ -130,6 +327,85 @@ impl Parser {
         self.modules.contains(&module)
     }
 
+    /// Whether `module` is declared or implied by the `md` profile's GFM subset.
+    #[inline]
+    fn module_enabled(&self, module: Module) -> bool {
+        self.has_module(module)
+            || (self.active_profile == Profile::Md
+                && matches!(
+                    module,
+                    Module::Tables | Module::Tasks | Module::Strikethrough | Module::Autolink
+                ))
+    }
+
+    /// Whether syntax from `module` should be parsed (always, unless enforcing).
+    #[inline]
+    fn allows(&self, module: Module) -> bool {
+        !self.enforce_modules || self.module_enabled(module)
+    }
+
+    /// Parse inline content, keeping disabled module syntax as text.
+    #[inline]
+    fn parse_inlines<'a>(&mut self, text: &'a str, offset: u32) -> Vec<Inline<'a>> {
+        // `\(` is an ordinary escape without the math module, so inline math
+        // is gated even when modules are not enforced, and never reported.
+        // Mentions are opt-in the same way.
+        let mut options = InlineOptions {
+            math: self.module_enabled(Module::Math),
+            mentions: self.module_enabled(Module::Mentions),
+            space_breaks: self.is_md(),
+            ..InlineOptions::default()
+        };
+        if self.enforce_modules {
+            options.strikethrough = self.module_enabled(Module::Strikethrough);
+            options.autolink = self.module_enabled(Module::Autolink);
+            options.footnotes = self.module_enabled(Module::Footnotes);
+        }
+        let mut disabled = Vec::new();
+        let inlines = crate::inline::parse_inlines_with(
+            text,
+            offset,
+            options,
+            &mut disabled,
+            &mut self.spares.inlines,
+        );
+        for (module, span) in disabled {
+            self.record_error(ParseError::disabled_module(module.name(), Some(span)));
+        }
+        self.check_inlines(&inlines);
+        inlines
+    }
+
+    /// Report links whose destination is malformed for its kind, and
+    /// mentions of unknown kinds.
+    fn check_inlines(&mut self, inlines: &[Inline]) {
+        for inline in inlines {
+            match inline {
+                Inline::Link(link) => {
+                    if let Some(problem) = url_problem(&link.url, link.kind) {
+                        let context = format!("link URL ({problem})");
+                        self.record_error(ParseError::invalid_syntax(&context, Some(link.span)));
+                    }
+                    self.check_inlines(&link.label);
+                }
+                Inline::Mention(m) if !self.mention_kinds.iter().any(|k| *k == m.kind) => {
+                    let context = format!("mention kind (unknown {})", m.kind);
+                    self.record_error(ParseError::invalid_syntax(&context, Some(m.span)));
+                }
+                Inline::Emphasis(e) => self.check_inlines(&e.content),
+                Inline::Strong(s) => self.check_inlines(&s.content),
+                Inline::Strikethrough(s) => self.check_inlines(&s.content),
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether CommonMark block syntax is active (`md` and `md-strict` profiles).
+    #[inline]
+    fn is_md(&self) -> bool {
+        matches!(self.active_profile, Profile::Md | Profile::MdStrict)
+    }
+
     #[inline]
     fn parse_profile_directive(&self, lexer: &mut Lexer) -> Option<Profile> {
         let line = lexer.peek_line()?;
@@ -153,15 +429,11 @@ impl Parser {
     }
 
     #[inline]
-    fn parse_modules_directive(&self, lexer: &mut Lexer) -> Vec<Module> {
-        let line = lexer.peek_line();
-        let trimmed = match line {
-            Some(l) => l.trimmed(),
-            None => return Vec::new(),
-        };
+    fn parse_modules_directive(&mut self, lexer: &mut Lexer) -> Option<Vec<Module>> {
+        let trimmed = lexer.peek_line()?.trimmed();
 
         if let Some(rest) = trimmed.strip_prefix("@modules") {
-            let mut modules = Vec::with_capacity(4);
+            let mut modules = spare(&mut self.spares.modules, 4);
             for part in rest.split(',') {
                 match part.trim() {
                     "tables" => modules.push(Module::Tables),
@@ -171,19 +443,20 @@ impl Parser {
                     "strikethrough" => modules.push(Module::Strikethrough),
                     "autolink" => modules.push(Module::Autolink),
                     "html" => modules.push(Module::Html),
+                    "mentions" => modules.push(Module::Mentions),
                     _ => {}
                 }
             }
             lexer.next_line();
             lexer.skip_blank_lines();
-            modules
+            Some(modules)
         } else {
-            Vec::new()
+            None
         }
     }
 
     #[inline]
-    fn parse_metadata<'a>(&self, lexer: &mut Lexer, input: &'a str) -> Option<Metadata<'a>> {
+    fn parse_metadata<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Metadata<'a>> {
         let start_span;
         {
             let line = lexer.peek_line()?;
@@ -195,7 +468,7 @@ impl Parser {
         }
         lexer.next_line();
 
-        let mut entries: Vec<(CowStr<'a>, AttrValue<'a>)> = Vec::with_capacity(8);
+        let mut entries: AttrMap<'a> = spare(&mut self.spares.attrs, 8);
         let mut end_span = start_span;
 
         loop {
@@ -243,7 +516,7 @@ impl Parser {
     }
 
     #[inline]
-    fn parse_attr_value<'a>(&self, s: &'a str) -> AttrValue<'a> {
+    fn parse_attr_value<'a>(&mut self, s: &'a str) -> AttrValue<'a> {
         if s == "true" {
             return AttrValue::Bool(true);
         }
@@ -267,26 +540,32 @@ impl Parser {
             }
         }
 
-        let unquoted = if (s.starts_with('"') && s.ends_with('"'))
-            || (s.starts_with('\'') && s.ends_with('\''))
+        if s.len() >= 2
+            && ((s.starts_with('"') && s.ends_with('"'))
+                || (s.starts_with('\'') && s.ends_with('\'')))
         {
-            &s[1..s.len() - 1]
-        } else {
-            s
-        };
+            return AttrValue::Str(unescape_quoted(&s[1..s.len() - 1]));
+        }
 
-        AttrValue::Str(Cow::Borrowed(unquoted))
+        AttrValue::Str(Cow::Borrowed(s))
     }
 
     #[inline]
-    fn parse_list_items<'a>(&self, s: &'a str) -> Vec<AttrValue<'a>> {
-        let mut items = Vec::with_capacity(4);
+    fn parse_list_items<'a>(&mut self, s: &'a str) -> Vec<AttrValue<'a>> {
+        let mut items = spare(&mut self.spares.values, 4);
         let mut start = 0;
         let mut in_quotes = false;
         let bytes = s.as_bytes();
 
+        let mut escaped = false;
+
         for i in 0..bytes.len() {
+            if escaped {
+                escaped = false;
+                continue;
+            }
             match bytes[i] {
+                b'\\' if in_quotes => escaped = true,
                 b'"' | b'\'' => in_quotes = !in_quotes,
                 b',' if !in_quotes => {
                     let item = s[start..i].trim();
@@ -307,9 +586,61 @@ impl Parser {
         items
     }
 
+    /// Parse space-separated `key=value` attributes.
+    ///
+    /// Values may be quoted (with `\"` and `\\` escapes) and are typed like
+    /// metadata values. A bare key is a flag and maps to `true`.
+    #[inline]
+    fn parse_attrs<'a>(&mut self, s: &'a str) -> AttrMap<'a> {
+        let mut rest = s.trim_start();
+        if rest.is_empty() {
+            // Most openers have no attributes; keep the spares for those that do.
+            return AttrMap::new();
+        }
+        let mut attrs = spare(&mut self.spares.attrs, 0);
+
+        while !rest.is_empty() {
+            let key_end = rest
+                .find(|c: char| c == '=' || c.is_whitespace())
+                .unwrap_or(rest.len());
+            let key = &rest[..key_end];
+            rest = &rest[key_end..];
+
+            let value = match rest.strip_prefix('=') {
+                Some(after) => {
+                    let value_end = attr_value_end(after);
+                    rest = &after[value_end..];
+                    self.parse_attr_value(&after[..value_end])
+                }
+                None => AttrValue::Bool(true),
+            };
+            if !key.is_empty() {
+                attrs.push((Cow::Borrowed(key), value));
+            }
+            rest = rest.trim_start();
+        }
+
+        attrs
+    }
+
+    /// Parse the attributes of a `::name key=value ...` opener line.
+    #[inline]
+    fn parse_opener_attrs<'a>(&mut self, open_line: Span, input: &'a str) -> AttrMap<'a> {
+        let name = opener_name(open_line, input);
+        let text = input[open_line.start as usize..open_line.end as usize].trim();
+        let after_colons = text.strip_prefix("::").unwrap_or(text);
+        self.parse_attrs(&after_colons[name.len()..])
+    }
+
+    /// An empty block list, reusing spare storage when there is some.
+    #[inline]
+    fn new_blocks<'a>(&mut self) -> Vec<Block<'a>> {
+        spare(&mut self.spares.blocks, 16)
+    }
+
     #[inline]
     fn parse_blocks<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Vec<Block<'a>> {
-        let mut blocks = Vec::with_capacity(16);
+        let mut blocks = self.new_blocks();
 
         while !lexer.is_eof() {
             lexer.skip_blank_lines();
@@ -326,14 +657,35 @@ impl Parser {
         blocks
     }
 
+    /// Parse container content (prefix-stripped lines) as a nested block sequence.
+    #[inline]
+    fn parse_nested_blocks<'a>(&mut self, input: &'a str, lines: Vec<Line>) -> Vec<Block<'a>> {
+        let mut lexer = Lexer::from_lines(input, lines);
+        self.parse_blocks(&mut lexer, input)
+    }
+
     #[inline]
     fn parse_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
-        let (first_byte, trimmed_starts_triple, is_hr, starts_colon, span) = {
+        if self.is_md() {
+            if let Some(start) = self.md_block_start(lexer) {
+                return match start {
+                    MdBlockStart::IndentedCode => self.parse_md_indented_code(lexer),
+                    MdBlockStart::ThematicBreak => {
+                        lexer.next_line().map(|l| Block::ThematicBreak(l.span))
+                    }
+                    MdBlockStart::Quote => self.parse_md_quote(lexer, input),
+                    MdBlockStart::List => self.parse_md_list(lexer, input),
+                    MdBlockStart::Table => self.parse_md_table(lexer, input),
+                };
+            }
+        }
+
+        let (first_byte, is_code_fence, is_hr, starts_colon, span) = {
             let line = lexer.peek_line()?;
             let trimmed = line.trimmed();
             (
                 trimmed.as_bytes().first().copied(),
-                trimmed.starts_with("```"),
+                code_fence_open(trimmed).is_some(),
                 trimmed == "---",
                 trimmed.starts_with("::"),
                 line.span,
@@ -342,7 +694,7 @@ impl Parser {
 
         match first_byte {
             Some(b'#') => self.parse_heading(lexer, input),
-            Some(b'`') if trimmed_starts_triple => self.parse_code_block(lexer, input),
+            Some(b'`') | Some(b'~') if is_code_fence => self.parse_code_block(lexer, input),
             Some(b'-') if is_hr => {
                 lexer.next_line();
                 Some(Block::ThematicBreak(span))
@@ -355,14 +707,21 @@ impl Parser {
     #[inline]
     fn parse_heading<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
         let line = lexer.next_line()?;
-        let text = &input[line.span.start as usize..line.span.end as usize];
+        let mut line_start = line.span.start;
+        let mut text = &input[line.span.start as usize..line.span.end as usize];
+        if self.is_md() {
+            // CommonMark allows up to three spaces of indentation.
+            let trimmed = text.trim_start();
+            line_start += (text.len() - trimmed.len()) as u32;
+            text = trimmed;
+        }
         let bytes = text.as_bytes();
 
         let level = bytes.iter().take_while(|&&b| b == b'#').count() as u8;
 
         if level == 0 || level > 6 {
             return Some(Block::Paragraph(Paragraph {
-                content: crate::inline::parse_inlines(text, line.span.start, input),
+                content: self.parse_inlines(text, line_start),
                 span: line.span,
             }));
         }
@@ -370,77 +729,85 @@ impl Parser {
         let rest = &text[level as usize..];
         if !rest.starts_with(' ') && !rest.is_empty() {
             return Some(Block::Paragraph(Paragraph {
-                content: crate::inline::parse_inlines(text, line.span.start, input),
+                content: self.parse_inlines(text, line_start),
                 span: line.span,
             }));
         }
 
-        let content_text = rest.trim_start();
-        let content_offset = line.span.start + (text.len() - content_text.len()) as u32;
+        let mut content_text = rest.trim_start();
+        if self.is_md() {
+            content_text = strip_closing_hashes(content_text);
+        }
+        let (content_text, explicit_id) = split_heading_id(content_text);
+        let content_offset = line_start + (text.len() - rest.trim_start().len()) as u32;
 
+        let content = self.parse_inlines(content_text, content_offset);
         Some(Block::Heading(Heading {
             level,
-            content: crate::inline::parse_inlines(content_text, content_offset, input),
+            id: self.heading_id(explicit_id, &content, line.span),
+            content,
             span: line.span,
         }))
     }
 
     #[inline]
     fn parse_code_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
-        let (start_span, lang_start, lang_end) = {
-            let open_line = lexer.next_line()?;
-            let text = &input[open_line.span.start as usize..open_line.span.end as usize];
-            let trimmed = text.trim();
-            let after_ticks = trimmed.strip_prefix("```").unwrap_or("");
-            let lang = after_ticks.trim();
-
-            // Calculate where lang is in input
-            let lang_offset = if lang.is_empty() {
-                open_line.span.end as usize
-            } else {
-                // Find lang in the line
-                open_line.span.start as usize + text.find(lang).unwrap_or(0)
-            };
-
-            (open_line.span, lang_offset, lang_offset + lang.len())
+        let start_span = lexer.next_line()?.span;
+        let text = &input[start_span.start as usize..start_span.end as usize];
+        let trimmed = text.trim();
+        let (fence_char, fence_len) = code_fence_open(trimmed)?;
+
+        // Info string: an optional language followed by `key=value` attributes.
+        let info = trimmed[fence_len..].trim();
+        let (lang, attr_text) = match info.split_once(char::is_whitespace) {
+            Some((first, rest)) if !first.contains('=') => (first, rest),
+            None if !info.contains('=') => (info, ""),
+            _ => ("", info),
         };
+        let attrs = self.parse_attrs(attr_text);
 
-        let lang = &input[lang_start..lang_end];
+        if lang.is_empty() && self.active_profile == Profile::Litedoc {
+            self.record_error(ParseError::invalid_syntax(
+                "code block (missing language)",
+                Some(start_span),
+            ));
+        }
 
         // Content starts after the opening fence line. Add 1 to skip newline if present,
         // but clamp to input length to handle EOF without trailing newline.
         let content_start = (start_span.end as usize + 1).min(input.len());
         let mut content_end = content_start;
         let mut end_span = start_span;
+        let mut holes: Vec<Span> = Vec::new();
+        let mut closed = false;
 
-        loop {
-            let (is_close, span) = {
-                match lexer.peek_line() {
-                    Some(line) => (line.trimmed() == "```", line.span),
-                    None => break,
-                }
-            };
-
-            if is_close {
-                end_span = span;
-                lexer.next_line();
+        while let Some(line) = lexer.next_line() {
+            end_span = line.span;
+            if code_fence_closes(line.trimmed(), fence_char, fence_len) {
+                closed = true;
                 break;
             }
+            content_end = line.span.end as usize;
+            holes.extend(lexer.stripped_prefix(&line));
+        }
 
-            end_span = span;
-            content_end = span.end as usize;
-            lexer.next_line();
+        if !closed {
+            self.record_error(ParseError::unclosed_delimiter(
+                "code block",
+                Some(start_span),
+            ));
         }
 
         let content = if content_start < content_end && content_end <= input.len() {
-            &input[content_start..content_end]
+            slice_without(input, content_start as u32, content_end as u32, &holes)
         } else {
-            ""
+            Cow::Borrowed("")
         };
 
         Some(Block::CodeBlock(CodeBlock {
             lang: Cow::Borrowed(lang),
-            content: Cow::Borrowed(content),
+            attrs,
+            content,
             span: Span::new(start_span.start, end_span.end),
         }))
     }
@@ -459,6 +826,17 @@ impl Parser {
             (bt, line.span)
         };
 
+        let gate = match block_type.as_str() {
+            "table" => Some(Module::Tables),
+            "footnotes" => Some(Module::Footnotes),
+            "math" => Some(Module::Math),
+            _ => None,
+        };
+        if let Some(module) = gate.filter(|&m| !self.allows(m)) {
+            self.record_error(ParseError::disabled_module(module.name(), Some(span)));
+            return self.parse_raw_fenced_block(lexer, input);
+        }
+
         match block_type.as_str() {
             "list" => self.parse_list_block(lexer, input),
             "callout" => self.parse_callout_block(lexer, input),
@@ -466,8 +844,13 @@ impl Parser {
             "figure" => self.parse_figure_block(lexer, input),
             "table" => self.parse_table_block(lexer, input),
             "footnotes" => self.parse_footnotes_block(lexer, input),
+            "sources" => self.parse_sources_block(lexer, input),
             "math" => self.parse_math_block(lexer, input),
             "html" => self.parse_html_block(lexer, input),
+            name if self.block_handlers.contains_key(name) => {
+                let handler = Arc::clone(&self.block_handlers[name]);
+                self.parse_custom_block(lexer, input, handler)
+            }
             _ => {
                 // Record error for unknown directive but continue with raw block
                 if self.recover_on_error && !block_type.is_empty() {
@@ -482,6 +865,10 @@ impl Parser {
     fn parse_html_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
         // Check if HTML module is enabled
         if !self.has_module(Module::Html) {
+            if self.enforce_modules {
+                let span = lexer.peek_line().map(|l| l.span);
+                self.record_error(ParseError::disabled_module(Module::Html.name(), span));
+            }
             return self.parse_raw_fenced_block(lexer, input);
         }
 
@@ -491,6 +878,7 @@ impl Parser {
         let content_start = (start_span.end as usize + 1).min(input.len());
         let mut content_end = content_start;
         let mut end_span = start_span;
+        let mut holes: Vec<Span> = Vec::new();
 
         loop {
             let (is_close, span) = {
@@ -515,89 +903,72 @@ impl Parser {
 
             content_end = span.end as usize;
             end_span = span;
-            lexer.next_line();
+            if let Some(line) = lexer.next_line() {
+                holes.extend(lexer.stripped_prefix(&line));
+            }
         }
 
         let content = if content_start < content_end && content_end <= input.len() {
-            &input[content_start..content_end]
+            slice_without(input, content_start as u32, content_end as u32, &holes)
         } else {
-            ""
+            Cow::Borrowed("")
         };
 
         Some(Block::Html(HtmlBlock {
-            content: Cow::Borrowed(content),
+            content,
             span: Span::new(start_span.start, end_span.end),
         }))
     }
 
     #[inline]
     fn parse_list_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
-        let (start_span, kind, start_num) = {
-            let open_line = lexer.next_line()?;
-            let text = &input[open_line.span.start as usize..open_line.span.end as usize];
-            let trimmed = text.trim();
-            let after_list = trimmed.strip_prefix("::list").unwrap_or("").trim();
-
-            let mut k = ListKind::Unordered;
-            let mut sn: Option<u64> = None;
-
-            for part in after_list.split_whitespace() {
-                match part {
-                    "ordered" => k = ListKind::Ordered,
-                    "unordered" => k = ListKind::Unordered,
-                    _ if part.starts_with("start=") => {
-                        sn = part[6..].parse().ok();
-                    }
-                    _ => {}
-                }
+        let start_span = lexer.next_line()?.span;
+        let attrs = self.parse_opener_attrs(start_span, input);
+
+        let mut kind = ListKind::Unordered;
+        let mut start_num: Option<u64> = None;
+        for (key, value) in &attrs {
+            match (key.as_ref(), value) {
+                ("ordered", AttrValue::Bool(true)) => kind = ListKind::Ordered,
+                ("unordered", AttrValue::Bool(true)) => kind = ListKind::Unordered,
+                ("start", AttrValue::Int(n)) => start_num = u64::try_from(*n).ok(),
+                _ => {}
             }
-            (open_line.span, k, sn)
-        };
+        }
 
-        let mut items: Vec<ListItem<'a>> = Vec::with_capacity(8);
-        let mut item_start: Option<u32> = None;
+        let mut items: Vec<ListItem<'a>> = spare(&mut self.spares.items, 8);
+        let mut item_lines: Vec<Line> = Vec::new();
         let mut item_end: u32 = start_span.end;
         let mut end_span = start_span;
         let mut last_span = start_span;
 
-        let finalize_item = |items: &mut Vec<ListItem<'a>>, start: Option<u32>, end: u32| {
-            if let Some(start) = start {
-                let content_slice = &input[start as usize..end as usize];
-                let content = crate::inline::parse_inlines(content_slice, start, input);
-                items.push(ListItem {
-                    blocks: vec![Block::Paragraph(Paragraph {
-                        content,
-                        span: Span::new(start, end),
-                    })],
-                    span: Span::new(start, end),
-                });
-            }
-        };
-
         while let Some(&line) = lexer.peek_line() {
             let text = &input[line.span.start as usize..line.span.end as usize];
             let trimmed = text.trim();
+            let indent = text.len() - text.trim_start().len();
 
             if trimmed == "::" {
                 lexer.next_line();
-                finalize_item(&mut items, item_start.take(), item_end);
+                self.finish_list_item(&mut items, &mut item_lines, item_end, input);
                 end_span = line.span;
                 break;
             }
 
             if trimmed.starts_with("- ") {
                 lexer.next_line();
-                finalize_item(&mut items, item_start.take(), item_end);
-                let dash_offset = text.find("- ").unwrap_or(0);
-                item_start = Some(line.span.start + dash_offset as u32 + 2);
+                self.finish_list_item(&mut items, &mut item_lines, item_end, input);
+                item_lines.push(line.skip(indent + 2));
                 item_end = line.span.end;
                 last_span = line.span;
                 end_span = line.span;
                 continue;
             }
 
-            if trimmed.starts_with("| ") {
+            // `| ` continues the current item; a bare `|` is a blank line inside it.
+            if !item_lines.is_empty() && (trimmed.starts_with("| ") || trimmed == "|") {
                 lexer.next_line();
+                let marker = if trimmed == "|" { 1 } else { 2 };
+                item_lines.push(line.skip(indent + marker));
                 item_end = line.span.end;
                 last_span = line.span;
                 end_span = line.span;
@@ -606,13 +977,16 @@ impl Parser {
 
             if line.is_blank() {
                 lexer.next_line();
+                if !item_lines.is_empty() {
+                    item_lines.push(line);
+                }
                 last_span = line.span;
                 end_span = line.span;
                 continue;
             }
 
             if trimmed.starts_with("::")
-                || trimmed.starts_with("```")
+                || code_fence_open(trimmed).is_some()
                 || trimmed.starts_with('#')
                 || trimmed.starts_with("@profile")
                 || trimmed.starts_with("@modules")
@@ -620,13 +994,13 @@ impl Parser {
                 || trimmed.starts_with("--- meta ---")
             {
                 self.record_error(ParseError::unclosed_delimiter("::list", Some(start_span)));
-                finalize_item(&mut items, item_start.take(), item_end);
+                self.finish_list_item(&mut items, &mut item_lines, item_end, input);
                 end_span = last_span;
                 break;
             }
 
             self.record_error(ParseError::invalid_syntax("list item", Some(line.span)));
-            finalize_item(&mut items, item_start.take(), item_end);
+            self.finish_list_item(&mut items, &mut item_lines, item_end, input);
             end_span = last_span;
             break;
         }
@@ -634,87 +1008,95 @@ impl Parser {
         Some(Block::List(List {
             kind,
             start: start_num,
+            attrs,
             items,
             span: Span::new(start_span.start, end_span.end),
         }))
     }
 
+    /// Parse the collected lines of a `::list` item into its blocks.
+    ///
+    /// The lines have their `- ` and `| ` markers stripped but keep their
+    /// spans, so nested blocks still point into the original input.
     #[inline]
-    fn parse_callout_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
-        let (start_span, kind, title) = {
-            let open_line = lexer.next_line()?;
-            let text = &input[open_line.span.start as usize..open_line.span.end as usize];
-            let trimmed = text.trim();
-            let after_callout = trimmed.strip_prefix("::callout").unwrap_or("").trim();
-            let (k, t) = self.parse_callout_attrs(after_callout, input, open_line.span.start);
-            (open_line.span, k, t)
+    fn finish_list_item<'a>(
+        &mut self,
+        items: &mut Vec<ListItem<'a>>,
+        lines: &mut Vec<Line>,
+        end: u32,
+        input: &'a str,
+    ) {
+        let Some(&first) = lines.first() else {
+            return;
+        };
+        let span = Span::new(first.span.start, end);
+        let (checked, rest) = self.take_task_marker(first);
+        lines[0] = rest;
+        let blocks = self.parse_nested_blocks(input, std::mem::take(lines));
+        items.push(ListItem {
+            checked,
+            blocks,
+            span,
+        });
+    }
+
+    /// Split a task checkbox (`[ ]`, `[x]`) off the first line of a list item.
+    ///
+    /// Checkboxes are only recognized when the tasks module is enabled (or
+    /// implied by the `md` profile); otherwise the brackets stay in the item text.
+    #[inline]
+    fn take_task_marker<'l>(&mut self, line: Line<'l>) -> (Option<bool>, Line<'l>) {
+        let enabled = self.module_enabled(Module::Tasks);
+        if !enabled && !self.enforce_modules {
+            return (None, line);
+        }
+        let checked = match line.text.get(..3) {
+            Some("[ ]") => false,
+            Some("[x]") | Some("[X]") => true,
+            _ => return (None, line),
         };
+        let width = match line.text.as_bytes().get(3) {
+            None => 3,
+            Some(b' ') | Some(b'\t') => 4,
+            _ => return (None, line),
+        };
+        if !enabled {
+            let span = Span::new(line.span.start, line.span.start + 3);
+            self.record_error(ParseError::disabled_module(
+                Module::Tasks.name(),
+                Some(span),
+            ));
+            return (None, line);
+        }
+        (Some(checked), line.skip(width))
+    }
+
+    #[inline]
+    fn parse_callout_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
+        let start_span = lexer.next_line()?.span;
+        let attrs = self.parse_opener_attrs(start_span, input);
+        let kind = attr_text(&attrs, "type").unwrap_or(Cow::Borrowed("note"));
+        let title = attr_text(&attrs, "title");
 
-        let (blocks, end_span) = self.parse_until_fence_close(lexer, input);
+        let (blocks, end_span) = self.parse_container_body(lexer, input, start_span);
 
         Some(Block::Callout(Callout {
             kind,
             title,
+            attrs,
             blocks,
             span: Span::new(start_span.start, end_span.end),
         }))
     }
 
-    #[inline]
-    fn parse_callout_attrs<'a>(
-        &self,
-        s: &str,
-        _input: &'a str,
-        _base: u32,
-    ) -> (CowStr<'a>, Option<CowStr<'a>>) {
-        let mut kind: CowStr<'a> = Cow::Owned("note".to_string());
-        let mut title: Option<CowStr<'a>> = None;
-
-        let mut remaining = s;
-        while !remaining.is_empty() {
-            let eq_pos = match remaining.find('=') {
-                Some(p) => p,
-                None => break,
-            };
-
-            let key = remaining[..eq_pos].trim();
-            remaining = remaining[eq_pos + 1..].trim_start();
-
-            if remaining.starts_with('"') {
-                if let Some(end) = remaining[1..].find('"') {
-                    let val = &remaining[1..end + 1];
-                    remaining = remaining[end + 2..].trim_start();
-
-                    match key {
-                        "type" => kind = Cow::Owned(val.to_string()),
-                        "title" => title = Some(Cow::Owned(val.to_string())),
-                        _ => {}
-                    }
-                } else {
-                    break;
-                }
-            } else {
-                let end = remaining.find(' ').unwrap_or(remaining.len());
-                let val = &remaining[..end];
-                remaining = remaining[end..].trim_start();
-
-                match key {
-                    "type" => kind = Cow::Owned(val.to_string()),
-                    "title" => title = Some(Cow::Owned(val.to_string())),
-                    _ => {}
-                }
-            }
-        }
-
-        (kind, title)
-    }
-
     #[inline]
     fn parse_quote_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
         let start_span = lexer.next_line()?.span;
-        let (blocks, end_span) = self.parse_until_fence_close(lexer, input);
+        let attrs = self.parse_opener_attrs(start_span, input);
+        let (blocks, end_span) = self.parse_container_body(lexer, input, start_span);
 
         Some(Block::Quote(Quote {
+            attrs,
             blocks,
             span: Span::new(start_span.start, end_span.end),
         }))
@@ -722,14 +1104,11 @@ impl Parser {
 
     #[inline]
     fn parse_figure_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
-        let (start_span, src, alt, caption) = {
-            let open_line = lexer.next_line()?;
-            let text = &input[open_line.span.start as usize..open_line.span.end as usize];
-            let trimmed = text.trim();
-            let after_figure = trimmed.strip_prefix("::figure").unwrap_or("").trim();
-            let (s, a, c) = self.parse_figure_attrs(after_figure);
-            (open_line.span, s, a, c)
-        };
+        let start_span = lexer.next_line()?.span;
+        let attrs = self.parse_opener_attrs(start_span, input);
+        let src = attr_text(&attrs, "src").unwrap_or_default();
+        let alt = attr_text(&attrs, "alt").unwrap_or_default();
+        let caption = attr_text(&attrs, "caption");
 
         let mut end_span = start_span;
         if let Some(line) = lexer.peek_line() {
@@ -743,153 +1122,176 @@ impl Parser {
             src,
             alt,
             caption,
+            attrs,
             span: Span::new(start_span.start, end_span.end),
         }))
     }
 
-    #[inline]
-    fn parse_figure_attrs<'a>(&self, s: &str) -> (CowStr<'a>, CowStr<'a>, Option<CowStr<'a>>) {
-        let mut src: CowStr<'a> = Cow::Owned(String::new());
-        let mut alt: CowStr<'a> = Cow::Owned(String::new());
-        let mut caption: Option<CowStr<'a>> = None;
-
-        let mut remaining = s;
-        while !remaining.is_empty() {
-            let eq_pos = match remaining.find('=') {
-                Some(p) => p,
-                None => break,
-            };
+    /// Parse a `::sources` block of `- id key=value ...` entries.
+    fn parse_sources_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
+        let start_span = lexer.next_line()?.span;
+        let attrs = self.parse_opener_attrs(start_span, input);
 
-            let key = remaining[..eq_pos].trim();
-            remaining = remaining[eq_pos + 1..].trim_start();
+        let mut entries = spare(&mut self.spares.sources, 0);
+        let mut end_span = start_span;
 
-            if remaining.starts_with('"') {
-                if let Some(end) = remaining[1..].find('"') {
-                    let val = remaining[1..end + 1].to_string();
-                    remaining = remaining[end + 2..].trim_start();
+        while let Some(&line) = lexer.peek_line() {
+            let text = &input[line.span.start as usize..line.span.end as usize];
+            let trimmed = text.trim();
 
-                    match key {
-                        "src" => src = Cow::Owned(val),
-                        "alt" => alt = Cow::Owned(val),
-                        "caption" => caption = Some(Cow::Owned(val)),
-                        _ => {}
-                    }
-                } else {
-                    break;
-                }
-            } else {
-                let end = remaining.find(' ').unwrap_or(remaining.len());
-                let val = remaining[..end].to_string();
-                remaining = remaining[end..].trim_start();
-
-                match key {
-                    "src" => src = Cow::Owned(val),
-                    "alt" => alt = Cow::Owned(val),
-                    "caption" => caption = Some(Cow::Owned(val)),
-                    _ => {}
-                }
+            if trimmed == "::" {
+                end_span = line.span;
+                lexer.next_line();
+                break;
+            }
+
+            let Some(entry) = trimmed.strip_prefix("- ") else {
+                self.record_error(ParseError::unclosed_delimiter(
+                    "::sources",
+                    Some(start_span),
+                ));
+                break;
+            };
+            lexer.next_line();
+            end_span = line.span;
+
+            let entry = entry.trim_start();
+            let id_len = entry
+                .bytes()
+                .take_while(|&b| crate::inline::is_citation_key_byte(b))
+                .count();
+            let (id, rest) = entry.split_at(id_len);
+            if id.is_empty() || !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+                self.record_error(ParseError::invalid_syntax("source id", Some(line.span)));
+                continue;
             }
+
+            let attrs = self.parse_attrs(rest);
+            entries.push(Source {
+                id: Cow::Borrowed(id),
+                title: attr_text(&attrs, "title"),
+                url: attr_text(&attrs, "url"),
+                attrs,
+                span: line.span,
+            });
         }
 
-        (src, alt, caption)
+        Some(Block::Sources(Sources {
+            attrs,
+            entries,
+            span: Span::new(start_span.start, end_span.end),
+        }))
     }
 
     #[inline]
     fn parse_table_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
         let start_span = lexer.next_line()?.span;
+        let attrs = self.parse_opener_attrs(start_span, input);
 
-        let mut rows: Vec<TableRow<'a>> = Vec::with_capacity(8);
+        let mut rows: Vec<TableRow<'a>> = spare(&mut self.spares.rows, 8);
+        let mut alignments = spare(&mut self.spares.alignments, 0);
+        let mut has_separator = false;
+        let mut separator_span = start_span;
         let mut end_span = start_span;
-        let mut found_separator = false;
 
-        loop {
-            let (is_close, is_sep, is_row, span, line_text) = {
-                match lexer.peek_line() {
-                    Some(line) => {
-                        let text = &input[line.span.start as usize..line.span.end as usize];
-                        let trimmed = text.trim();
-                        (
-                            trimmed == "::",
-                            trimmed.starts_with('|') && trimmed.contains("---"),
-                            trimmed.starts_with('|'),
-                            line.span,
-                            trimmed,
-                        )
-                    }
-                    None => break,
-                }
-            };
+        while let Some(&line) = lexer.peek_line() {
+            let text = &input[line.span.start as usize..line.span.end as usize];
+            let trimmed = text.trim();
 
-            if is_close {
-                end_span = span;
+            if trimmed == "::" {
+                end_span = line.span;
                 lexer.next_line();
                 break;
             }
 
-            if is_sep {
-                found_separator = true;
-                end_span = span;
-                lexer.next_line();
-                continue;
+            if !trimmed.starts_with('|') {
+                self.record_error(ParseError::unclosed_delimiter("::table", Some(start_span)));
+                break;
             }
 
-            if is_row {
-                let cells = self.parse_table_row(line_text, span.start, input);
-                let is_header = !found_separator && rows.is_empty();
-                rows.push(TableRow {
-                    cells,
-                    header: is_header,
-                    span,
-                });
-                end_span = span;
+            // The separator is either the first line or follows the header row.
+            if !has_separator && rows.len() <= 1 && table_alignments(text, &mut alignments) {
+                has_separator = true;
+                separator_span = line.span;
+                end_span = line.span;
                 lexer.next_line();
                 continue;
             }
 
-            self.record_error(ParseError::unclosed_delimiter("::table", Some(start_span)));
-            break;
+            let cells = self.parse_table_row(text, line.span.start);
+            let is_header = !has_separator && rows.is_empty();
+            rows.push(TableRow {
+                cells,
+                header: is_header,
+                span: line.span,
+            });
+            end_span = line.span;
+            lexer.next_line();
         }
 
+        self.check_table_columns(&rows, &alignments, separator_span);
+
         Some(Block::Table(Table {
+            alignments,
+            attrs,
             rows,
             span: Span::new(start_span.start, end_span.end),
         }))
     }
 
+    /// Split a table row into cells, keeping empty cells in place.
+    ///
+    /// `line` is the full row text starting at `base_offset`; each cell span
+    /// covers the text between its pipes.
     #[inline]
-    fn parse_table_row<'a>(
-        &self,
-        line: &'a str,
-        base_offset: u32,
-        input: &'a str,
-    ) -> Vec<TableCell<'a>> {
-        let mut cells = Vec::with_capacity(8);
-        let mut offset = base_offset;
-
-        for (i, part) in line.split('|').enumerate() {
-            if i == 0 {
-                offset += part.len() as u32 + 1;
-                continue;
-            }
-
-            let trimmed = part.trim();
-            if trimmed.is_empty() {
-                offset += part.len() as u32 + 1;
-                continue;
-            }
-
-            let content = crate::inline::parse_inlines(trimmed, offset, input);
+    fn parse_table_row<'a>(&mut self, line: &'a str, base_offset: u32) -> Vec<TableCell<'a>> {
+        let ranges = table_cell_ranges(line);
+        let mut cells = spare(&mut self.spares.cells, ranges.len());
+
+        for (start, end) in ranges {
+            let part = &line[start..end];
+            let lead = part.len() - part.trim_start().len();
+            let offset = base_offset + (start + lead) as u32;
+            let content = self.parse_inlines(part.trim(), offset);
             cells.push(TableCell {
                 content,
-                span: Span::new(offset, offset + part.len() as u32),
+                span: Span::new(base_offset + start as u32, base_offset + end as u32),
             });
-
-            offset += part.len() as u32 + 1;
         }
 
         cells
     }
 
+    /// Report rows (and the separator) whose cell count differs from the header.
+    #[inline]
+    fn check_table_columns(&mut self, rows: &[TableRow], alignments: &[Alignment], sep: Span) {
+        let expected = match rows.first() {
+            Some(row) if row.header => row.cells.len(),
+            _ if !alignments.is_empty() => alignments.len(),
+            Some(row) => row.cells.len(),
+            None => return,
+        };
+
+        if !alignments.is_empty() && alignments.len() != expected {
+            let context = format!(
+                "table separator (expected {} cells, found {})",
+                expected,
+                alignments.len()
+            );
+            self.record_error(ParseError::invalid_syntax(&context, Some(sep)));
+        }
+        for row in rows {
+            if row.cells.len() != expected {
+                let context = format!(
+                    "table row (expected {} cells, found {})",
+                    expected,
+                    row.cells.len()
+                );
+                self.record_error(ParseError::invalid_syntax(&context, Some(row.span)));
+            }
+        }
+    }
+
     #[inline]
     fn parse_footnotes_block<'a>(
         &mut self,
@@ -898,7 +1300,7 @@ impl Parser {
     ) -> Option<Block<'a>> {
         let start_span = lexer.next_line()?.span;
 
-        let mut defs: Vec<FootnoteDef<'a>> = Vec::with_capacity(4);
+        let mut defs: Vec<FootnoteDef<'a>> = spare(&mut self.spares.defs, 4);
         let mut end_span = start_span;
 
         loop {
@@ -932,13 +1334,15 @@ impl Parser {
             }
 
             if is_def {
-                let content_inlines = crate::inline::parse_inlines(content_text, span.start, input);
+                let content_inlines = self.parse_inlines(content_text, span.start);
+                let mut blocks = self.new_blocks();
+                blocks.push(Block::Paragraph(Paragraph {
+                    content: content_inlines,
+                    span,
+                }));
                 defs.push(FootnoteDef {
                     label: Cow::Owned(label.to_string()),
-                    blocks: vec![Block::Paragraph(Paragraph {
-                        content: content_inlines,
-                        span,
-                    })],
+                    blocks,
                     span,
                 });
             }
@@ -955,18 +1359,17 @@ impl Parser {
 
     #[inline]
     fn parse_math_block<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
-        let (start_span, display) = {
-            let open_line = lexer.next_line()?;
-            let text = &input[open_line.span.start as usize..open_line.span.end as usize];
-            let trimmed = text.trim();
-            let d = trimmed.contains("block") || trimmed.contains("display");
-            (open_line.span, d)
-        };
+        let start_span = lexer.next_line()?.span;
+        let attrs = self.parse_opener_attrs(start_span, input);
+        let display = attrs.iter().any(|(key, value)| {
+            matches!(key.as_ref(), "block" | "display") && *value == AttrValue::Bool(true)
+        });
 
         // Content starts after opening fence, clamped to input length
         let content_start = (start_span.end as usize + 1).min(input.len());
         let mut content_end = content_start;
         let mut end_span = start_span;
+        let mut holes: Vec<Span> = Vec::new();
 
         loop {
             let (is_close, span) = {
@@ -984,18 +1387,21 @@ impl Parser {
 
             content_end = span.end as usize;
             end_span = span;
-            lexer.next_line();
+            if let Some(line) = lexer.next_line() {
+                holes.extend(lexer.stripped_prefix(&line));
+            }
         }
 
         let content = if content_start < content_end && content_end <= input.len() {
-            &input[content_start..content_end]
+            slice_without(input, content_start as u32, content_end as u32, &holes)
         } else {
-            ""
+            Cow::Borrowed("")
         };
 
         Some(Block::Math(MathBlock {
             display,
-            content: Cow::Borrowed(content),
+            attrs,
+            content,
             span: Span::new(start_span.start, end_span.end),
         }))
     }
@@ -1007,11 +1413,14 @@ impl Parser {
         input: &'a str,
     ) -> Option<Block<'a>> {
         let start_span = lexer.next_line()?.span;
+        let name = opener_name(start_span, input);
+        let attrs = self.parse_opener_attrs(start_span, input);
 
         // Content starts after opening fence, clamped to input length
         let content_start = (start_span.end as usize + 1).min(input.len());
         let mut content_end = content_start;
         let mut end_span = start_span;
+        let mut holes: Vec<Span> = Vec::new();
 
         loop {
             let (is_close, span) = {
@@ -1029,119 +1438,913 @@ impl Parser {
 
             content_end = span.end as usize;
             end_span = span;
-            lexer.next_line();
+            if let Some(line) = lexer.next_line() {
+                holes.extend(lexer.stripped_prefix(&line));
+            }
         }
 
         let content = if content_start < content_end && content_end <= input.len() {
-            &input[content_start..content_end]
+            slice_without(input, content_start as u32, content_end as u32, &holes)
         } else {
-            ""
+            Cow::Borrowed("")
         };
 
         Some(Block::Raw(RawBlock {
-            content: Cow::Borrowed(content),
+            name: Cow::Borrowed(name),
+            attrs,
+            content,
             span: Span::new(start_span.start, end_span.end),
         }))
     }
 
+    /// Parse the body of a `::callout` or `::quote` up to its matching bare `::`.
+    ///
+    /// Body lines are collected first, skipping over nested fences (which keep
+    /// their own closers) and code blocks, then parsed as a full block sequence.
+    /// Returns the nested blocks and the span of the last consumed line.
     #[inline]
-    fn parse_until_fence_close<'a>(
+    fn parse_container_body<'a>(
         &mut self,
         lexer: &mut Lexer,
         input: &'a str,
+        open: Span,
     ) -> (Vec<Block<'a>>, Span) {
-        let mut blocks = Vec::with_capacity(4);
-        let mut para_start: Option<u32> = None;
-        let mut para_end: u32 = 0;
-        let mut end_span = Span::new(0, 0);
+        let (lines, _, end_span) = self.collect_container_lines(lexer, open);
+        (self.parse_nested_blocks(input, lines), end_span)
+    }
 
-        loop {
-            let (is_close, is_blank, span) = {
-                match lexer.next_line() {
-                    Some(line) => (line.trimmed() == "::", line.is_blank(), line.span),
-                    None => break,
-                }
-            };
+    /// Collect the body lines of a container block up to its matching bare `::`.
+    ///
+    /// Returns the lines, the prefixes stripped from them and the span of the
+    /// last consumed line.
+    fn collect_container_lines<'l>(
+        &self,
+        lexer: &mut Lexer<'l>,
+        open: Span,
+    ) -> (Vec<Line<'l>>, Vec<Span>, Span) {
+        let mut lines = Vec::with_capacity(8);
+        let mut holes = Vec::new();
+        let mut end_span = open;
+        let mut depth = 0usize;
+        let mut in_code: Option<(u8, usize)> = None;
+        let mut in_leaf = false;
+
+        while let Some(line) = lexer.next_line() {
+            end_span = line.span;
+            let trimmed = line.trimmed();
+            holes.extend(lexer.stripped_prefix(&line));
 
-            if is_close {
-                if let Some(start) = para_start {
-                    let content_slice = &input[start as usize..para_end as usize];
-                    let content = crate::inline::parse_inlines(content_slice, start, input);
-                    blocks.push(Block::Paragraph(Paragraph {
-                        content,
-                        span: Span::new(start, para_end),
-                    }));
+            if let Some((fence_char, fence_len)) = in_code {
+                if code_fence_closes(trimmed, fence_char, fence_len) {
+                    in_code = None;
                 }
-                end_span = span;
-                break;
-            }
-
-            if is_blank {
-                if let Some(start) = para_start.take() {
-                    let content_slice = &input[start as usize..para_end as usize];
-                    let content = crate::inline::parse_inlines(content_slice, start, input);
-                    blocks.push(Block::Paragraph(Paragraph {
-                        content,
-                        span: Span::new(start, para_end),
-                    }));
+            } else if trimmed == "::" {
+                if depth == 0 {
+                    break;
                 }
-            } else {
-                if para_start.is_none() {
-                    para_start = Some(span.start);
+                depth -= 1;
+                in_leaf = false;
+            } else if in_leaf {
+                // Math, HTML and raw bodies are opaque up to their closer.
+            } else if let Some(fence) = code_fence_open(trimmed) {
+                in_code = Some(fence);
+            } else if let Some(after) = trimmed.strip_prefix("::") {
+                match after.split_whitespace().next().unwrap_or("") {
+                    "figure" => {
+                        // A figure only owns a closer on the line right after it.
+                        lines.push(line);
+                        match lexer.peek_line() {
+                            Some(next) if next.trimmed() == "::" => {}
+                            _ => continue,
+                        }
+                        let Some(close) = lexer.next_line() else {
+                            break;
+                        };
+                        end_span = close.span;
+                        holes.extend(lexer.stripped_prefix(&close));
+                        lines.push(close);
+                        continue;
+                    }
+                    "list" | "callout" | "quote" | "table" | "footnotes" => depth += 1,
+                    name if self.registered_body(name) == Some(BodyKind::Blocks) => depth += 1,
+                    _ => {
+                        depth += 1;
+                        in_leaf = true;
+                    }
                 }
-                para_end = span.end;
             }
 
-            end_span = span;
+            lines.push(line);
         }
 
-        (blocks, end_span)
+        (lines, holes, end_span)
+    }
+
+    /// Body kind of a registered block, or `None` if `name` is not registered.
+    #[inline]
+    fn registered_body(&self, name: &str) -> Option<BodyKind> {
+        self.block_handlers.get(name).map(|h| h.body())
+    }
+
+    /// Parse a `::name` block with a registered handler.
+    fn parse_custom_block<'a>(
+        &mut self,
+        lexer: &mut Lexer,
+        input: &'a str,
+        handler: Arc<dyn BlockHandler>,
+    ) -> Option<Block<'a>> {
+        let open = lexer.next_line()?.span;
+        let opener = input[open.start as usize..open.end as usize].trim();
+        let name = opener[2..].split_whitespace().next().unwrap_or("");
+        let attrs = self.parse_opener_attrs(open, input);
+
+        let (lines, holes, end_span) = match handler.body() {
+            BodyKind::Blocks => self.collect_container_lines(lexer, open),
+            BodyKind::Content => {
+                let mut lines = Vec::new();
+                let mut holes = Vec::new();
+                let mut end_span = open;
+                while let Some(line) = lexer.next_line() {
+                    end_span = line.span;
+                    if line.trimmed() == "::" {
+                        break;
+                    }
+                    holes.extend(lexer.stripped_prefix(&line));
+                    lines.push(line);
+                }
+                (lines, holes, end_span)
+            }
+        };
+        // Re-borrow the lines from `input` so nodes can outlive the lexer.
+        let lines = lines
+            .into_iter()
+            .map(|l| Line {
+                text: &input[l.span.start as usize..l.span.end as usize],
+                span: l.span,
+            })
+            .collect();
+
+        let span = Span::new(open.start, end_span.end);
+        let mut ctx = BlockContext {
+            name,
+            attrs,
+            span,
+            input,
+            open,
+            lines,
+            holes,
+            parser: self,
+        };
+
+        match handler.parse(&mut ctx) {
+            Ok(body) => Some(Block::Custom(CustomBlock {
+                name: Cow::Borrowed(name),
+                attrs: ctx.attrs,
+                body,
+                span,
+            })),
+            Err(message) => {
+                let content = ctx.content();
+                let attrs = ctx.attrs;
+                self.record_error(ParseError::invalid_syntax(
+                    &format!("{} block ({})", name, message),
+                    Some(open),
+                ));
+                Some(Block::Raw(RawBlock {
+                    name: Cow::Borrowed(name),
+                    attrs,
+                    content,
+                    span,
+                }))
+            }
+        }
     }
 
     #[inline]
     fn parse_paragraph<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
+        let md = self.is_md();
         let mut start_span: Option<Span> = None;
         let mut end_span = Span::new(0, 0);
+        let mut holes: Vec<Span> = Vec::new();
 
-        loop {
-            let should_break = {
-                match lexer.peek_line() {
-                    Some(line) => {
-                        if line.is_blank() {
-                            true
-                        } else {
-                            let trimmed = line.trimmed();
-                            let first = trimmed.as_bytes().first().copied();
-                            match first {
-                                Some(b'#') | Some(b':') => true,
-                                Some(b'`') if trimmed.starts_with("```") => true,
-                                Some(b'-') if trimmed == "---" => true,
-                                _ => false,
-                            }
-                        }
+        while let Some(&line) = lexer.peek_line() {
+            if line.is_blank() {
+                break;
+            }
+
+            // A paragraph always takes its first line, so every call makes progress.
+            if start_span.is_some() {
+                if md {
+                    if let Some(level) = setext_level(&line) {
+                        lexer.next_line();
+                        let start = start_span.unwrap_or(line.span);
+                        return Some(
+                            self.make_heading(level, start, end_span, line.span, input, &holes),
+                        );
+                    }
+                    if md_interrupts_paragraph(&line) {
+                        break;
+                    }
+                } else {
+                    let trimmed = line.trimmed();
+                    let interrupts = match trimmed.as_bytes().first().copied() {
+                        Some(b'#') | Some(b':') => true,
+                        Some(b'`') | Some(b'~') => code_fence_open(trimmed).is_some(),
+                        Some(b'-') => trimmed == "---",
+                        _ => false,
+                    };
+                    if interrupts {
+                        break;
                     }
-                    None => true,
                 }
-            };
-
-            if should_break {
-                break;
+                if let Some(mut hole) = lexer.stripped_prefix(&line) {
+                    // Indentation after a container prefix is dropped like
+                    // that after any other line break.
+                    if md {
+                        let text = line.text.trim_start_matches([' ', '\t']);
+                        hole.end += (line.text.len() - text.len()) as u32;
+                    }
+                    holes.push(hole);
+                }
             }
 
-            let line = lexer.next_line().unwrap();
+            lexer.next_line();
             if start_span.is_none() {
-                start_span = Some(line.span);
+                // CommonMark drops up to three columns of indentation.
+                let first = if md { line.strip_indent(3) } else { line };
+                start_span = Some(first.span);
             }
             end_span = line.span;
         }
 
         let start = start_span?;
         let content_slice = &input[start.start as usize..end_span.end as usize];
-        let content = crate::inline::parse_inlines(content_slice, start.start, input);
+        let mut content = self.parse_inlines(content_slice, start.start);
+        if !holes.is_empty() {
+            crate::inline::remove_holes(&mut content, input, &holes);
+        }
 
         Some(Block::Paragraph(Paragraph {
             content,
             span: Span::new(start.start, end_span.end),
         }))
     }
+
+    /// Build a setext heading from paragraph lines `start..=end` and its underline.
+    #[inline]
+    fn make_heading<'a>(
+        &mut self,
+        level: u8,
+        start: Span,
+        end: Span,
+        underline: Span,
+        input: &'a str,
+        holes: &[Span],
+    ) -> Block<'a> {
+        let text = input[start.start as usize..end.end as usize].trim_end();
+        let (text, explicit_id) = split_heading_id(text);
+        let mut content = self.parse_inlines(text, start.start);
+        if !holes.is_empty() {
+            crate::inline::remove_holes(&mut content, input, holes);
+        }
+        let span = Span::new(start.start, underline.end);
+        Block::Heading(Heading {
+            level,
+            id: self.heading_id(explicit_id, &content, span),
+            content,
+            span,
+        })
+    }
+
+    /// Assign a heading its anchor id. Explicit ids are kept as written and
+    /// reported when already taken; slugs get a `-1`, `-2`, ... suffix until
+    /// they are unique.
+    fn heading_id<'a>(
+        &mut self,
+        explicit: Option<&'a str>,
+        content: &[Inline],
+        span: Span,
+    ) -> Option<CowStr<'a>> {
+        if let Some(id) = explicit {
+            if !self.heading_ids.insert(id.to_string()) {
+                let context = format!("heading id (duplicate #{id})");
+                self.record_error(ParseError::invalid_syntax(&context, Some(span)));
+            }
+            return Some(Cow::Borrowed(id));
+        }
+
+        crate::resolve::unique_slug(content, &mut self.heading_ids).map(Cow::Owned)
+    }
+
+    /// Classify the next line as the start of a CommonMark-only block, if any.
+    #[inline]
+    fn md_block_start(&self, lexer: &mut Lexer) -> Option<MdBlockStart> {
+        let line = *lexer.peek_line()?;
+        if line.is_blank() {
+            return None;
+        }
+        if line.indent() >= 4 {
+            return Some(MdBlockStart::IndentedCode);
+        }
+
+        let trimmed = line.text.trim_start();
+        if is_md_thematic_break(trimmed) {
+            return Some(MdBlockStart::ThematicBreak);
+        }
+        if trimmed.starts_with('>') {
+            return Some(MdBlockStart::Quote);
+        }
+        if md_list_marker(line.text).is_some() {
+            return Some(MdBlockStart::List);
+        }
+        // GFM pipe tables are not part of CommonMark core.
+        if self.module_enabled(Module::Tables) && trimmed.contains('|') {
+            let header_cells = table_cell_ranges(trimmed).len();
+            if let Some(next) = lexer.peek_second() {
+                let mut alignments = Vec::new();
+                if table_alignments(next.text, &mut alignments) && alignments.len() == header_cells
+                {
+                    return Some(MdBlockStart::Table);
+                }
+            }
+        }
+        None
+    }
+
+    /// Parse an indented code block (four or more columns of indentation).
+    #[inline]
+    fn parse_md_indented_code<'a>(&mut self, lexer: &mut Lexer) -> Option<Block<'a>> {
+        let start = lexer.peek_line()?.span;
+        let mut lines: Vec<&str> = Vec::with_capacity(8);
+        let mut end = start;
+        let mut content_lines = 0;
+
+        while let Some(&line) = lexer.peek_line() {
+            if line.is_blank() {
+                // Blank lines belong to the block only if more code follows.
+                lines.push(line.strip_indent(4).text);
+            } else if line.indent() >= 4 {
+                lines.push(line.strip_indent(4).text);
+                content_lines = lines.len();
+                end = line.span;
+            } else {
+                break;
+            }
+            lexer.next_line();
+        }
+        lines.truncate(content_lines);
+
+        Some(Block::CodeBlock(CodeBlock {
+            lang: Cow::Borrowed(""),
+            attrs: Vec::new(),
+            content: Cow::Owned(lines.join("\n")),
+            span: Span::new(start.start, end.end),
+        }))
+    }
+
+    /// Parse a `>` block quote, including lazy continuation lines.
+    #[inline]
+    fn parse_md_quote<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
+        let start = lexer.peek_line()?.span;
+        let mut end = start;
+        let mut lines: Vec<Line> = Vec::with_capacity(8);
+        let mut lazy_ok = false;
+
+        while let Some(&line) = lexer.peek_line() {
+            let indent_bytes = line.text.len() - line.text.trim_start().len();
+            if line.indent() < 4 && line.text[indent_bytes..].starts_with('>') {
+                let mut skip = indent_bytes + 1;
+                if matches!(line.text.as_bytes().get(skip), Some(b' ') | Some(b'\t')) {
+                    skip += 1;
+                }
+                let inner = line.skip(skip);
+                lazy_ok = !inner.is_blank();
+                lines.push(inner);
+            } else if lazy_ok && !line.is_blank() && !md_interrupts_paragraph(&line) {
+                lines.push(line);
+            } else {
+                break;
+            }
+            end = line.span;
+            lexer.next_line();
+        }
+
+        let blocks = self.parse_nested_blocks(input, lines);
+        Some(Block::Quote(Quote {
+            attrs: Vec::new(),
+            blocks,
+            span: Span::new(start.start, end.end),
+        }))
+    }
+
+    /// Parse a CommonMark bullet or ordered list.
+    #[inline]
+    fn parse_md_list<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
+        let first_line = *lexer.peek_line()?;
+        let first = md_list_marker(first_line.text)?;
+        let mut items: Vec<ListItem<'a>> = spare(&mut self.spares.items, 8);
+        let mut end = first_line.span.end;
+
+        while let Some(&line) = lexer.peek_line() {
+            let marker = match md_list_marker(line.text) {
+                Some(m) if m.same_list(&first) && !is_md_thematic_break(line.text.trim_start()) => {
+                    m
+                }
+                _ => break,
+            };
+            lexer.next_line();
+
+            let mut item_end = line.span.end;
+            let (checked, first_content) = self.take_task_marker(line.skip(marker.content_bytes));
+            let mut lines: Vec<Line> = vec![first_content];
+            let mut prev_blank = false;
+
+            while let Some(&next) = lexer.peek_line() {
+                if next.is_blank() {
+                    lines.push(next.strip_indent(marker.content_indent));
+                    prev_blank = true;
+                    lexer.next_line();
+                    continue;
+                }
+                if next.indent() >= marker.content_indent {
+                    lines.push(next.strip_indent(marker.content_indent));
+                } else if !prev_blank
+                    && md_list_marker(next.text).is_none()
+                    && !md_interrupts_paragraph(&next)
+                {
+                    // Lazy paragraph continuation.
+                    lines.push(next);
+                } else {
+                    break;
+                }
+                prev_blank = false;
+                item_end = next.span.end;
+                lexer.next_line();
+            }
+
+            while lines.last().is_some_and(|l| l.is_blank()) {
+                lines.pop();
+            }
+
+            items.push(ListItem {
+                checked,
+                blocks: self.parse_nested_blocks(input, lines),
+                span: Span::new(line.span.start, item_end),
+            });
+            end = item_end;
+        }
+
+        Some(Block::List(List {
+            kind: if first.ordered {
+                ListKind::Ordered
+            } else {
+                ListKind::Unordered
+            },
+            start: first.ordered.then_some(first.number),
+            attrs: Vec::new(),
+            items,
+            span: Span::new(first_line.span.start, end),
+        }))
+    }
+
+    /// Parse a GFM pipe table (header row, delimiter row, body rows).
+    #[inline]
+    fn parse_md_table<'a>(&mut self, lexer: &mut Lexer, input: &'a str) -> Option<Block<'a>> {
+        let header = lexer.next_line()?;
+        let delimiter = lexer.next_line()?;
+        let mut alignments = spare(&mut self.spares.alignments, 0);
+        table_alignments(delimiter.text, &mut alignments);
+
+        let mut rows: Vec<TableRow<'a>> = spare(&mut self.spares.rows, 8);
+        rows.push(TableRow {
+            cells: self.parse_md_table_row(header.span, input),
+            header: true,
+            span: header.span,
+        });
+        let mut end = delimiter.span;
+
+        while let Some(&line) = lexer.peek_line() {
+            if line.is_blank() || !line.text.contains('|') || md_interrupts_paragraph(&line) {
+                break;
+            }
+            rows.push(TableRow {
+                cells: self.parse_md_table_row(line.span, input),
+                header: false,
+                span: line.span,
+            });
+            end = line.span;
+            lexer.next_line();
+        }
+
+        self.check_table_columns(&rows, &alignments, delimiter.span);
+
+        Some(Block::Table(Table {
+            alignments,
+            attrs: Vec::new(),
+            rows,
+            span: Span::new(header.span.start, end.end),
+        }))
+    }
+
+    #[inline]
+    fn parse_md_table_row<'a>(&mut self, span: Span, input: &'a str) -> Vec<TableCell<'a>> {
+        let text = &input[span.start as usize..span.end as usize];
+        self.parse_table_row(text, span.start)
+    }
+}
+
+/// An empty vector from `spares`, or a new one with room for `capacity`.
+#[inline]
+fn spare<T>(spares: &mut Vec<Vec<T>>, capacity: usize) -> Vec<T> {
+    spares.pop().unwrap_or_else(|| Vec::with_capacity(capacity))
+}
+
+/// Resolve `\"` and `\\` escapes inside a quoted string.
+///
+/// Borrows the input unless an escape is actually present.
+#[inline]
+fn unescape_quoted(s: &str) -> CowStr<'_> {
+    if !s.contains('\\') {
+        return Cow::Borrowed(s);
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some(next @ ('"' | '\\' | '\'')) => out.push(next),
+                Some(next) => {
+                    out.push('\\');
+                    out.push(next);
+                }
+                None => out.push('\\'),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// Split a trailing `{#id}` off heading text.
+///
+/// The id must be non-empty, contain no whitespace, and be preceded by
+/// whitespace (or be the whole text).
+pub(crate) fn split_heading_id(text: &str) -> (&str, Option<&str>) {
+    let trimmed = text.trim_end();
+    let Some(inner) = trimmed.strip_suffix('}') else {
+        return (text, None);
+    };
+    let Some(open) = inner.rfind("{#") else {
+        return (text, None);
+    };
+    let id = &inner[open + 2..];
+    let before = &trimmed[..open];
+    let separated = before.is_empty() || before.ends_with([' ', '\t']);
+    if id.is_empty() || id.contains(|c: char| c.is_whitespace() || c == '{') || !separated {
+        return (text, None);
+    }
+    (before.trim_end(), Some(id))
+}
+
+/// Why a link destination is malformed, if it is.
+fn url_problem(url: &str, kind: LinkKind) -> Option<&'static str> {
+    if url.is_empty() {
+        return Some("empty");
+    }
+    if url.contains(char::is_whitespace) {
+        return Some("contains whitespace");
+    }
+    match kind {
+        LinkKind::Fragment if url.len() == 1 => Some("empty fragment"),
+        LinkKind::Email => {
+            let address = url[7..].split('?').next().unwrap_or("");
+            match address.split_once('@') {
+                Some((user, host)) if !user.is_empty() && !host.is_empty() => None,
+                _ => Some("missing email address"),
+            }
+        }
+        LinkKind::Absolute => {
+            let (scheme, rest) = url.split_once(':').unwrap_or((url, ""));
+            let missing_host = match rest.strip_prefix("//") {
+                // `file:///path` is the one common scheme with an empty host.
+                Some(authority) => {
+                    authority.split(['/', '?', '#']).next() == Some("")
+                        && !scheme.eq_ignore_ascii_case("file")
+                }
+                None => rest.is_empty(),
+            };
+            missing_host.then_some("missing host")
+        }
+        _ => None,
+    }
+}
+
+/// Block name of a `::name attrs` opener line; empty for a bare `::`.
+fn opener_name(open_line: Span, input: &str) -> &str {
+    let text = input[open_line.start as usize..open_line.end as usize].trim();
+    let after_colons = text.strip_prefix("::").unwrap_or(text);
+    let name_end = after_colons
+        .find(char::is_whitespace)
+        .unwrap_or(after_colons.len());
+    &after_colons[..name_end]
+}
+
+/// Text of a scalar attribute, if present. Typed values are rendered back to text.
+#[inline]
+fn attr_text<'a>(attrs: &AttrMap<'a>, key: &str) -> Option<CowStr<'a>> {
+    let (_, value) = attrs.iter().rev().find(|(k, _)| k == key)?;
+    match value {
+        AttrValue::Str(s) => Some(s.clone()),
+        AttrValue::Bool(b) => Some(Cow::Owned(b.to_string())),
+        AttrValue::Int(i) => Some(Cow::Owned(i.to_string())),
+        AttrValue::Float(f) => Some(Cow::Owned(f.to_string())),
+        AttrValue::List(_) => None,
+    }
+}
+
+/// Length of an attribute value: a quoted string up to its closing quote, a
+/// `[...]` list up to its closing bracket, or everything up to the next
+/// whitespace.
+#[inline]
+fn attr_value_end(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    match bytes.first() {
+        Some(&q @ (b'"' | b'\'')) => {
+            let mut i = 1;
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\\' => i += 2,
+                    b if b == q => return i + 1,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        }
+        Some(b'[') => {
+            let mut quote: Option<u8> = None;
+            let mut i = 1;
+            while i < bytes.len() {
+                match (bytes[i], quote) {
+                    (b'\\', Some(_)) => i += 1,
+                    (b, Some(q)) if b == q => quote = None,
+                    (b @ (b'"' | b'\''), None) => quote = Some(b),
+                    (b']', None) => return i + 1,
+                    _ => {}
+                }
+                i += 1;
+            }
+            bytes.len()
+        }
+        _ => s.find(char::is_whitespace).unwrap_or(s.len()),
+    }
+}
+
+/// Opening code fence: three or more backticks or tildes, as `(char, length)`.
+#[inline]
+fn code_fence_open(trimmed: &str) -> Option<(u8, usize)> {
+    let fence_char = *trimmed.as_bytes().first()?;
+    if fence_char != b'`' && fence_char != b'~' {
+        return None;
+    }
+    let len = trimmed.bytes().take_while(|&b| b == fence_char).count();
+    // A backtick info string cannot itself contain backticks.
+    if len < 3 || (fence_char == b'`' && trimmed[len..].contains('`')) {
+        return None;
+    }
+    Some((fence_char, len))
+}
+
+/// Whether a line closes a code fence opened with `len` `fence_char`s.
+#[inline]
+fn code_fence_closes(trimmed: &str, fence_char: u8, len: usize) -> bool {
+    trimmed.len() >= len && trimmed.bytes().all(|b| b == fence_char)
+}
+
+/// CommonMark block kinds that only exist in the `md` profiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MdBlockStart {
+    IndentedCode,
+    ThematicBreak,
+    Quote,
+    List,
+    Table,
+}
+
+/// A CommonMark list item marker (`-`, `*`, `+`, `1.`, `1)`).
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MdListMarker {
+    ordered: bool,
+    /// Bullet character, or the delimiter (`.`/`)`) for ordered lists.
+    marker: u8,
+    number: u64,
+    /// Column where item content starts; continuation lines must reach it.
+    content_indent: usize,
+    /// Bytes to skip on the marker line to reach the content.
+    content_bytes: usize,
+    /// Whether the marker line has no content after the marker.
+    empty: bool,
+}
+
+impl MdListMarker {
+    /// Items with the same marker type belong to the same list.
+    #[inline]
+    fn same_list(&self, other: &MdListMarker) -> bool {
+        self.ordered == other.ordered && self.marker == other.marker
+    }
+}
+
+#[inline]
+pub(crate) fn md_list_marker(text: &str) -> Option<MdListMarker> {
+    let bytes = text.as_bytes();
+    let indent = bytes.iter().take_while(|&&b| b == b' ').count();
+    if indent > 3 {
+        return None;
+    }
+
+    let mut pos = indent;
+    let (ordered, marker, number) = match bytes.get(pos)? {
+        b @ (b'-' | b'*' | b'+') => {
+            pos += 1;
+            (false, *b, 0)
+        }
+        b'0'..=b'9' => {
+            let digits = bytes[pos..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            if digits > 9 {
+                return None;
+            }
+            let number = text[pos..pos + digits].parse().ok()?;
+            pos += digits;
+            match bytes.get(pos)? {
+                b @ (b'.' | b')') => {
+                    pos += 1;
+                    (true, *b, number)
+                }
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+
+    let spaces = bytes[pos..]
+        .iter()
+        .take_while(|&&b| b == b' ' || b == b'\t')
+        .count();
+    let empty = pos + spaces == bytes.len();
+    if spaces == 0 && !empty {
+        return None;
+    }
+    // Five or more spaces start an indented code block inside the item.
+    let padding = if empty || spaces > 4 { 1 } else { spaces };
+
+    Some(MdListMarker {
+        ordered,
+        marker,
+        number,
+        content_indent: pos + padding,
+        content_bytes: (pos + padding).min(bytes.len()),
+        empty,
+    })
+}
+
+/// `***`, `---`, `___`, optionally with spaces between the characters.
+#[inline]
+pub(crate) fn is_md_thematic_break(trimmed: &str) -> bool {
+    let mut chars = trimmed.bytes().filter(|&b| b != b' ' && b != b'\t');
+    let first = match chars.next() {
+        Some(b @ (b'*' | b'-' | b'_')) => b,
+        _ => return false,
+    };
+    let mut count = 1;
+    for b in chars {
+        if b != first {
+            return false;
+        }
+        count += 1;
+    }
+    count >= 3
+}
+
+/// Setext underline (`===` or `---`) level, if the line is one.
+#[inline]
+fn setext_level(line: &Line) -> Option<u8> {
+    if line.indent() >= 4 {
+        return None;
+    }
+    let trimmed = line.trimmed();
+    if !trimmed.is_empty() && trimmed.bytes().all(|b| b == b'=') {
+        Some(1)
+    } else if !trimmed.is_empty() && trimmed.bytes().all(|b| b == b'-') {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Whether a line starts a block that can interrupt a CommonMark paragraph.
+#[inline]
+fn md_interrupts_paragraph(line: &Line) -> bool {
+    if line.indent() >= 4 {
+        return false;
+    }
+    let trimmed = line.text.trim_start();
+    if trimmed.starts_with("```") || trimmed.starts_with("~~~") || trimmed.starts_with("::") {
+        return true;
+    }
+    if trimmed.starts_with('>') || is_md_thematic_break(trimmed) {
+        return true;
+    }
+
+    let hashes = trimmed.bytes().take_while(|&b| b == b'#').count();
+    if (1..=6).contains(&hashes) && matches!(trimmed.as_bytes().get(hashes), None | Some(b' ')) {
+        return true;
+    }
+
+    // Empty items and ordered lists not starting at 1 cannot interrupt a paragraph.
+    match md_list_marker(line.text) {
+        Some(m) => !m.empty && (!m.ordered || m.number == 1),
+        None => false,
+    }
+}
+
+/// Strip an ATX closing sequence (`## Title ##` -> `Title`).
+#[inline]
+fn strip_closing_hashes(s: &str) -> &str {
+    let trimmed = s.trim_end();
+    let without = trimmed.trim_end_matches('#');
+    if without.len() == trimmed.len() {
+        return trimmed;
+    }
+    if without.is_empty() {
+        return without;
+    }
+    if without.ends_with(' ') || without.ends_with('\t') {
+        without.trim_end()
+    } else {
+        trimmed
+    }
+}
+
+/// Byte ranges of the cells of a pipe table row.
+///
+/// Leading and trailing pipes are optional and cells are split on unescaped
+/// `|`, so empty cells keep their position. Ranges include the whitespace
+/// around each cell.
+#[inline]
+fn table_cell_ranges(row: &str) -> Vec<(usize, usize)> {
+    let bytes = row.as_bytes();
+    let mut start = row.len() - row.trim_start().len();
+    let mut end = row.trim_end().len();
+    if start < end && bytes[start] == b'|' {
+        start += 1;
+    }
+    if end > start && bytes[end - 1] == b'|' && (end < start + 2 || bytes[end - 2] != b'\\') {
+        end -= 1;
+    }
+
+    let mut ranges = Vec::with_capacity(8);
+    let mut cell_start = start;
+    let mut i = start;
+    while i < end {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'|' => {
+                ranges.push((cell_start, i));
+                cell_start = i + 1;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    ranges.push((cell_start, end));
+    ranges
+}
+
+/// Read the column alignments of a separator row (`| --- | :-: | --: |`)
+/// into `out`, returning whether the row is one. `out` is left empty if not.
+#[inline]
+fn table_alignments(row: &str, out: &mut Vec<Alignment>) -> bool {
+    out.clear();
+    if !row.contains('-') {
+        return false;
+    }
+    for (start, end) in table_cell_ranges(row) {
+        let cell = row[start..end].trim();
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        if dashes.is_empty() || !dashes.bytes().all(|b| b == b'-') {
+            out.clear();
+            return false;
+        }
+        out.push(match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => Alignment::Center,
+            (true, false) => Alignment::Left,
+            (false, true) => Alignment::Right,
+            (false, false) => Alignment::None,
+        });
+    }
+    true
 }
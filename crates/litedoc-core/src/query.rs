@@ -0,0 +1,732 @@
+//! Selecting nodes with CSS-like selectors.
+//!
+//! A selector names the nodes to find and how they relate to each other.
+//! [`Document::select`] returns the matching nodes in document order, each
+//! with its span:
+//!
+//! ```rust
+//! use litedoc_core::{Parser, Profile};
+//!
+//! let input = "@modules tables\n\n## Findings\n\n::table\n| a | b |\n| --- | --- |\n| 1 | 2 |\n::\n\n\
+//!     ::callout type=warning\nFlaky on *arm64*.\n::";
+//! let doc = Parser::new(Profile::Litedoc).parse(input).unwrap();
+//!
+//! let tables = doc.select(r#"heading[level=2]:contains("Findings") + table"#).unwrap();
+//! assert_eq!(tables[0].name(), "table");
+//!
+//! let warnings = doc.select("callout[type=warning]").unwrap();
+//! assert_eq!(warnings[0].text(), "Flaky on arm64.");
+//! ```
+//!
+//! ## Node types
+//!
+//! Blocks are named after their directive: `heading`, `paragraph`, `list`,
+//! `code`, `callout`, `quote`, `figure`, `table`, `footnotes`, `sources`,
+//! `math`, `thematic-break`, `html` and `raw`; custom blocks by their
+//! registered name. Their parts are `item`, `row`, `cell`, `footnote` and
+//! `source`. Inline nodes are `text`, `emphasis`, `strong`,
+//! `strikethrough`, `code-span`, `link`, `autolink`, `footnote-ref`,
+//! `citation`, `mention`, `inline-math`, `hard-break` and `soft-break`.
+//! `*` matches any node.
+//!
+//! ## Filters
+//!
+//! - `[name]` - the node has the attribute
+//! - `[name=value]`, `[name!=value]` - it is (not) equal to `value`
+//! - `[name^=value]`, `[name$=value]`, `[name*=value]` - it starts with,
+//!   ends with or contains `value`
+//! - `:contains("text")` - the node's plain text contains `text`
+//! - `:first-child`, `:last-child`, `:nth-child(n)` - position among siblings
+//!
+//! Attributes are the node's fields (`level` and `id` of headings, `kind` and
+//! `start` of lists, `checked` of items, `lang` of code, `type` and `title`
+//! of callouts, `header` of rows, `url` of links, ...) followed by its
+//! written attributes. Values are compared as text; quote values that
+//! contain spaces or `]`.
+//!
+//! ## Combinators
+//!
+//! - `a b` - `b` anywhere inside `a`
+//! - `a > b` - `b` directly inside `a`
+//! - `a + b` - `b` immediately after its sibling `a`
+//! - `a ~ b` - `b` after its sibling `a`
+//! - `a, b` - either selector
+
+use std::slice;
+
+use crate::ast::{
+    AttrValue, Block, CowStr, CustomBlock, CustomBody, Document, FootnoteDef, Inline, ListItem,
+    ListKind, Source, TableCell, TableRow,
+};
+use crate::error::ParseError;
+use crate::format::format_attr_value;
+use crate::resolve::push_plain_text;
+use crate::span::Span;
+
+/// A node matched by a selector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Node<'d> {
+    /// Block (`heading`, `table`, ...)
+    Block(&'d Block<'d>),
+    /// List item (`item`)
+    ListItem(&'d ListItem<'d>),
+    /// Table row (`row`)
+    TableRow(&'d TableRow<'d>),
+    /// Table cell (`cell`)
+    TableCell(&'d TableCell<'d>),
+    /// Footnote definition (`footnote`)
+    FootnoteDef(&'d FootnoteDef<'d>),
+    /// Sources entry (`source`)
+    Source(&'d Source<'d>),
+    /// Inline node (`link`, `mention`, ...)
+    Inline(&'d Inline<'d>),
+}
+
+impl<'d> Node<'d> {
+    /// The node's type name, as written in selectors.
+    pub fn name(&self) -> &'d str {
+        match *self {
+            Node::Block(block) => match block {
+                Block::Heading(_) => "heading",
+                Block::Paragraph(_) => "paragraph",
+                Block::List(_) => "list",
+                Block::CodeBlock(_) => "code",
+                Block::Callout(_) => "callout",
+                Block::Quote(_) => "quote",
+                Block::Figure(_) => "figure",
+                Block::Table(_) => "table",
+                Block::Footnotes(_) => "footnotes",
+                Block::Sources(_) => "sources",
+                Block::Math(_) => "math",
+                Block::ThematicBreak(_) => "thematic-break",
+                Block::Html(_) => "html",
+                Block::Raw(_) => "raw",
+                Block::Custom(c) => &c.name,
+            },
+            Node::ListItem(_) => "item",
+            Node::TableRow(_) => "row",
+            Node::TableCell(_) => "cell",
+            Node::FootnoteDef(_) => "footnote",
+            Node::Source(_) => "source",
+            Node::Inline(inline) => match inline {
+                Inline::Text(_) => "text",
+                Inline::Emphasis(_) => "emphasis",
+                Inline::Strong(_) => "strong",
+                Inline::CodeSpan(_) => "code-span",
+                Inline::Link(_) => "link",
+                Inline::AutoLink(_) => "autolink",
+                Inline::Strikethrough(_) => "strikethrough",
+                Inline::FootnoteRef(_) => "footnote-ref",
+                Inline::Citation(_) => "citation",
+                Inline::Mention(_) => "mention",
+                Inline::Math(_) => "inline-math",
+                Inline::HardBreak(_) => "hard-break",
+                Inline::SoftBreak(_) => "soft-break",
+            },
+        }
+    }
+
+    /// Source span of the node.
+    pub fn span(&self) -> Span {
+        match *self {
+            Node::Block(block) => block.span(),
+            Node::ListItem(item) => item.span,
+            Node::TableRow(row) => row.span,
+            Node::TableCell(cell) => cell.span,
+            Node::FootnoteDef(def) => def.span,
+            Node::Source(source) => source.span,
+            Node::Inline(inline) => inline.span(),
+        }
+    }
+
+    /// Child nodes in source order.
+    pub fn children(&self) -> Vec<Node<'d>> {
+        fn blocks<'d>(blocks: &'d [Block<'d>]) -> Vec<Node<'d>> {
+            blocks.iter().map(Node::Block).collect()
+        }
+        fn inlines<'d>(inlines: &'d [Inline<'d>]) -> Vec<Node<'d>> {
+            inlines.iter().map(Node::Inline).collect()
+        }
+
+        match *self {
+            Node::Block(block) => match block {
+                Block::Heading(h) => inlines(&h.content),
+                Block::Paragraph(p) => inlines(&p.content),
+                Block::List(l) => l.items.iter().map(Node::ListItem).collect(),
+                Block::Callout(c) => blocks(&c.blocks),
+                Block::Quote(q) => blocks(&q.blocks),
+                Block::Table(t) => t.rows.iter().map(Node::TableRow).collect(),
+                Block::Footnotes(f) => f.defs.iter().map(Node::FootnoteDef).collect(),
+                Block::Sources(s) => s.entries.iter().map(Node::Source).collect(),
+                Block::Custom(c) => match &c.body {
+                    CustomBody::Blocks(b) => blocks(b),
+                    CustomBody::Content(_) => Vec::new(),
+                },
+                Block::CodeBlock(_)
+                | Block::Figure(_)
+                | Block::Math(_)
+                | Block::ThematicBreak(_)
+                | Block::Html(_)
+                | Block::Raw(_) => Vec::new(),
+            },
+            Node::ListItem(item) => blocks(&item.blocks),
+            Node::TableRow(row) => row.cells.iter().map(Node::TableCell).collect(),
+            Node::TableCell(cell) => inlines(&cell.content),
+            Node::FootnoteDef(def) => blocks(&def.blocks),
+            Node::Source(_) => Vec::new(),
+            Node::Inline(inline) => match inline {
+                Inline::Emphasis(e) => inlines(&e.content),
+                Inline::Strong(s) => inlines(&s.content),
+                Inline::Strikethrough(s) => inlines(&s.content),
+                Inline::Link(l) => inlines(&l.label),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    /// Plain text of the node, without markup.
+    ///
+    /// Text of child blocks, items and rows is joined with newlines, and that
+    /// of the cells in a row with tabs.
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        self.push_text(&mut out);
+        out
+    }
+
+    fn push_text(&self, out: &mut String) {
+        match *self {
+            Node::Block(Block::Heading(h)) => push_plain_text(out, &h.content),
+            Node::Block(Block::Paragraph(p)) => push_plain_text(out, &p.content),
+            Node::Block(Block::CodeBlock(c)) => out.push_str(&c.content),
+            Node::Block(Block::Figure(f)) => out.push_str(f.caption.as_deref().unwrap_or(&f.alt)),
+            Node::Block(Block::Math(m)) => out.push_str(&m.content),
+            Node::Block(Block::Html(h)) => out.push_str(&h.content),
+            Node::Block(Block::Raw(r)) => out.push_str(&r.content),
+            Node::Block(Block::Custom(CustomBlock {
+                body: CustomBody::Content(content),
+                ..
+            })) => out.push_str(content),
+            Node::TableRow(row) => {
+                for (i, cell) in row.cells.iter().enumerate() {
+                    if i > 0 {
+                        out.push('\t');
+                    }
+                    push_plain_text(out, &cell.content);
+                }
+            }
+            Node::TableCell(cell) => push_plain_text(out, &cell.content),
+            Node::Source(source) => out.push_str(source.title.as_deref().unwrap_or(&source.id)),
+            Node::Inline(inline) => push_plain_text(out, slice::from_ref(inline)),
+            _ => {
+                for (i, child) in self.children().iter().enumerate() {
+                    if i > 0 {
+                        out.push('\n');
+                    }
+                    child.push_text(out);
+                }
+            }
+        }
+    }
+
+    /// The value of attribute `key` as text: a field of the node, or else
+    /// one of its written attributes.
+    fn attr(&self, key: &str) -> Option<String> {
+        let field = match (*self, key) {
+            (Node::Block(Block::Heading(h)), "level") => Some(h.level.to_string()),
+            (Node::Block(Block::Heading(h)), "id") => h.id.as_deref().map(str::to_string),
+            (Node::Block(Block::List(l)), "kind") => Some(match l.kind {
+                ListKind::Ordered => "ordered".to_string(),
+                ListKind::Unordered => "unordered".to_string(),
+            }),
+            (Node::Block(Block::List(l)), "start") => l.start.map(|s| s.to_string()),
+            (Node::Block(Block::CodeBlock(c)), "lang") if !c.lang.is_empty() => {
+                Some(c.lang.to_string())
+            }
+            (Node::Block(Block::Callout(c)), "type") => Some(c.kind.to_string()),
+            (Node::Block(Block::Callout(c)), "title") => c.title.as_deref().map(str::to_string),
+            (Node::Block(Block::Figure(f)), "src") => Some(f.src.to_string()),
+            (Node::Block(Block::Figure(f)), "alt") => Some(f.alt.to_string()),
+            (Node::Block(Block::Figure(f)), "caption") => f.caption.as_deref().map(str::to_string),
+            (Node::Block(Block::Math(m)), "display") => Some(m.display.to_string()),
+            (Node::ListItem(item), "checked") => item.checked.map(|c| c.to_string()),
+            (Node::TableRow(row), "header") => Some(row.header.to_string()),
+            (Node::FootnoteDef(def), "label") => Some(def.label.to_string()),
+            (Node::Source(s), "id") => Some(s.id.to_string()),
+            (Node::Source(s), "title") => s.title.as_deref().map(str::to_string),
+            (Node::Source(s), "url") => s.url.as_deref().map(str::to_string),
+            (Node::Inline(Inline::Link(l)), "url") => Some(l.url.to_string()),
+            (Node::Inline(Inline::Link(l)), "kind") => Some(l.kind.name().to_string()),
+            (Node::Inline(Inline::Link(l)), "title") => l.title.as_deref().map(str::to_string),
+            (Node::Inline(Inline::AutoLink(a)), "url") => Some(a.url.to_string()),
+            (Node::Inline(Inline::FootnoteRef(f)), "label") => Some(f.label.to_string()),
+            (Node::Inline(Inline::Citation(c)), "key") => Some(c.key.to_string()),
+            (Node::Inline(Inline::Mention(m)), "kind") => Some(m.kind.to_string()),
+            (Node::Inline(Inline::Mention(m)), "id") => Some(m.id.to_string()),
+            _ => None,
+        };
+        field.or_else(|| {
+            self.attrs()
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| match value {
+                    AttrValue::Str(s) => s.to_string(),
+                    value => format_attr_value(value),
+                })
+        })
+    }
+
+    fn attrs(&self) -> &'d [(CowStr<'d>, AttrValue<'d>)] {
+        match *self {
+            Node::Block(block) => match block {
+                Block::List(l) => &l.attrs,
+                Block::CodeBlock(c) => &c.attrs,
+                Block::Callout(c) => &c.attrs,
+                Block::Quote(q) => &q.attrs,
+                Block::Figure(f) => &f.attrs,
+                Block::Table(t) => &t.attrs,
+                Block::Sources(s) => &s.attrs,
+                Block::Math(m) => &m.attrs,
+                Block::Raw(r) => &r.attrs,
+                Block::Custom(c) => &c.attrs,
+                _ => &[],
+            },
+            Node::Source(source) => &source.attrs,
+            _ => &[],
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Node<'_> {
+    /// Serializes the node itself, in its canonical shape.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Node::Block(block) => block.serialize(serializer),
+            Node::ListItem(item) => item.serialize(serializer),
+            Node::TableRow(row) => row.serialize(serializer),
+            Node::TableCell(cell) => cell.serialize(serializer),
+            Node::FootnoteDef(def) => def.serialize(serializer),
+            Node::Source(source) => source.serialize(serializer),
+            Node::Inline(inline) => inline.serialize(serializer),
+        }
+    }
+}
+
+impl Document<'_> {
+    /// The nodes matching `selector`, in document order.
+    ///
+    /// See the [`query`](crate::query) module for the selector syntax.
+    /// Errors are spanned by byte offsets into `selector`.
+    pub fn select(&self, selector: &str) -> Result<Vec<Node<'_>>, ParseError> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+}
+
+/// A parsed selector, reusable across documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    alternatives: Vec<Complex>,
+}
+
+/// Compounds joined by combinators: `combinators[i]` sits between
+/// `compounds[i]` and `compounds[i + 1]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Complex {
+    compounds: Vec<Compound>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+    Adjacent,
+    Sibling,
+}
+
+/// A node type (`None` for `*`) and the filters it must pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Compound {
+    name: Option<String>,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Filter {
+    Attr {
+        key: String,
+        test: Option<(AttrOp, String)>,
+    },
+    Contains(String),
+    FirstChild,
+    LastChild,
+    NthChild(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AttrOp {
+    Equals,
+    NotEquals,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+impl Selector {
+    /// Parse a selector.
+    pub fn parse(selector: &str) -> Result<Self, ParseError> {
+        SelectorParser {
+            src: selector,
+            pos: 0,
+        }
+        .parse()
+    }
+
+    /// The nodes of `doc` matching this selector, in document order.
+    pub fn select<'d>(&self, doc: &'d Document<'_>) -> Vec<Node<'d>> {
+        let tree = Tree::new(doc);
+        let mut selected = vec![false; tree.entries.len()];
+        for complex in &self.alternatives {
+            for (selected, matched) in selected.iter_mut().zip(complex.matches(&tree)) {
+                *selected |= matched;
+            }
+        }
+        tree.entries
+            .iter()
+            .zip(selected)
+            .filter(|(_, selected)| *selected)
+            .map(|(entry, _)| entry.node)
+            .collect()
+    }
+}
+
+impl Complex {
+    /// Which entries match, decided one compound at a time: after step `i`,
+    /// `matched[id]` says whether entry `id` matches compound `i` with the
+    /// compounds before it matching its related entries. Entries are in
+    /// pre-order, so parents and previous siblings are settled first and
+    /// each step is a single pass.
+    fn matches(&self, tree: &Tree) -> Vec<bool> {
+        let entries = &tree.entries;
+        let mut matched: Vec<bool> = (0..entries.len())
+            .map(|id| self.compounds[0].matches(tree, id))
+            .collect();
+        for (compound, &combinator) in self.compounds[1..].iter().zip(&self.combinators) {
+            let link = |entry: &Entry| match combinator {
+                Combinator::Descendant | Combinator::Child => entry.parent,
+                Combinator::Adjacent | Combinator::Sibling => entry.prev,
+            };
+            // Whether the entry or one of its ancestors (or earlier
+            // siblings) matched the previous compound.
+            let mut reached = vec![false; entries.len()];
+            for (id, entry) in entries.iter().enumerate() {
+                reached[id] = matched[id] || link(entry).is_some_and(|l| reached[l]);
+            }
+            matched = entries
+                .iter()
+                .enumerate()
+                .map(|(id, entry)| {
+                    let related = match combinator {
+                        Combinator::Child | Combinator::Adjacent => {
+                            link(entry).is_some_and(|l| matched[l])
+                        }
+                        Combinator::Descendant | Combinator::Sibling => {
+                            link(entry).is_some_and(|l| reached[l])
+                        }
+                    };
+                    related && compound.matches(tree, id)
+                })
+                .collect();
+        }
+        matched
+    }
+}
+
+impl Compound {
+    fn matches(&self, tree: &Tree, id: usize) -> bool {
+        let entry = &tree.entries[id];
+        if self.name.as_ref().is_some_and(|n| n != entry.node.name()) {
+            return false;
+        }
+        self.filters.iter().all(|filter| match filter {
+            Filter::Attr { key, test } => match (entry.node.attr(key), test) {
+                (None, Some((AttrOp::NotEquals, _))) => true,
+                (None, _) => false,
+                (Some(_), None) => true,
+                (Some(actual), Some((op, value))) => match op {
+                    AttrOp::Equals => actual == *value,
+                    AttrOp::NotEquals => actual != *value,
+                    AttrOp::Prefix => actual.starts_with(value.as_str()),
+                    AttrOp::Suffix => actual.ends_with(value.as_str()),
+                    AttrOp::Substring => actual.contains(value.as_str()),
+                },
+            },
+            Filter::Contains(text) => entry.node.text().contains(text.as_str()),
+            Filter::FirstChild => entry.index == 0,
+            Filter::LastChild => entry.index + 1 == entry.siblings,
+            Filter::NthChild(n) => entry.index + 1 == *n,
+        })
+    }
+}
+
+/// Every node of a document in pre-order, linked to its parent and its
+/// previous sibling.
+struct Tree<'d> {
+    entries: Vec<Entry<'d>>,
+}
+
+struct Entry<'d> {
+    node: Node<'d>,
+    parent: Option<usize>,
+    prev: Option<usize>,
+    /// Position among siblings, from 0.
+    index: usize,
+    siblings: usize,
+}
+
+impl<'d> Tree<'d> {
+    fn new(doc: &'d Document<'_>) -> Self {
+        let mut tree = Tree {
+            entries: Vec::new(),
+        };
+        tree.push_all(doc.blocks.iter().map(Node::Block).collect(), None);
+        tree
+    }
+
+    fn push_all(&mut self, nodes: Vec<Node<'d>>, parent: Option<usize>) {
+        let siblings = nodes.len();
+        let mut prev = None;
+        for (index, node) in nodes.into_iter().enumerate() {
+            let id = self.entries.len();
+            self.entries.push(Entry {
+                node,
+                parent,
+                prev,
+                index,
+                siblings,
+            });
+            self.push_all(node.children(), Some(id));
+            prev = Some(id);
+        }
+    }
+}
+
+struct SelectorParser<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+impl SelectorParser<'_> {
+    fn parse(mut self) -> Result<Selector, ParseError> {
+        let mut alternatives = vec![self.complex()?];
+        while self.eat(',') {
+            alternatives.push(self.complex()?);
+        }
+        Ok(Selector { alternatives })
+    }
+
+    fn complex(&mut self) -> Result<Complex, ParseError> {
+        self.skip_whitespace();
+        let mut compounds = vec![self.compound()?];
+        let mut combinators = Vec::new();
+        loop {
+            let spaced = self.skip_whitespace();
+            let combinator = match self.peek() {
+                None | Some(',') => break,
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::Adjacent,
+                Some('~') => Combinator::Sibling,
+                Some(_) if spaced => Combinator::Descendant,
+                Some(c) => return Err(self.error(&format!("unexpected `{c}`"))),
+            };
+            if combinator != Combinator::Descendant {
+                self.bump();
+                self.skip_whitespace();
+            }
+            combinators.push(combinator);
+            compounds.push(self.compound()?);
+        }
+        Ok(Complex {
+            compounds,
+            combinators,
+        })
+    }
+
+    fn compound(&mut self) -> Result<Compound, ParseError> {
+        let start = self.pos;
+        let name = if self.eat('*') {
+            None
+        } else {
+            Some(self.ident().to_string()).filter(|n| !n.is_empty())
+        };
+        let mut filters = Vec::new();
+        loop {
+            match self.peek() {
+                Some('[') => filters.push(self.attribute()?),
+                Some(':') => filters.push(self.pseudo_class()?),
+                _ => break,
+            }
+        }
+        if self.pos == start {
+            return Err(self.error("expected a node type"));
+        }
+        Ok(Compound { name, filters })
+    }
+
+    fn attribute(&mut self) -> Result<Filter, ParseError> {
+        self.bump();
+        self.skip_whitespace();
+        let key = self.ident().to_string();
+        if key.is_empty() {
+            return Err(self.error("expected an attribute name"));
+        }
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Filter::Attr { key, test: None });
+        }
+        let op = match self.peek() {
+            Some('=') => AttrOp::Equals,
+            Some('!') => AttrOp::NotEquals,
+            Some('^') => AttrOp::Prefix,
+            Some('$') => AttrOp::Suffix,
+            Some('*') => AttrOp::Substring,
+            _ => return Err(self.error("expected `]` or an operator")),
+        };
+        if op != AttrOp::Equals {
+            self.bump();
+        }
+        if !self.eat('=') {
+            return Err(self.error("expected `=`"));
+        }
+        self.skip_whitespace();
+        let value = self.value(']')?;
+        self.skip_whitespace();
+        if !self.eat(']') {
+            return Err(self.error("expected `]`"));
+        }
+        Ok(Filter::Attr {
+            key,
+            test: Some((op, value)),
+        })
+    }
+
+    fn pseudo_class(&mut self) -> Result<Filter, ParseError> {
+        let start = self.pos;
+        self.bump();
+        let contains = match self.ident() {
+            "first-child" => return Ok(Filter::FirstChild),
+            "last-child" => return Ok(Filter::LastChild),
+            "contains" => true,
+            "nth-child" => false,
+            _ => {
+                let span = Span::new(start as u32, self.pos as u32);
+                let reason = format!("unknown pseudo-class `{}`", &self.src[start..self.pos]);
+                return Err(ParseError::invalid_selector(&reason, Some(span)));
+            }
+        };
+        if !self.eat('(') {
+            return Err(self.error("expected `(`"));
+        }
+        self.skip_whitespace();
+        let filter = if contains {
+            Filter::Contains(self.value(')')?)
+        } else {
+            let digits = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.bump();
+            }
+            match self.src[digits..self.pos].parse() {
+                Ok(n) if n > 0 => Filter::NthChild(n),
+                _ => {
+                    let span = Span::new(digits as u32, self.pos as u32);
+                    return Err(ParseError::invalid_selector(
+                        "expected a position from 1",
+                        Some(span),
+                    ));
+                }
+            }
+        };
+        self.skip_whitespace();
+        if !self.eat(')') {
+            return Err(self.error("expected `)`"));
+        }
+        Ok(filter)
+    }
+
+    /// A quoted string, or bare text up to whitespace or `end`.
+    fn value(&mut self, end: char) -> Result<String, ParseError> {
+        let Some(quote @ ('"' | '\'')) = self.peek() else {
+            let start = self.pos;
+            while self.peek().is_some_and(|c| c != end && !c.is_whitespace()) {
+                self.bump();
+            }
+            if self.pos == start {
+                return Err(self.error("expected a value"));
+            }
+            return Ok(self.src[start..self.pos].to_string());
+        };
+        let start = self.pos;
+        self.bump();
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some(c) if c == quote => return Ok(value),
+                Some('\\') => match self.bump() {
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                Some(c) => value.push(c),
+                None => break,
+            }
+        }
+        let span = Span::new(start as u32, self.pos as u32);
+        Err(ParseError::invalid_selector("unclosed string", Some(span)))
+    }
+
+    fn ident(&mut self) -> &str {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            self.bump();
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// Skip whitespace, returning whether there was any.
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+        self.pos > start
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// An error at the current character.
+    fn error(&self, reason: &str) -> ParseError {
+        let end = self.pos + self.peek().map_or(0, char::len_utf8);
+        let span = Span::new(self.pos as u32, end as u32);
+        ParseError::invalid_selector(reason, Some(span))
+    }
+}
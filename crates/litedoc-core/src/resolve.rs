@@ -0,0 +1,166 @@
+//! Cross-reference resolution for parsed documents.
+//!
+//! Headings carry an anchor id, either written as `{#id}` or derived from the
+//! heading text with [`slugify`], and `::sources` entries carry the keys that
+//! `[@key]` citations use. [`resolve_references`] checks every internal
+//! `[[#id]]` link and every citation against those definitions.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::{Citation, Document, Heading, Inline, Link, LinkKind, Source};
+use crate::error::ParseError;
+use crate::span::Span;
+use crate::visit::{walk_link, Visit};
+
+/// Derive an anchor id from heading content.
+///
+/// The plain text is lowercased; letters and digits are kept, runs of
+/// whitespace, `-` and `_` become a single `-`, and everything else is
+/// dropped. The result may be empty.
+pub fn slugify(content: &[Inline]) -> String {
+    let mut text = String::new();
+    push_plain_text(&mut text, content);
+
+    let mut slug = String::with_capacity(text.len());
+    let mut pending_dash = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            if pending_dash && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_dash = false;
+            slug.extend(c.to_lowercase());
+        } else if c.is_whitespace() || c == '-' || c == '_' {
+            pending_dash = true;
+        }
+    }
+    slug
+}
+
+/// Slug `content` into an id not yet in `taken`, adding a `-1`, `-2`, ...
+/// suffix as needed, and record it. `None` when there is nothing to slug.
+pub(crate) fn unique_slug(content: &[Inline], taken: &mut HashSet<String>) -> Option<String> {
+    let slug = slugify(content);
+    if slug.is_empty() {
+        return None;
+    }
+    let mut id = slug.clone();
+    let mut n = 0;
+    while taken.contains(&id) {
+        n += 1;
+        id = format!("{slug}-{n}");
+    }
+    taken.insert(id.clone());
+    Some(id)
+}
+
+pub(crate) fn push_plain_text(out: &mut String, inlines: &[Inline]) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(t) => out.push_str(&t.content),
+            Inline::CodeSpan(c) => out.push_str(&c.content),
+            Inline::Math(m) => out.push_str(&m.content),
+            Inline::Emphasis(e) => push_plain_text(out, &e.content),
+            Inline::Strong(s) => push_plain_text(out, &s.content),
+            Inline::Strikethrough(s) => push_plain_text(out, &s.content),
+            Inline::Link(l) => push_plain_text(out, &l.label),
+            Inline::AutoLink(a) => out.push_str(&a.url),
+            Inline::Mention(m) => out.push_str(&m.id),
+            Inline::HardBreak(_) | Inline::SoftBreak(_) => out.push(' '),
+            Inline::FootnoteRef(_) | Inline::Citation(_) => {}
+        }
+    }
+}
+
+/// Check internal references in `doc` and return one error per problem.
+///
+/// A `[[#id]]` link resolves when some heading in the document has that id,
+/// and a `[@key]` citation when some `::sources` entry has that id. Citations
+/// of unknown keys, sources that are never cited and source ids listed twice
+/// are all reported. A parser built with
+/// [`with_reference_checks`](crate::Parser::with_reference_checks) records
+/// these errors itself; otherwise call this after parsing, or for documents
+/// that were built or edited by hand.
+pub fn resolve_references(doc: &Document) -> Vec<ParseError> {
+    let mut defs = Definitions::default();
+    defs.visit_document(doc);
+
+    let mut check = Check {
+        errors: std::mem::take(&mut defs.errors),
+        defs: &mut defs,
+    };
+    check.visit_document(doc);
+    let mut errors = check.errors;
+
+    let mut unused: Vec<_> = defs
+        .sources
+        .iter()
+        .filter(|(_, (_, cited))| !cited)
+        .map(|(id, (span, _))| (*span, *id))
+        .collect();
+    unused.sort_by_key(|(span, _)| span.start);
+    for (span, id) in unused {
+        errors.push(ParseError::unused_definition(&format!("@{id}"), Some(span)));
+    }
+    errors
+}
+
+/// Everything in a document that a reference can point to.
+#[derive(Default)]
+struct Definitions<'d> {
+    heading_ids: HashSet<&'d str>,
+    /// Source ids with the entry span and whether a citation uses them.
+    sources: HashMap<&'d str, (Span, bool)>,
+    /// Source ids listed twice.
+    errors: Vec<ParseError>,
+}
+
+impl<'d> Visit<'d> for Definitions<'d> {
+    fn visit_heading(&mut self, heading: &'d Heading<'d>) {
+        if let Some(id) = &heading.id {
+            self.heading_ids.insert(id);
+        }
+    }
+
+    fn visit_source(&mut self, source: &'d Source<'d>) {
+        if self.sources.contains_key(source.id.as_ref()) {
+            let context = format!("source id (duplicate @{})", source.id);
+            self.errors
+                .push(ParseError::invalid_syntax(&context, Some(source.span)));
+        } else {
+            self.sources.insert(&source.id, (source.span, false));
+        }
+    }
+}
+
+/// Checks every reference against the collected definitions.
+struct Check<'r, 'd> {
+    defs: &'r mut Definitions<'d>,
+    errors: Vec<ParseError>,
+}
+
+impl<'ast> Visit<'ast> for Check<'_, '_> {
+    fn visit_link(&mut self, link: &'ast Link<'ast>) {
+        // A bare `#` is already reported as a malformed URL.
+        let id = link.url.strip_prefix('#').unwrap_or_default();
+        if link.kind == LinkKind::Fragment && !id.is_empty() && !self.defs.heading_ids.contains(id)
+        {
+            self.errors
+                .push(ParseError::unresolved_reference(&link.url, Some(link.span)));
+        }
+        walk_link(self, link);
+    }
+
+    fn visit_citation(&mut self, citation: &'ast Citation<'ast>) {
+        match self.defs.sources.get_mut(citation.key.as_ref()) {
+            Some((_, cited)) => *cited = true,
+            None => {
+                let target = format!("@{}", citation.key);
+                self.errors.push(ParseError::unresolved_reference(
+                    &target,
+                    Some(citation.span),
+                ));
+            }
+        }
+    }
+}
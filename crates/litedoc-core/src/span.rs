@@ -1,7 +1,8 @@
 //! Source location tracking for AST nodes.
 //!
 //! Every AST node includes a `Span` indicating its position in the source text.
-//! This enables precise error reporting and source mapping.
+//! This enables precise error reporting and source mapping. A [`LineIndex`]
+//! turns those byte offsets into lines and columns for display.
 
 /// A byte range in the source text.
 ///
@@ -25,6 +26,15 @@ pub struct Span {
 }
 
 impl Span {
+    /// Placeholder span for nodes that were not parsed from any input, such
+    /// as those made by [`DocumentBuilder`](crate::builder::DocumentBuilder).
+    ///
+    /// It is empty and lies past the end of any real input.
+    pub const SYNTHETIC: Span = Span {
+        start: u32::MAX,
+        end: u32::MAX,
+    };
+
     /// Create a new span from byte offsets.
     #[inline]
     pub const fn new(start: u32, end: u32) -> Self {
@@ -49,6 +59,12 @@ impl Span {
         offset >= self.start && offset < self.end
     }
 
+    /// Check if this is [`Span::SYNTHETIC`].
+    #[inline]
+    pub const fn is_synthetic(&self) -> bool {
+        self.start == u32::MAX && self.end == u32::MAX
+    }
+
     /// Merge two spans into one covering both.
     #[inline]
     pub fn merge(self, other: Span) -> Span {
@@ -58,3 +74,125 @@ impl Span {
         }
     }
 }
+
+/// A position as line and column, all counted from 1.
+///
+/// The column is given in three units: bytes, Unicode scalar values (Rust
+/// `char`s and Python `str` indices) and UTF-16 code units (JavaScript
+/// strings and LSP positions).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineCol {
+    /// Line number; lines end at `\n`.
+    pub line: u32,
+    /// Column in bytes.
+    pub byte_column: u32,
+    /// Column in Unicode scalar values.
+    pub char_column: u32,
+    /// Column in UTF-16 code units.
+    pub utf16_column: u32,
+}
+
+/// Line starts of an input, for converting its byte offsets to [`LineCol`].
+///
+/// Build one per input and reuse it for every span of that input:
+///
+/// ```rust
+/// use litedoc_core::span::{LineIndex, Span};
+///
+/// let input = "# Title\n\nCafé 🦀 **open";
+/// let index = LineIndex::new(input);
+/// let (start, end) = index.range(Span::new(20, 26));
+/// assert_eq!((start.line, start.byte_column), (3, 12));
+/// assert_eq!((start.char_column, start.utf16_column), (8, 9));
+/// assert_eq!(end.char_column, 14);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    text: &'a str,
+    /// Byte offset of the start of each line.
+    line_starts: Vec<u32>,
+}
+
+impl<'a> LineIndex<'a> {
+    /// Index the lines of `text`.
+    pub fn new(text: &'a str) -> Self {
+        let newlines = text
+            .bytes()
+            .enumerate()
+            .filter(|&(_, b)| b == b'\n')
+            .map(|(i, _)| i as u32 + 1);
+        Self {
+            text,
+            line_starts: std::iter::once(0).chain(newlines).collect(),
+        }
+    }
+
+    /// Number of lines; text after the last `\n`, even if empty, is a line.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Line and column of byte `offset`.
+    ///
+    /// Offsets inside a character map to the start of that character, and
+    /// offsets past the end, such as those of [`Span::SYNTHETIC`], to the end
+    /// of the input.
+    pub fn line_col(&self, offset: u32) -> LineCol {
+        let mut offset = (offset as usize).min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line_starts.partition_point(|&s| s as usize <= offset) - 1;
+        let before = &self.text[self.line_starts[line] as usize..offset];
+        LineCol {
+            line: line as u32 + 1,
+            byte_column: before.len() as u32 + 1,
+            char_column: before.chars().count() as u32 + 1,
+            utf16_column: before.encode_utf16().count() as u32 + 1,
+        }
+    }
+
+    /// Positions of the start and end of `span`.
+    pub fn range(&self, span: Span) -> (LineCol, LineCol) {
+        (self.line_col(span.start), self.line_col(span.end))
+    }
+}
+
+/// Spans serialize as a `[start, end]` pair.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Span {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.start, self.end).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Span {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (start, end) = <(u32, u32)>::deserialize(deserializer)?;
+        Ok(Span { start, end })
+    }
+}
+
+/// `{"span": [start, end]}` for the span-only variants
+/// (`ThematicBreak`, `HardBreak`, `SoftBreak`), which serialize with a
+/// `"type"` tag and therefore need an object body.
+#[cfg(feature = "serde")]
+pub(crate) mod serde_span_field {
+    use super::Span;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct SpanField {
+        span: Span,
+    }
+
+    pub fn serialize<S: Serializer>(span: &Span, serializer: S) -> Result<S::Ok, S::Error> {
+        SpanField { span: *span }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Span, D::Error> {
+        SpanField::deserialize(deserializer).map(|field| field.span)
+    }
+}
@@ -0,0 +1,578 @@
+//! Traversal of the AST.
+//!
+//! [`Visit`] walks a document by shared reference and [`VisitMut`] by mutable
+//! reference. Every node type has a `visit_*` method whose default calls the
+//! matching `walk_*` function, which visits the node's children in source
+//! order. Override the methods for the nodes a pass cares about and call the
+//! `walk_*` function from the override to keep descending:
+//!
+//! ```
+//! use litedoc_core::ast::Link;
+//! use litedoc_core::visit::{walk_link, Visit};
+//! use litedoc_core::{Parser, Profile};
+//!
+//! #[derive(Default)]
+//! struct Urls<'ast>(Vec<&'ast str>);
+//!
+//! impl<'ast> Visit<'ast> for Urls<'ast> {
+//!     fn visit_link(&mut self, link: &'ast Link<'ast>) {
+//!         self.0.push(&link.url);
+//!         walk_link(self, link);
+//!     }
+//! }
+//!
+//! let doc = Parser::new(Profile::Litedoc)
+//!     .parse("- See [[the docs|https://example.com]].")
+//!     .unwrap();
+//! let mut urls = Urls::default();
+//! urls.visit_document(&doc);
+//! assert_eq!(urls.0, ["https://example.com"]);
+//! ```
+//!
+//! Leaf nodes have no children, so their methods default to doing nothing.
+
+use crate::ast::{
+    AutoLink, Block, Callout, Citation, CodeBlock, CodeSpan, CustomBlock, CustomBody, Document,
+    Emphasis, Figure, FootnoteDef, FootnoteRef, Footnotes, Heading, HtmlBlock, Inline, InlineMath,
+    Link, List, ListItem, MathBlock, Mention, Metadata, Paragraph, Quote, RawBlock, Source,
+    Sources, Strikethrough, Strong, Table, TableCell, TableRow, Text,
+};
+use crate::span::Span;
+
+/// A pass over an AST by shared reference.
+///
+/// Nodes are borrowed for `'ast`, so a visitor may keep references to them.
+pub trait Visit<'ast> {
+    fn visit_document(&mut self, doc: &'ast Document<'ast>) {
+        walk_document(self, doc);
+    }
+
+    fn visit_metadata(&mut self, _metadata: &'ast Metadata<'ast>) {}
+
+    fn visit_block(&mut self, block: &'ast Block<'ast>) {
+        walk_block(self, block);
+    }
+
+    fn visit_heading(&mut self, heading: &'ast Heading<'ast>) {
+        walk_heading(self, heading);
+    }
+
+    fn visit_paragraph(&mut self, paragraph: &'ast Paragraph<'ast>) {
+        walk_paragraph(self, paragraph);
+    }
+
+    fn visit_list(&mut self, list: &'ast List<'ast>) {
+        walk_list(self, list);
+    }
+
+    fn visit_list_item(&mut self, item: &'ast ListItem<'ast>) {
+        walk_list_item(self, item);
+    }
+
+    fn visit_code_block(&mut self, _code: &'ast CodeBlock<'ast>) {}
+
+    fn visit_callout(&mut self, callout: &'ast Callout<'ast>) {
+        walk_callout(self, callout);
+    }
+
+    fn visit_quote(&mut self, quote: &'ast Quote<'ast>) {
+        walk_quote(self, quote);
+    }
+
+    fn visit_figure(&mut self, _figure: &'ast Figure<'ast>) {}
+
+    fn visit_table(&mut self, table: &'ast Table<'ast>) {
+        walk_table(self, table);
+    }
+
+    fn visit_table_row(&mut self, row: &'ast TableRow<'ast>) {
+        walk_table_row(self, row);
+    }
+
+    fn visit_table_cell(&mut self, cell: &'ast TableCell<'ast>) {
+        walk_table_cell(self, cell);
+    }
+
+    fn visit_footnotes(&mut self, footnotes: &'ast Footnotes<'ast>) {
+        walk_footnotes(self, footnotes);
+    }
+
+    fn visit_footnote_def(&mut self, def: &'ast FootnoteDef<'ast>) {
+        walk_footnote_def(self, def);
+    }
+
+    fn visit_sources(&mut self, sources: &'ast Sources<'ast>) {
+        walk_sources(self, sources);
+    }
+
+    fn visit_source(&mut self, _source: &'ast Source<'ast>) {}
+
+    fn visit_math_block(&mut self, _math: &'ast MathBlock<'ast>) {}
+
+    fn visit_thematic_break(&mut self, _span: Span) {}
+
+    fn visit_html_block(&mut self, _html: &'ast HtmlBlock<'ast>) {}
+
+    fn visit_raw_block(&mut self, _raw: &'ast RawBlock<'ast>) {}
+
+    fn visit_custom_block(&mut self, custom: &'ast CustomBlock<'ast>) {
+        walk_custom_block(self, custom);
+    }
+
+    fn visit_inline(&mut self, inline: &'ast Inline<'ast>) {
+        walk_inline(self, inline);
+    }
+
+    fn visit_text(&mut self, _text: &'ast Text<'ast>) {}
+
+    fn visit_emphasis(&mut self, emphasis: &'ast Emphasis<'ast>) {
+        walk_emphasis(self, emphasis);
+    }
+
+    fn visit_strong(&mut self, strong: &'ast Strong<'ast>) {
+        walk_strong(self, strong);
+    }
+
+    fn visit_strikethrough(&mut self, strike: &'ast Strikethrough<'ast>) {
+        walk_strikethrough(self, strike);
+    }
+
+    fn visit_code_span(&mut self, _code: &'ast CodeSpan<'ast>) {}
+
+    fn visit_link(&mut self, link: &'ast Link<'ast>) {
+        walk_link(self, link);
+    }
+
+    fn visit_auto_link(&mut self, _link: &'ast AutoLink<'ast>) {}
+
+    fn visit_footnote_ref(&mut self, _footnote: &'ast FootnoteRef<'ast>) {}
+
+    fn visit_citation(&mut self, _citation: &'ast Citation<'ast>) {}
+
+    fn visit_mention(&mut self, _mention: &'ast Mention<'ast>) {}
+
+    fn visit_inline_math(&mut self, _math: &'ast InlineMath<'ast>) {}
+
+    fn visit_hard_break(&mut self, _span: Span) {}
+
+    fn visit_soft_break(&mut self, _span: Span) {}
+}
+
+/// Visit the metadata, then every top-level block.
+pub fn walk_document<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, doc: &'ast Document<'ast>) {
+    if let Some(metadata) = &doc.metadata {
+        v.visit_metadata(metadata);
+    }
+    for block in &doc.blocks {
+        v.visit_block(block);
+    }
+}
+
+/// Dispatch to the `visit_*` method for the block's variant.
+pub fn walk_block<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, block: &'ast Block<'ast>) {
+    match block {
+        Block::Heading(h) => v.visit_heading(h),
+        Block::Paragraph(p) => v.visit_paragraph(p),
+        Block::List(l) => v.visit_list(l),
+        Block::CodeBlock(c) => v.visit_code_block(c),
+        Block::Callout(c) => v.visit_callout(c),
+        Block::Quote(q) => v.visit_quote(q),
+        Block::Figure(f) => v.visit_figure(f),
+        Block::Table(t) => v.visit_table(t),
+        Block::Footnotes(f) => v.visit_footnotes(f),
+        Block::Sources(s) => v.visit_sources(s),
+        Block::Math(m) => v.visit_math_block(m),
+        Block::ThematicBreak(span) => v.visit_thematic_break(*span),
+        Block::Html(h) => v.visit_html_block(h),
+        Block::Raw(r) => v.visit_raw_block(r),
+        Block::Custom(c) => v.visit_custom_block(c),
+    }
+}
+
+pub fn walk_heading<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, heading: &'ast Heading<'ast>) {
+    walk_inlines(v, &heading.content);
+}
+
+pub fn walk_paragraph<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, paragraph: &'ast Paragraph<'ast>) {
+    walk_inlines(v, &paragraph.content);
+}
+
+pub fn walk_list<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, list: &'ast List<'ast>) {
+    for item in &list.items {
+        v.visit_list_item(item);
+    }
+}
+
+pub fn walk_list_item<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, item: &'ast ListItem<'ast>) {
+    walk_blocks(v, &item.blocks);
+}
+
+pub fn walk_callout<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, callout: &'ast Callout<'ast>) {
+    walk_blocks(v, &callout.blocks);
+}
+
+pub fn walk_quote<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, quote: &'ast Quote<'ast>) {
+    walk_blocks(v, &quote.blocks);
+}
+
+pub fn walk_table<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, table: &'ast Table<'ast>) {
+    for row in &table.rows {
+        v.visit_table_row(row);
+    }
+}
+
+pub fn walk_table_row<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, row: &'ast TableRow<'ast>) {
+    for cell in &row.cells {
+        v.visit_table_cell(cell);
+    }
+}
+
+pub fn walk_table_cell<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, cell: &'ast TableCell<'ast>) {
+    walk_inlines(v, &cell.content);
+}
+
+pub fn walk_footnotes<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, footnotes: &'ast Footnotes<'ast>) {
+    for def in &footnotes.defs {
+        v.visit_footnote_def(def);
+    }
+}
+
+pub fn walk_footnote_def<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, def: &'ast FootnoteDef<'ast>) {
+    walk_blocks(v, &def.blocks);
+}
+
+pub fn walk_sources<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, sources: &'ast Sources<'ast>) {
+    for entry in &sources.entries {
+        v.visit_source(entry);
+    }
+}
+
+/// Visit the body blocks of a custom block; a raw text body has no children.
+pub fn walk_custom_block<'ast, V: Visit<'ast> + ?Sized>(
+    v: &mut V,
+    custom: &'ast CustomBlock<'ast>,
+) {
+    if let CustomBody::Blocks(blocks) = &custom.body {
+        walk_blocks(v, blocks);
+    }
+}
+
+/// Dispatch to the `visit_*` method for the inline's variant.
+pub fn walk_inline<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, inline: &'ast Inline<'ast>) {
+    match inline {
+        Inline::Text(t) => v.visit_text(t),
+        Inline::Emphasis(e) => v.visit_emphasis(e),
+        Inline::Strong(s) => v.visit_strong(s),
+        Inline::CodeSpan(c) => v.visit_code_span(c),
+        Inline::Link(l) => v.visit_link(l),
+        Inline::AutoLink(a) => v.visit_auto_link(a),
+        Inline::Strikethrough(s) => v.visit_strikethrough(s),
+        Inline::FootnoteRef(f) => v.visit_footnote_ref(f),
+        Inline::Citation(c) => v.visit_citation(c),
+        Inline::Mention(m) => v.visit_mention(m),
+        Inline::Math(m) => v.visit_inline_math(m),
+        Inline::HardBreak(span) => v.visit_hard_break(*span),
+        Inline::SoftBreak(span) => v.visit_soft_break(*span),
+    }
+}
+
+pub fn walk_emphasis<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, emphasis: &'ast Emphasis<'ast>) {
+    walk_inlines(v, &emphasis.content);
+}
+
+pub fn walk_strong<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, strong: &'ast Strong<'ast>) {
+    walk_inlines(v, &strong.content);
+}
+
+pub fn walk_strikethrough<'ast, V: Visit<'ast> + ?Sized>(
+    v: &mut V,
+    strike: &'ast Strikethrough<'ast>,
+) {
+    walk_inlines(v, &strike.content);
+}
+
+/// Visit the label of a link.
+pub fn walk_link<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, link: &'ast Link<'ast>) {
+    walk_inlines(v, &link.label);
+}
+
+fn walk_blocks<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, blocks: &'ast [Block<'ast>]) {
+    for block in blocks {
+        v.visit_block(block);
+    }
+}
+
+fn walk_inlines<'ast, V: Visit<'ast> + ?Sized>(v: &mut V, inlines: &'ast [Inline<'ast>]) {
+    for inline in inlines {
+        v.visit_inline(inline);
+    }
+}
+
+/// A pass over an AST by mutable reference.
+///
+/// Passes can rewrite nodes in place, for example to change link URLs, or
+/// replace whole `Block` and `Inline` values from `visit_block_mut` and
+/// `visit_inline_mut`.
+pub trait VisitMut<'a> {
+    fn visit_document_mut(&mut self, doc: &mut Document<'a>) {
+        walk_document_mut(self, doc);
+    }
+
+    fn visit_metadata_mut(&mut self, _metadata: &mut Metadata<'a>) {}
+
+    fn visit_block_mut(&mut self, block: &mut Block<'a>) {
+        walk_block_mut(self, block);
+    }
+
+    fn visit_heading_mut(&mut self, heading: &mut Heading<'a>) {
+        walk_heading_mut(self, heading);
+    }
+
+    fn visit_paragraph_mut(&mut self, paragraph: &mut Paragraph<'a>) {
+        walk_paragraph_mut(self, paragraph);
+    }
+
+    fn visit_list_mut(&mut self, list: &mut List<'a>) {
+        walk_list_mut(self, list);
+    }
+
+    fn visit_list_item_mut(&mut self, item: &mut ListItem<'a>) {
+        walk_list_item_mut(self, item);
+    }
+
+    fn visit_code_block_mut(&mut self, _code: &mut CodeBlock<'a>) {}
+
+    fn visit_callout_mut(&mut self, callout: &mut Callout<'a>) {
+        walk_callout_mut(self, callout);
+    }
+
+    fn visit_quote_mut(&mut self, quote: &mut Quote<'a>) {
+        walk_quote_mut(self, quote);
+    }
+
+    fn visit_figure_mut(&mut self, _figure: &mut Figure<'a>) {}
+
+    fn visit_table_mut(&mut self, table: &mut Table<'a>) {
+        walk_table_mut(self, table);
+    }
+
+    fn visit_table_row_mut(&mut self, row: &mut TableRow<'a>) {
+        walk_table_row_mut(self, row);
+    }
+
+    fn visit_table_cell_mut(&mut self, cell: &mut TableCell<'a>) {
+        walk_table_cell_mut(self, cell);
+    }
+
+    fn visit_footnotes_mut(&mut self, footnotes: &mut Footnotes<'a>) {
+        walk_footnotes_mut(self, footnotes);
+    }
+
+    fn visit_footnote_def_mut(&mut self, def: &mut FootnoteDef<'a>) {
+        walk_footnote_def_mut(self, def);
+    }
+
+    fn visit_sources_mut(&mut self, sources: &mut Sources<'a>) {
+        walk_sources_mut(self, sources);
+    }
+
+    fn visit_source_mut(&mut self, _source: &mut Source<'a>) {}
+
+    fn visit_math_block_mut(&mut self, _math: &mut MathBlock<'a>) {}
+
+    fn visit_thematic_break_mut(&mut self, _span: &mut Span) {}
+
+    fn visit_html_block_mut(&mut self, _html: &mut HtmlBlock<'a>) {}
+
+    fn visit_raw_block_mut(&mut self, _raw: &mut RawBlock<'a>) {}
+
+    fn visit_custom_block_mut(&mut self, custom: &mut CustomBlock<'a>) {
+        walk_custom_block_mut(self, custom);
+    }
+
+    fn visit_inline_mut(&mut self, inline: &mut Inline<'a>) {
+        walk_inline_mut(self, inline);
+    }
+
+    fn visit_text_mut(&mut self, _text: &mut Text<'a>) {}
+
+    fn visit_emphasis_mut(&mut self, emphasis: &mut Emphasis<'a>) {
+        walk_emphasis_mut(self, emphasis);
+    }
+
+    fn visit_strong_mut(&mut self, strong: &mut Strong<'a>) {
+        walk_strong_mut(self, strong);
+    }
+
+    fn visit_strikethrough_mut(&mut self, strike: &mut Strikethrough<'a>) {
+        walk_strikethrough_mut(self, strike);
+    }
+
+    fn visit_code_span_mut(&mut self, _code: &mut CodeSpan<'a>) {}
+
+    fn visit_link_mut(&mut self, link: &mut Link<'a>) {
+        walk_link_mut(self, link);
+    }
+
+    fn visit_auto_link_mut(&mut self, _link: &mut AutoLink<'a>) {}
+
+    fn visit_footnote_ref_mut(&mut self, _footnote: &mut FootnoteRef<'a>) {}
+
+    fn visit_citation_mut(&mut self, _citation: &mut Citation<'a>) {}
+
+    fn visit_mention_mut(&mut self, _mention: &mut Mention<'a>) {}
+
+    fn visit_inline_math_mut(&mut self, _math: &mut InlineMath<'a>) {}
+
+    fn visit_hard_break_mut(&mut self, _span: &mut Span) {}
+
+    fn visit_soft_break_mut(&mut self, _span: &mut Span) {}
+}
+
+/// Visit the metadata, then every top-level block.
+pub fn walk_document_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, doc: &mut Document<'a>) {
+    if let Some(metadata) = &mut doc.metadata {
+        v.visit_metadata_mut(metadata);
+    }
+    walk_blocks_mut(v, &mut doc.blocks);
+}
+
+/// Dispatch to the `visit_*_mut` method for the block's variant.
+pub fn walk_block_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, block: &mut Block<'a>) {
+    match block {
+        Block::Heading(h) => v.visit_heading_mut(h),
+        Block::Paragraph(p) => v.visit_paragraph_mut(p),
+        Block::List(l) => v.visit_list_mut(l),
+        Block::CodeBlock(c) => v.visit_code_block_mut(c),
+        Block::Callout(c) => v.visit_callout_mut(c),
+        Block::Quote(q) => v.visit_quote_mut(q),
+        Block::Figure(f) => v.visit_figure_mut(f),
+        Block::Table(t) => v.visit_table_mut(t),
+        Block::Footnotes(f) => v.visit_footnotes_mut(f),
+        Block::Sources(s) => v.visit_sources_mut(s),
+        Block::Math(m) => v.visit_math_block_mut(m),
+        Block::ThematicBreak(span) => v.visit_thematic_break_mut(span),
+        Block::Html(h) => v.visit_html_block_mut(h),
+        Block::Raw(r) => v.visit_raw_block_mut(r),
+        Block::Custom(c) => v.visit_custom_block_mut(c),
+    }
+}
+
+pub fn walk_heading_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, heading: &mut Heading<'a>) {
+    walk_inlines_mut(v, &mut heading.content);
+}
+
+pub fn walk_paragraph_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, paragraph: &mut Paragraph<'a>) {
+    walk_inlines_mut(v, &mut paragraph.content);
+}
+
+pub fn walk_list_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, list: &mut List<'a>) {
+    for item in &mut list.items {
+        v.visit_list_item_mut(item);
+    }
+}
+
+pub fn walk_list_item_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, item: &mut ListItem<'a>) {
+    walk_blocks_mut(v, &mut item.blocks);
+}
+
+pub fn walk_callout_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, callout: &mut Callout<'a>) {
+    walk_blocks_mut(v, &mut callout.blocks);
+}
+
+pub fn walk_quote_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, quote: &mut Quote<'a>) {
+    walk_blocks_mut(v, &mut quote.blocks);
+}
+
+pub fn walk_table_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, table: &mut Table<'a>) {
+    for row in &mut table.rows {
+        v.visit_table_row_mut(row);
+    }
+}
+
+pub fn walk_table_row_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, row: &mut TableRow<'a>) {
+    for cell in &mut row.cells {
+        v.visit_table_cell_mut(cell);
+    }
+}
+
+pub fn walk_table_cell_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, cell: &mut TableCell<'a>) {
+    walk_inlines_mut(v, &mut cell.content);
+}
+
+pub fn walk_footnotes_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, footnotes: &mut Footnotes<'a>) {
+    for def in &mut footnotes.defs {
+        v.visit_footnote_def_mut(def);
+    }
+}
+
+pub fn walk_footnote_def_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, def: &mut FootnoteDef<'a>) {
+    walk_blocks_mut(v, &mut def.blocks);
+}
+
+pub fn walk_sources_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, sources: &mut Sources<'a>) {
+    for entry in &mut sources.entries {
+        v.visit_source_mut(entry);
+    }
+}
+
+/// Visit the body blocks of a custom block; a raw text body has no children.
+pub fn walk_custom_block_mut<'a, V: VisitMut<'a> + ?Sized>(
+    v: &mut V,
+    custom: &mut CustomBlock<'a>,
+) {
+    if let CustomBody::Blocks(blocks) = &mut custom.body {
+        walk_blocks_mut(v, blocks);
+    }
+}
+
+/// Dispatch to the `visit_*_mut` method for the inline's variant.
+pub fn walk_inline_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, inline: &mut Inline<'a>) {
+    match inline {
+        Inline::Text(t) => v.visit_text_mut(t),
+        Inline::Emphasis(e) => v.visit_emphasis_mut(e),
+        Inline::Strong(s) => v.visit_strong_mut(s),
+        Inline::CodeSpan(c) => v.visit_code_span_mut(c),
+        Inline::Link(l) => v.visit_link_mut(l),
+        Inline::AutoLink(a) => v.visit_auto_link_mut(a),
+        Inline::Strikethrough(s) => v.visit_strikethrough_mut(s),
+        Inline::FootnoteRef(f) => v.visit_footnote_ref_mut(f),
+        Inline::Citation(c) => v.visit_citation_mut(c),
+        Inline::Mention(m) => v.visit_mention_mut(m),
+        Inline::Math(m) => v.visit_inline_math_mut(m),
+        Inline::HardBreak(span) => v.visit_hard_break_mut(span),
+        Inline::SoftBreak(span) => v.visit_soft_break_mut(span),
+    }
+}
+
+pub fn walk_emphasis_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, emphasis: &mut Emphasis<'a>) {
+    walk_inlines_mut(v, &mut emphasis.content);
+}
+
+pub fn walk_strong_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, strong: &mut Strong<'a>) {
+    walk_inlines_mut(v, &mut strong.content);
+}
+
+pub fn walk_strikethrough_mut<'a, V: VisitMut<'a> + ?Sized>(
+    v: &mut V,
+    strike: &mut Strikethrough<'a>,
+) {
+    walk_inlines_mut(v, &mut strike.content);
+}
+
+/// Visit the label of a link.
+pub fn walk_link_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, link: &mut Link<'a>) {
+    walk_inlines_mut(v, &mut link.label);
+}
+
+fn walk_blocks_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, blocks: &mut [Block<'a>]) {
+    for block in blocks {
+        v.visit_block_mut(block);
+    }
+}
+
+fn walk_inlines_mut<'a, V: VisitMut<'a> + ?Sized>(v: &mut V, inlines: &mut [Inline<'a>]) {
+    for inline in inlines {
+        v.visit_inline_mut(inline);
+    }
+}
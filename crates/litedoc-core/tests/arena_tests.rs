@@ -0,0 +1,142 @@
+//! Integration tests for arena parsing (`arena` feature)
+
+#![cfg(feature = "arena")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use litedoc_core::arena::Arena;
+use litedoc_core::{Parser, Profile};
+
+const MESSAGE: &str = "@modules tables, footnotes, mentions\n\n\
+--- meta ---\nfrom: ci\nlabels: [flaky, [arm64, x86]]\n---\n\n\
+# Status for <@task:t-42>\n\n\
+Build is **green** after *three* ~~retries~~ [[runs|https://ci.example]].[^1]\n\n\
+::list\n- parse\n|\n| ::quote\n| Nested **quote**.\n| ::\n- format\n::\n\n\
+::table\n| a | *b* |\n| --- | --- |\n| 1 | 2 |\n::\n\n\
+::footnotes\n[^1]: Flaky on *arm64*.\n::\n\n\
+::callout type=warning title=\"Retry\"\nSee [@ci].\n::\n\n\
+::sources\n- ci title=\"CI logs\" url=https://ci.example\n::\n";
+
+/// Counts fresh allocations per thread, so parallel tests do not interfere.
+/// Growing an allocation in place of another is not counted.
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+/// Run `f`, returning its result and the number of allocations it made.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+// ============================================================================
+// Arena Tests
+// ============================================================================
+
+#[test]
+fn test_parse_in_matches_parse() {
+    let mut parser = Parser::new(Profile::Litedoc);
+    let mut arena = Arena::new();
+
+    let mut inputs = vec![MESSAGE.to_string(), "Short *note*.".to_string()];
+    for entry in std::fs::read_dir("../../examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "ld") {
+            inputs.push(std::fs::read_to_string(path).unwrap());
+        }
+    }
+
+    // Twice over, so the second pass parses into reclaimed storage.
+    for input in inputs.iter().chain(&inputs) {
+        let expected = Parser::new(Profile::Litedoc).parse_with_recovery(input);
+        let result = parser.parse_with_recovery_in(input, &mut arena);
+        assert_eq!(result.document, expected.document);
+        assert_eq!(result.errors.len(), expected.errors.len());
+        arena.reset(result.document);
+    }
+}
+
+#[test]
+fn test_arena_reuses_storage() {
+    let mut parser = Parser::new(Profile::Litedoc);
+    let mut arena = Arena::new();
+    assert_eq!(arena.spare_count(), 0);
+
+    let doc = parser.parse_in(MESSAGE, &mut arena).unwrap();
+    arena.reset(doc);
+    let spares = arena.spare_count();
+    assert!(spares > 10);
+
+    // The same document uses up exactly the storage it gave back.
+    let doc = parser.parse_in(MESSAGE, &mut arena).unwrap();
+    assert_eq!(arena.spare_count(), 0);
+    arena.reset(doc);
+    assert_eq!(arena.spare_count(), spares);
+
+    // Plain parses never touch the arena.
+    let doc = parser.parse(MESSAGE).unwrap();
+    assert_eq!(arena.spare_count(), spares);
+    drop(doc);
+}
+
+#[test]
+fn test_parse_in_documents_can_be_kept() {
+    let mut parser = Parser::new(Profile::Litedoc);
+    let mut arena = Arena::new();
+
+    let kept = parser.parse_in("# Kept\n\nText.", &mut arena).unwrap();
+    let doc = parser.parse_in(MESSAGE, &mut arena).unwrap();
+    arena.reset(doc);
+
+    // Errors leave the arena usable.
+    let bad = "::table\n| a | b |\n| --- | --- |\n| 1 |\n::";
+    assert!(parser.parse_in(bad, &mut arena).is_err());
+    let again = parser.parse_in("# Kept\n\nText.", &mut arena).unwrap();
+    assert_eq!(again, kept);
+    assert_eq!(kept.into_owned().blocks.len(), 2);
+}
+
+#[test]
+fn test_steady_state_parse_allocates_no_node_storage() {
+    let mut parser = Parser::new(Profile::Litedoc);
+    let mut arena = Arena::new();
+    let doc = parser.parse_in(MESSAGE, &mut arena).unwrap();
+    arena.reset(doc);
+
+    // Every node vector of a plain parse is one allocation...
+    let (doc, plain) = count_allocations(|| parser.parse(MESSAGE).unwrap());
+    let mut counter = Arena::new();
+    counter.reset(doc);
+    let node_vectors = counter.spare_count();
+    assert!(node_vectors > 20);
+
+    // ...that a parse in a warm arena no longer makes. What is left is
+    // scratch space and owned text, such as heading ids.
+    for _ in 0..3 {
+        let (doc, in_arena) = count_allocations(|| parser.parse_in(MESSAGE, &mut arena).unwrap());
+        assert_eq!(in_arena, plain - node_vectors);
+        arena.reset(doc);
+    }
+}
@@ -0,0 +1,175 @@
+//! Integration tests for the document builder
+
+use litedoc_core::ast::{AttrValue, ListKind, Module};
+use litedoc_core::builder::{BlockBuilder, DocumentBuilder, InlineBuilder};
+use litedoc_core::span::Span;
+use litedoc_core::{format_document, Block, Document, FormatOptions, Inline, Parser, Profile};
+
+fn report() -> DocumentBuilder {
+    DocumentBuilder::new()
+        .meta("title", "Weekly report")
+        .meta("draft", true)
+        .meta("version", 3)
+        .meta("tags", vec!["ci".into(), AttrValue::Int(2)])
+        .heading(1, "Summary")
+        .paragraph(
+            InlineBuilder::new()
+                .text("Found ")
+                .strong("3 *new*")
+                .text(" issues, see ")
+                .link(InlineBuilder::new().code("ci.yml"), "https://ci.example")
+                .text(" and ask ")
+                .mention("agent", "triage")
+                .text("."),
+        )
+        .table([vec!["Issue", "Owner", "Age"], vec!["Flaky test", "ci"]])
+        .task_list([(true, "Rerun"), (false, "Bisect")])
+        .list_of(
+            ListKind::Ordered,
+            [
+                BlockBuilder::new().paragraph("First"),
+                BlockBuilder::new()
+                    .paragraph("Second")
+                    .list(["a", "b"])
+                    .code_block("sh", "make test"),
+            ],
+        )
+        .titled_callout(
+            "warning",
+            "Heads up",
+            BlockBuilder::new().heading(2, "Summary").paragraph(
+                InlineBuilder::new()
+                    .math("x^2")
+                    .hard_break()
+                    .text("# not a heading"),
+            ),
+        )
+        .thematic_break()
+        .quote(BlockBuilder::new().paragraph(InlineBuilder::new().strikethrough("old")))
+}
+
+/// Debug output of `doc` with every span blanked out.
+fn without_spans(doc: &Document) -> String {
+    let debug = format!("{doc:?}");
+    let mut out = String::with_capacity(debug.len());
+    let mut rest = debug.as_str();
+    while let Some(i) = rest.find("Span {") {
+        out.push_str(&rest[..i]);
+        out.push_str("Span");
+        rest = &rest[i + rest[i..].find('}').unwrap() + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// ============================================================================
+// Builder Tests
+// ============================================================================
+
+#[test]
+fn test_builder_output_reparses() {
+    let doc = report().build();
+    let text = format_document(&doc, &FormatOptions::default());
+
+    let parsed = Parser::new(Profile::Litedoc).parse(&text).unwrap();
+    assert_eq!(format_document(&parsed, &FormatOptions::default()), text);
+    assert_eq!(parsed.modules, doc.modules);
+    assert_eq!(without_spans(&parsed), without_spans(&doc));
+}
+
+#[test]
+fn test_builder_enables_used_modules() {
+    let doc = report().build();
+    assert_eq!(
+        doc.modules,
+        [
+            Module::Mentions,
+            Module::Tables,
+            Module::Tasks,
+            Module::Math,
+            Module::Strikethrough
+        ]
+    );
+
+    let doc = DocumentBuilder::new()
+        .with_module(Module::Footnotes)
+        .paragraph("plain")
+        .build();
+    assert_eq!(doc.modules, [Module::Footnotes]);
+}
+
+#[test]
+fn test_builder_spans_are_synthetic() {
+    let doc = report().build();
+    assert!(doc.span.is_synthetic());
+    assert!(doc.blocks.iter().all(|b| match b {
+        Block::Heading(h) => h.span.is_synthetic(),
+        Block::Paragraph(p) => p.content.iter().all(|i| match i {
+            Inline::Text(t) => t.span.is_synthetic(),
+            _ => true,
+        }),
+        Block::ThematicBreak(span) => *span == Span::SYNTHETIC,
+        _ => true,
+    }));
+    assert!(!Span::new(0, 0).is_synthetic());
+}
+
+#[test]
+fn test_builder_heading_ids() {
+    let doc = DocumentBuilder::new()
+        .heading(1, "Setup")
+        .heading_with_id(2, "Install", "setup-1")
+        .callout("note", BlockBuilder::new().heading(3, "Setup"))
+        .heading(2, "Setup")
+        .heading(2, "!!!")
+        .build();
+
+    let mut ids = Vec::new();
+    for block in &doc.blocks {
+        match block {
+            Block::Heading(h) => ids.push(h.id.as_deref()),
+            Block::Callout(c) => {
+                if let Block::Heading(h) = &c.blocks[0] {
+                    ids.push(h.id.as_deref());
+                }
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(
+        ids,
+        [
+            Some("setup"),
+            Some("setup-1"),
+            Some("setup-2"),
+            Some("setup-3"),
+            None
+        ]
+    );
+}
+
+#[test]
+fn test_builder_table_shape() {
+    let doc = DocumentBuilder::new()
+        .table([vec!["a", "b"], vec!["1"], vec!["2", "3", "4"]])
+        .build();
+    let Block::Table(table) = &doc.blocks[0] else {
+        panic!("Expected table");
+    };
+    assert_eq!(table.alignments.len(), 3);
+    assert!(table.rows[0].header);
+    assert!(!table.rows[1].header);
+    assert!(table.rows.iter().all(|r| r.cells.len() == 3));
+
+    let text = format_document(
+        &doc,
+        &FormatOptions {
+            emit_directives: false,
+            ..FormatOptions::default()
+        },
+    );
+    assert_eq!(
+        text,
+        "::table\n| a | b |  |\n| --- | --- | --- |\n| 1 |  |  |\n| 2 | 3 | 4 |\n::\n"
+    );
+}
@@ -0,0 +1,571 @@
+//! Integration tests for the canonical formatter
+
+use std::fs;
+use std::path::PathBuf;
+
+use litedoc_core::ast::{AttrValue, Metadata};
+use litedoc_core::{
+    format_document, Block, BlockHandler, BodyKind, Document, FormatOptions, Parser, Profile,
+};
+
+fn parse(input: &str) -> Document<'_> {
+    let mut parser = Parser::new(Profile::Litedoc);
+    parser.parse_with_recovery(input).document
+}
+
+fn parse_md(input: &str) -> Document<'_> {
+    let mut parser = Parser::new(Profile::Md);
+    parser.parse_with_recovery(input).document
+}
+
+fn format(doc: &Document) -> String {
+    format_document(doc, &FormatOptions::default())
+}
+
+/// Debug representation of a document with all spans removed, so that
+/// documents parsed from different texts can be compared structurally.
+fn structure(doc: &Document) -> String {
+    let debug = format!("{:?}", doc);
+    let mut out = String::with_capacity(debug.len());
+    let mut rest = debug.as_str();
+    while let Some(pos) = rest.find("Span {") {
+        out.push_str(&rest[..pos]);
+        let end = rest[pos..].find('}').map_or(rest.len(), |e| pos + e + 1);
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn examples() -> Vec<(PathBuf, String)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut files: Vec<_> = fs::read_dir(&dir)
+        .expect("examples directory")
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "ld"))
+        .collect();
+    files.sort();
+    files
+        .into_iter()
+        .map(|p| {
+            let text = fs::read_to_string(&p).unwrap();
+            (p, text)
+        })
+        .collect()
+}
+
+// ============================================================================
+// Round-trip Tests
+// ============================================================================
+
+#[test]
+fn test_round_trip_examples() {
+    let files = examples();
+    assert!(!files.is_empty());
+
+    for (path, input) in files {
+        let doc = parse(&input);
+        let formatted = format(&doc);
+        let reparsed = parse(&formatted);
+
+        assert_eq!(
+            structure(&doc),
+            structure(&reparsed),
+            "AST changed after formatting {}",
+            path.display()
+        );
+        assert_eq!(
+            formatted,
+            format(&reparsed),
+            "formatting is not idempotent for {}",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn test_canonical_example_is_unchanged() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../examples/simple.ld");
+    let input = fs::read_to_string(path).unwrap();
+    assert_eq!(format(&parse(&input)), input);
+}
+
+#[test]
+fn test_round_trip_md_block_starters() {
+    let inputs = [
+        "1\\. x",
+        "2\\) x",
+        "\\- x",
+        "\\+ x",
+        "\\* x",
+        "\\> x",
+        "\\_\\_\\_",
+        "a\\\n\\===",
+        "a\\\n\\--",
+        "a\n\\-",
+        "a\n1\\. b\n\\> c",
+        "::callout\n\\- inside\n::",
+        "# 1\\. Heading",
+        "-5 and 3.14 stay as they are",
+    ];
+    for input in inputs {
+        let doc = parse_md(input);
+        assert!(
+            matches!(
+                doc.blocks.last(),
+                Some(Block::Paragraph(_) | Block::Heading(_))
+            ) || input.starts_with("::"),
+            "{input:?} is not plain text"
+        );
+        let formatted = format(&doc);
+        let reparsed = parse_md(&formatted);
+        assert_eq!(structure(&doc), structure(&reparsed), "{input:?}");
+        assert_eq!(formatted, format(&reparsed), "{input:?}");
+    }
+
+    let doc = parse_md("1\\. x\n\\- y\n\\===");
+    assert_eq!(format(&doc), "@profile md\n\n1\\. x\n\\- y\n\\===\n");
+    let doc = parse_md("-5 and 3.14");
+    assert_eq!(format(&doc), "@profile md\n\n-5 and 3.14\n");
+}
+
+#[test]
+fn test_round_trip_md_lists() {
+    let inputs = [
+        "- a\n- b",
+        "1. a\n2. b",
+        "3) x\n\n   more\n4) y",
+        "- a\n  - nested\n\n    text\n- b",
+        "- a\n* b\n+ c\n\n1. d\n1) e",
+        "- \n- b",
+        "999999999. x\n1. y",
+        "- ```\n  code\n\n    indented\n  ```\n- > quote",
+        "::callout\n1. x\n::",
+        "::list ordered start=2\n- a\n::\n\n::list id=l1\n- b\n::",
+    ];
+    for input in inputs {
+        let doc = parse_md(input);
+        let formatted = format(&doc);
+        let reparsed = parse_md(&formatted);
+        // Attributes are compared too: no `ordered` or `start` is added.
+        assert_eq!(structure(&doc), structure(&reparsed), "{input:?}");
+        assert_eq!(formatted, format(&reparsed), "{input:?}");
+    }
+
+    let doc = parse_md("@modules tasks\n\n3. [x] a\n   b\n4. [ ]\n\n- c\n+ d");
+    assert_eq!(
+        format(&doc),
+        "@profile md\n@modules tasks\n\n3. [x] a\n   b\n4. [ ]\n\n- c\n\n* d\n"
+    );
+}
+
+#[test]
+fn test_round_trip_md_indented_paragraphs() {
+    let inputs = [
+        "  hello",
+        "   *x* y\n  z",
+        "- a\n\n    code",
+        "> x\n>   y",
+        "  Title\n  =====",
+    ];
+    for input in inputs {
+        let doc = parse_md(input);
+        let formatted = format(&doc);
+        let reparsed = parse_md(&formatted);
+        assert_eq!(structure(&doc), structure(&reparsed), "{input:?}");
+        assert_eq!(formatted, format(&reparsed), "{input:?}");
+    }
+    assert_eq!(format(&parse_md("  hello")), "@profile md\n\nhello\n");
+}
+
+#[test]
+fn test_round_trip_callout_attrs() {
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    for input in [
+        "::callout\nx\n::\n",
+        "::callout type=note\nx\n::\n",
+        "::callout title=\"T\" type=tip\nx\n::\n",
+    ] {
+        let doc = parse(input);
+        let formatted = format_document(&doc, &options);
+        assert_eq!(formatted, input);
+        assert_eq!(structure(&parse(&formatted)), structure(&doc));
+    }
+}
+
+// ============================================================================
+// Block Formatting Tests
+// ============================================================================
+
+#[test]
+fn test_format_directives() {
+    let doc = parse("@profile litedoc\n@modules tables, math\n\n# Title");
+    assert_eq!(
+        format(&doc),
+        "@profile litedoc\n@modules tables, math\n\n# Title\n"
+    );
+
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format_document(&doc, &options), "# Title\n");
+}
+
+#[test]
+fn test_format_blocks() {
+    let input = "::list ordered start=3\n- Three\n- Four\n::\n\n```rust\nfn main() {}\n```\n\n::math block\nx^2\n::\n\n::figure src=\"a.png\" alt=\"An image\"\n::\n\n---\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format_document(&parse(input), &options), input);
+}
+
+#[test]
+fn test_format_nested_containers() {
+    let input = "::callout type=warning title=\"Careful\"\n# Heads up\n\n```sh\nmake clean\n```\n\n::quote\nInner\n::\n::\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format_document(&parse(input), &options), input);
+}
+
+#[test]
+fn test_format_nested_list() {
+    let input = "::list\n- Item one\n| Continued\n|\n| ::list ordered\n| - Nested\n| | more\n| ::\n- Item two\n::\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format_document(&parse(input), &options), input);
+}
+
+#[test]
+fn test_format_task_list() {
+    let input = "@profile litedoc\n@modules tasks\n\n::list\n- [x] Done\n- [ ] Todo\n| more\n- [ ]\n- Plain\n::\n";
+    assert_eq!(format(&parse(input)), input);
+}
+
+#[test]
+fn test_format_code_block_fences() {
+    let input = "~~~~md title=\"Read me.md\" lines=3-5\n```rust\nfn main() {}\n```\n~~~~\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        format_document(&parse(input), &options),
+        "````md title=\"Read me.md\" lines=3-5\n```rust\nfn main() {}\n```\n````\n"
+    );
+}
+
+#[test]
+fn test_format_block_attrs() {
+    let input = "::callout title=\"Heads up\" type=warning id=w1 pinned\nBody\n::\n\n\
+                 ::figure src=a.png width=640\n::\n\n\
+                 ::math display label=eq1\nx\n::\n\n\
+                 ::table id=t1\n| a |\n| --- |\n::\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        format_document(&parse(input), &options),
+        "::callout title=\"Heads up\" type=warning id=w1 pinned\nBody\n::\n\n\
+         ::figure src=\"a.png\" width=640\n::\n\n\
+         ::math display label=eq1\nx\n::\n\n\
+         ::table id=t1\n| a |\n| --- |\n::\n"
+    );
+}
+
+#[test]
+fn test_format_custom_blocks() {
+    struct Plan;
+    impl BlockHandler for Plan {}
+    struct ToolCall;
+    impl BlockHandler for ToolCall {
+        fn body(&self) -> BodyKind {
+            BodyKind::Content
+        }
+    }
+
+    let input = "::plan id=p1\n# Steps\n::\n\n::tool_call name=search\n{\"q\": 1}\n::\n";
+    let mut parser = Parser::new(Profile::Litedoc);
+    parser
+        .register_block("plan", Plan)
+        .register_block("tool_call", ToolCall);
+    let doc = parser.parse(input).unwrap();
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format_document(&doc, &options), input);
+}
+
+#[test]
+fn test_format_unregistered_custom_block() {
+    struct Plan;
+    impl BlockHandler for Plan {}
+
+    // Without a handler the block is kept raw, opener included.
+    let input = "::plan x=1\n# Steps\n::\n";
+    let doc = parse(input);
+    assert!(matches!(doc.blocks[0], Block::Raw(_)));
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let formatted = format_document(&doc, &options);
+    assert_eq!(formatted, input);
+
+    // A parser that knows the block reads the formatted text as one.
+    let mut parser = Parser::new(Profile::Litedoc);
+    parser.register_block("plan", Plan);
+    let reparsed = parser.parse(&formatted).unwrap();
+    let Block::Custom(plan) = &reparsed.blocks[0] else {
+        panic!("Expected custom block");
+    };
+    assert_eq!(plan.name, "plan");
+    assert_eq!(plan.attrs.len(), 1);
+}
+
+#[test]
+fn test_format_nested_raw_block() {
+    // A bare `::` opener would close the callout instead of opening a block.
+    let input = "::callout\n::unknown\nx\n::\n::\n";
+    let doc = parse(input);
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let formatted = format_document(&doc, &options);
+    assert_eq!(formatted, input);
+    assert_eq!(structure(&parse(&formatted)), structure(&doc));
+}
+
+#[test]
+fn test_format_raw_block_attrs() {
+    let input = "::unknown a=1 title=\"Two words\" flag\nx\n::\n";
+    let doc = parse(input);
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format_document(&doc, &options), input);
+}
+
+#[test]
+fn test_format_long_line() {
+    // Each character checks whether it starts a line; that must not mean
+    // rescanning the line written so far.
+    let input = "word # - : ".repeat(40_000) + "\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let formatted = format_document(&parse(&input), &options);
+    assert_eq!(formatted.trim_end(), input.trim_end());
+}
+
+#[test]
+fn test_format_table() {
+    let doc = parse("::table\n|Name|Age|\n|---|---|\n|Alice|30|\n::");
+    let options = FormatOptions {
+        emit_directives: false,
+        align_tables: true,
+    };
+    assert_eq!(
+        format_document(&doc, &options),
+        "::table\n| Name  | Age |\n| ----- | --- |\n| Alice | 30  |\n::\n"
+    );
+}
+
+#[test]
+fn test_format_table_alignment_and_empty_cells() {
+    let input = "::table\n| Name | Age |  |\n| :--- | --: | :-: |\n| Alice |  | x |\n::\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        format_document(&parse(input), &options),
+        "::table\n| Name | Age |  |\n| :-- | --: | :-: |\n| Alice |  | x |\n::\n"
+    );
+}
+
+#[test]
+fn test_format_inlines() {
+    let input = "A **bold *nested* text** `code` ~~gone~~ [[label|https://x.y]] [[https://x.y]] <https://z.w> ref[^1]\n";
+    let doc = parse(input);
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    assert_eq!(format_document(&doc, &options), input);
+}
+
+#[test]
+fn test_format_code_spans() {
+    let input =
+        "``plain`` and ``echo `date` `` and ` `` ` and ` ` and `a ` and ` a ` and ```` ``` ````\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let out = format_document(&parse(input), &options);
+    // The shortest fence that cannot close early is used, with padding only
+    // where a backtick or a space sits at the edge.
+    assert_eq!(
+        out,
+        "`plain` and ``echo `date` `` and ` `` ` and ` ` and `a ` and `a` and ` ``` `\n"
+    );
+    assert_eq!(format_document(&parse(&out), &options), out);
+}
+
+#[test]
+fn test_format_escapes() {
+    let input = "\\*not\\* a \\[\\[link]] C:\\path 2 \\< 3\n\\# not a heading\n\\---\n\n[[a\\|b|https://x.y]]\n\n::table\n| a \\| b | c |\n| --- | --- |\n::\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let doc = parse(input);
+    let out = format_document(&doc, &options);
+    // Literal backslashes are escaped too, so the output parses back unchanged.
+    assert_eq!(out, input.replacen("C:\\path", "C:\\\\path", 1),);
+    assert_eq!(format_document(&parse(&out), &options), out);
+}
+
+#[test]
+fn test_format_line_breaks() {
+    let input = "soft  \nbreak and hard\\\nbreak *across\nlines*\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let out = format_document(&parse(input), &options);
+    assert_eq!(out, "soft\nbreak and hard\\\nbreak *across\nlines*\n");
+    assert_eq!(format_document(&parse(&out), &options), out);
+}
+
+#[test]
+fn test_format_links() {
+    let input = "[[**bold** docs|https://x.y \"Say \\\"hi\\\"\"]] [[#intro]] [[a\\|b|page.ld]]\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let out = format_document(&parse(input), &options);
+    assert_eq!(out, input);
+}
+
+#[test]
+fn test_format_heading_ids() {
+    let input = "# Key Findings {#findings}\n\n## Summary\n\n## Summary\n\n## Plain \\{#text}\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let out = format_document(&parse(input), &options);
+    // Slugs are left implicit; other ids, including deduplicated ones, are written.
+    assert_eq!(
+        out,
+        "# Key Findings {#findings}\n\n## Summary\n\n## Summary {#summary-1}\n\n## Plain \\{#text}\n"
+    );
+    assert_eq!(format_document(&parse(&out), &options), out);
+}
+
+#[test]
+fn test_format_sources_and_citations() {
+    let input = "Warming [@ipcc2023; see \\[@x\\]].\n\n\
+                 Per [@ipcc2023].\n\n\
+                 ::sources id=refs\n- ipcc2023 title=\"AR6 Report\" url=https://ipcc.ch year=2023\n::\n";
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let out = format_document(&parse(input), &options);
+    assert_eq!(
+        out,
+        "Warming \\[@ipcc2023; see \\[@x]].\n\n\
+         Per [@ipcc2023].\n\n\
+         ::sources id=refs\n- ipcc2023 title=\"AR6 Report\" url=\"https://ipcc.ch\" year=2023\n::\n"
+    );
+    assert_eq!(format_document(&parse(&out), &options), out);
+}
+
+#[test]
+fn test_format_mentions() {
+    let input =
+        "@modules mentions\n\nAsk <@agent:summarizer-v2> about <@task:t-1>, not \\<@agent:x>.\n";
+    let options = FormatOptions {
+        emit_directives: true,
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        format_document(&parse(input), &options),
+        "@profile litedoc\n".to_owned() + input
+    );
+}
+
+#[test]
+fn test_format_inline_math() {
+    let input = "@modules math\n\nLet \\(a*b \\\\ c\\) hold.\n";
+    let options = FormatOptions {
+        emit_directives: true,
+        ..FormatOptions::default()
+    };
+    assert_eq!(
+        format_document(&parse(input), &options),
+        "@profile litedoc\n".to_owned() + input
+    );
+}
+
+// ============================================================================
+// Metadata Tests
+// ============================================================================
+
+#[test]
+fn test_format_metadata_quoting() {
+    let doc = Document {
+        profile: Profile::Litedoc,
+        modules: Vec::new(),
+        metadata: Some(Metadata {
+            entries: vec![
+                ("plain".into(), AttrValue::Str("word".into())),
+                ("spaced".into(), AttrValue::Str("two words".into())),
+                ("numeric".into(), AttrValue::Str("42".into())),
+                ("quoted".into(), AttrValue::Str("say \"hi\" \\o/".into())),
+                ("whole".into(), AttrValue::Float(2.0)),
+                (
+                    "tags".into(),
+                    AttrValue::List(vec![
+                        AttrValue::Str("a, b".into()),
+                        AttrValue::Int(1),
+                        AttrValue::Bool(true),
+                    ]),
+                ),
+            ],
+            span: Default::default(),
+        }),
+        blocks: Vec::new(),
+        span: Default::default(),
+    };
+
+    let formatted = format(&doc);
+    assert_eq!(
+        formatted,
+        "@profile litedoc\n\n--- meta ---\nplain: word\nspaced: \"two words\"\nnumeric: \"42\"\nquoted: \"say \\\"hi\\\" \\\\o/\"\nwhole: 2.0\ntags: [\"a, b\", 1, true]\n---\n"
+    );
+
+    let reparsed = parse(&formatted);
+    assert_eq!(
+        reparsed.metadata.unwrap().entries,
+        doc.metadata.unwrap().entries
+    );
+}
@@ -1,7 +1,12 @@
 //! Integration tests for the LiteDoc parser
 
-use litedoc_core::ast::{AttrValue, ListKind, Module};
-use litedoc_core::{Block, Inline, Parser, Profile};
+use std::borrow::Cow;
+
+use litedoc_core::ast::{Alignment, AttrValue, CustomBody, LinkKind, ListKind, Module};
+use litedoc_core::resolve::resolve_references;
+use litedoc_core::{
+    Block, BlockContext, BlockHandler, BodyKind, Inline, ParseErrorKind, Parser, Profile,
+};
 
 // ============================================================================
 // Profile and Module Directive Tests
@@ -50,6 +55,89 @@ fn test_parse_all_modules() {
     assert_eq!(doc.modules.len(), 7);
 }
 
+#[test]
+fn test_with_modules_preset() {
+    let mut parser = Parser::new(Profile::Litedoc).with_modules([Module::Tables, Module::Math]);
+    let doc = parser.parse("# Hello").unwrap();
+    assert_eq!(doc.modules, vec![Module::Tables, Module::Math]);
+
+    // A directive replaces the preset set.
+    let doc = parser.parse("@modules footnotes\n\n# Hello").unwrap();
+    assert_eq!(doc.modules, vec![Module::Footnotes]);
+}
+
+#[test]
+fn test_lenient_modules_parse_undeclared_syntax() {
+    let input = "::table\n| a |\n::\n\n~~gone~~";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    assert!(matches!(&doc.blocks[0], Block::Table(_)));
+    if let Block::Paragraph(p) = &doc.blocks[1] {
+        assert!(matches!(&p.content[0], Inline::Strikethrough(_)));
+    } else {
+        panic!("Expected paragraph");
+    }
+}
+
+#[test]
+fn test_enforced_modules_reject_disabled_blocks() {
+    let input =
+        "::table\n| a |\n::\n\n::math\nx\n::\n\n::footnotes\n[^1]: note\n::\n\n::html\n<b>\n::";
+    let mut parser = Parser::new(Profile::Litedoc).with_module_enforcement(true);
+    let result = parser.parse_with_recovery(input);
+
+    assert_eq!(result.document.blocks.len(), 4);
+    assert!(result
+        .document
+        .blocks
+        .iter()
+        .all(|b| matches!(b, Block::Raw(_))));
+    let kinds: Vec<_> = result.errors.iter().map(|e| e.kind).collect();
+    assert_eq!(kinds, vec![ParseErrorKind::DisabledModule; 4]);
+    let first = result.errors.iter().next().unwrap();
+    assert_eq!(first.message, "module not enabled: tables");
+    assert_eq!(first.span, Some(litedoc_core::span::Span::new(0, 7)));
+}
+
+#[test]
+fn test_enforced_modules_reject_disabled_inlines() {
+    let input = "::list\n- [x] done\n::\n\n~~old~~ <https://x.y> ref[^1]";
+    let mut parser = Parser::new(Profile::Litedoc).with_module_enforcement(true);
+    let result = parser.parse_with_recovery(input);
+
+    assert_eq!(result.errors.len(), 4);
+    if let Block::List(l) = &result.document.blocks[0] {
+        assert_eq!(l.items[0].checked, None);
+    } else {
+        panic!("Expected list block");
+    }
+    if let Block::Paragraph(p) = &result.document.blocks[1] {
+        assert_eq!(p.content.len(), 1);
+        if let Inline::Text(t) = &p.content[0] {
+            assert_eq!(t.content.as_ref(), "~~old~~ <https://x.y> ref[^1]");
+        }
+    } else {
+        panic!("Expected paragraph");
+    }
+}
+
+#[test]
+fn test_enforced_modules_accept_enabled_syntax() {
+    let input = "@modules tables, strikethrough\n\n::table\n| a |\n::\n\n~~old~~";
+    let mut parser = Parser::new(Profile::Litedoc).with_module_enforcement(true);
+    let doc = parser.parse(input).unwrap();
+    assert!(matches!(&doc.blocks[0], Block::Table(_)));
+
+    // The md profile implies the GFM modules.
+    let mut parser = Parser::new(Profile::Md).with_module_enforcement(true);
+    let doc = parser.parse("- [ ] task ~~old~~ <https://x.y>").unwrap();
+    assert!(matches!(&doc.blocks[0], Block::List(_)));
+
+    let mut parser = Parser::new(Profile::MdStrict).with_module_enforcement(true);
+    assert!(parser.parse("~~old~~").is_err());
+}
+
 // ============================================================================
 // Metadata Block Tests
 // ============================================================================
@@ -130,6 +218,102 @@ fn test_parse_heading_content() {
     }
 }
 
+#[test]
+fn test_heading_ids() {
+    let input = "# Key Findings {#findings}\n## Key *Findings*\n## Key Findings\n### `API` & Setup_Notes\n# !!!\n# Text {not an id}";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    let ids: Vec<_> = doc
+        .blocks
+        .iter()
+        .map(|b| match b {
+            Block::Heading(h) => h.id.as_deref(),
+            _ => panic!("Expected heading"),
+        })
+        .collect();
+    assert_eq!(
+        ids,
+        [
+            Some("findings"),
+            Some("key-findings"),
+            Some("key-findings-1"),
+            Some("api-setup-notes"),
+            None,
+            Some("text-not-an-id"),
+        ]
+    );
+
+    if let Block::Heading(h) = &doc.blocks[0] {
+        // The `{#id}` suffix is not part of the content.
+        assert!(matches!(&h.content[..], [Inline::Text(t)] if t.content == "Key Findings"));
+    }
+}
+
+#[test]
+fn test_md_setext_heading_id() {
+    let input = "Overview {#top}\n===";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Heading(h) = &doc.blocks[0] {
+        assert_eq!(h.id.as_deref(), Some("top"));
+        assert!(matches!(&h.content[..], [Inline::Text(t)] if t.content == "Overview"));
+    } else {
+        panic!("Expected heading");
+    }
+}
+
+#[test]
+fn test_duplicate_heading_id() {
+    let input = "# Intro\n\n# Again {#intro}";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let result = parser.parse_with_recovery(input);
+
+    assert_eq!(result.errors.len(), 1);
+    let error = result.errors.iter().next().unwrap();
+    assert_eq!(error.kind, ParseErrorKind::InvalidSyntax);
+    assert_eq!(
+        error.message,
+        "invalid syntax in heading id (duplicate #intro)"
+    );
+}
+
+#[test]
+fn test_resolve_internal_links() {
+    let input = "# Key Findings {#findings}\n\nSee [[#findings]], [[the intro|#intro]] and\n\
+                 [[*setup*|#setup-notes]].\n\n::callout\n## Setup Notes\n::";
+    let mut parser = Parser::new(Profile::Litedoc).with_reference_checks(true);
+    let result = parser.parse_with_recovery(input);
+
+    assert_eq!(result.errors.len(), 1);
+    let error = result.errors.iter().next().unwrap();
+    assert_eq!(error.kind, ParseErrorKind::UnresolvedReference);
+    assert_eq!(error.message, "unresolved reference: #intro");
+    let span = error.span.unwrap();
+    assert_eq!(
+        &input[span.start as usize..span.end as usize],
+        "[[the intro|#intro]]"
+    );
+
+    // The same check is available for documents built or edited by hand.
+    let mut doc = result.document;
+    doc.blocks.remove(0);
+    let errors = resolve_references(&doc);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].message, "unresolved reference: #findings");
+}
+
+#[test]
+fn test_unresolved_links_parse_by_default() {
+    let input = "See [[intro|#intro]].";
+    assert!(Parser::new(Profile::Litedoc).parse(input).is_ok());
+    assert!(Parser::new(Profile::Litedoc)
+        .with_reference_checks(true)
+        .parse(input)
+        .is_err());
+}
+
 #[test]
 fn test_invalid_heading_no_space() {
     let input = "#NoSpace";
@@ -213,10 +397,70 @@ fn test_parse_code_block() {
 fn test_parse_code_block_no_lang() {
     let input = "```\nplain code\n```";
     let mut parser = Parser::new(Profile::Litedoc);
+    let result = parser.parse_with_recovery(input);
+
+    // `lang` is required in the litedoc profile, but the block is still produced.
+    if let Block::CodeBlock(c) = &result.document.blocks[0] {
+        assert!(c.lang.is_empty());
+    } else {
+        panic!("Expected code block");
+    }
+    let errors: Vec<_> = result.errors.iter().collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::InvalidSyntax);
+
+    // In the md profile the language is optional.
+    let mut parser = Parser::new(Profile::Md);
+    assert!(parser.parse(input).is_ok());
+}
+
+#[test]
+fn test_parse_code_block_longer_fence() {
+    let input = "````markdown\n```rust\nfn main() {}\n```\n````\n\nAfter";
+    let mut parser = Parser::new(Profile::Litedoc);
     let doc = parser.parse(input).unwrap();
 
+    assert_eq!(doc.blocks.len(), 2);
     if let Block::CodeBlock(c) = &doc.blocks[0] {
-        assert!(c.lang.is_empty());
+        assert_eq!(c.lang.as_ref(), "markdown");
+        assert_eq!(c.content.as_ref(), "```rust\nfn main() {}\n```");
+    } else {
+        panic!("Expected code block");
+    }
+}
+
+#[test]
+fn test_parse_code_block_tilde_fence() {
+    let input = "~~~python\nprint(1)\n```\n~~~~";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 1);
+    if let Block::CodeBlock(c) = &doc.blocks[0] {
+        assert_eq!(c.lang.as_ref(), "python");
+        assert_eq!(c.content.as_ref(), "print(1)\n```");
+    } else {
+        panic!("Expected code block");
+    }
+}
+
+#[test]
+fn test_parse_code_block_attrs() {
+    let input = "```python title=\"x.py\" lines=3-5 start=10 linenos\nprint(1)\n```";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::CodeBlock(c) = &doc.blocks[0] {
+        assert_eq!(c.lang.as_ref(), "python");
+        assert_eq!(
+            c.attrs,
+            vec![
+                ("title".into(), AttrValue::Str("x.py".into())),
+                ("lines".into(), AttrValue::Str("3-5".into())),
+                ("start".into(), AttrValue::Int(10)),
+                ("linenos".into(), AttrValue::Bool(true)),
+            ]
+        );
     } else {
         panic!("Expected code block");
     }
@@ -267,6 +511,121 @@ fn test_parse_ordered_list_with_start() {
     }
 }
 
+#[test]
+fn test_parse_list_item_continuation() {
+    let input = "::list\n- Item one\n| Continued line\n- Item two\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::List(l) = &doc.blocks[0] {
+        assert_eq!(l.items.len(), 2);
+        let item = &l.items[0];
+        assert_eq!(item.blocks.len(), 1);
+        if let Block::Paragraph(p) = &item.blocks[0] {
+            assert!(matches!(
+                &p.content[..],
+                [Inline::Text(a), Inline::SoftBreak(_), Inline::Text(b)]
+                    if a.content == "Item one" && b.content == "Continued line"
+            ));
+        } else {
+            panic!("Expected paragraph in list item");
+        }
+        let span = item.span;
+        assert_eq!(
+            &input[span.start as usize..span.end as usize],
+            "Item one\n| Continued line"
+        );
+    } else {
+        panic!("Expected list block");
+    }
+}
+
+#[test]
+fn test_parse_nested_list() {
+    let input = "::list unordered\n- Item one\n| ::list ordered start=1\n| - Nested one\n| - Nested two\n| ::\n- Item two\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 1);
+    if let Block::List(l) = &doc.blocks[0] {
+        assert_eq!(l.items.len(), 2);
+        assert_eq!(l.items[0].blocks.len(), 2);
+        if let Block::List(nested) = &l.items[0].blocks[1] {
+            assert_eq!(nested.kind, ListKind::Ordered);
+            assert_eq!(nested.start, Some(1));
+            assert_eq!(nested.items.len(), 2);
+            let span = nested.items[1].span;
+            assert_eq!(&input[span.start as usize..span.end as usize], "Nested two");
+        } else {
+            panic!("Expected nested list");
+        }
+    } else {
+        panic!("Expected list block");
+    }
+}
+
+#[test]
+fn test_parse_list_item_multiple_blocks() {
+    let input =
+        "::list\n- Intro\n|\n| ```sh\n| make\n| make test\n| ```\n\n| Second paragraph.\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::List(l) = &doc.blocks[0] {
+        let item = &l.items[0];
+        assert_eq!(item.blocks.len(), 3);
+        assert!(matches!(&item.blocks[0], Block::Paragraph(_)));
+        if let Block::CodeBlock(c) = &item.blocks[1] {
+            assert_eq!(c.lang.as_ref(), "sh");
+            assert_eq!(c.content.as_ref(), "make\nmake test");
+        } else {
+            panic!("Expected code block in list item");
+        }
+        assert!(matches!(&item.blocks[2], Block::Paragraph(_)));
+    } else {
+        panic!("Expected list block");
+    }
+}
+
+#[test]
+fn test_parse_task_list() {
+    let input =
+        "@modules tasks\n\n::list\n- [ ] todo\n- [x] done\n- [X]\n- plain\n- [y] not a task\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::List(l) = &doc.blocks[0] {
+        let states: Vec<_> = l.items.iter().map(|i| i.checked).collect();
+        assert_eq!(
+            states,
+            vec![Some(false), Some(true), Some(true), None, None]
+        );
+        if let Block::Paragraph(p) = &l.items[0].blocks[0] {
+            if let Inline::Text(t) = &p.content[0] {
+                assert_eq!(t.content.as_ref(), "todo");
+            }
+        } else {
+            panic!("Expected paragraph in task item");
+        }
+        assert!(l.items[2].blocks.is_empty());
+    } else {
+        panic!("Expected list block");
+    }
+}
+
+#[test]
+fn test_task_markers_require_tasks_module() {
+    let input = "::list\n- [x] done\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::List(l) = &doc.blocks[0] {
+        assert_eq!(l.items[0].checked, None);
+    } else {
+        panic!("Expected list block");
+    }
+}
+
 // ============================================================================
 // Callout Block Tests
 // ============================================================================
@@ -298,6 +657,70 @@ fn test_parse_callout_default_type() {
     }
 }
 
+#[test]
+fn test_parse_callout_extra_attrs() {
+    let input = "::callout type=warning id=w1 severity=high title=\"Say \\\"hi\\\"\"\nBody\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Callout(c) = &doc.blocks[0] {
+        assert_eq!(c.kind.as_ref(), "warning");
+        assert_eq!(c.title.as_deref(), Some("Say \"hi\""));
+        assert_eq!(
+            c.attrs,
+            vec![
+                ("type".into(), AttrValue::Str("warning".into())),
+                ("id".into(), AttrValue::Str("w1".into())),
+                ("severity".into(), AttrValue::Str("high".into())),
+                ("title".into(), AttrValue::Str("Say \"hi\"".into())),
+            ]
+        );
+        // Unescaped values borrow from the input.
+        assert!(matches!(c.kind, std::borrow::Cow::Borrowed(_)));
+    } else {
+        panic!("Expected callout block");
+    }
+}
+
+#[test]
+fn test_parse_callout_nested_blocks() {
+    let input = "::callout type=warning\n## Careful\n\nRun this:\n\n```sh\nrm -rf build\n```\n\n::list\n- Back up\n- Retry\n::\n::\n\nAfter.";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 2);
+    if let Block::Callout(c) = &doc.blocks[0] {
+        assert_eq!(c.blocks.len(), 4);
+        assert!(matches!(&c.blocks[0], Block::Heading(h) if h.level == 2));
+        assert!(matches!(&c.blocks[1], Block::Paragraph(_)));
+        assert!(matches!(&c.blocks[2], Block::CodeBlock(code) if code.lang == "sh"));
+        assert!(matches!(&c.blocks[3], Block::List(l) if l.items.len() == 2));
+        assert_eq!(c.span.end as usize, input.find("\n\nAfter").unwrap());
+    } else {
+        panic!("Expected callout block");
+    }
+    assert!(matches!(&doc.blocks[1], Block::Paragraph(_)));
+}
+
+#[test]
+fn test_parse_callout_fence_inside_code_block() {
+    let input = "::callout\n```text\n::\n```\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 1);
+    if let Block::Callout(c) = &doc.blocks[0] {
+        assert_eq!(c.blocks.len(), 1);
+        if let Block::CodeBlock(code) = &c.blocks[0] {
+            assert_eq!(code.content.as_ref(), "::");
+        } else {
+            panic!("Expected code block");
+        }
+    } else {
+        panic!("Expected callout block");
+    }
+}
+
 // ============================================================================
 // Quote Block Tests
 // ============================================================================
@@ -311,6 +734,24 @@ fn test_parse_quote() {
     assert!(matches!(&doc.blocks[0], Block::Quote(_)));
 }
 
+#[test]
+fn test_parse_quote_nested_fences() {
+    let input = "::quote\nOuter.\n\n::callout type=tip\nInner.\n::\n\n::figure src=a.png\n::\n\n::math\n::x\n::\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 1);
+    if let Block::Quote(q) = &doc.blocks[0] {
+        assert_eq!(q.blocks.len(), 4);
+        assert!(matches!(&q.blocks[1], Block::Callout(c) if c.blocks.len() == 1));
+        assert!(matches!(&q.blocks[2], Block::Figure(_)));
+        assert!(matches!(&q.blocks[3], Block::Math(m) if m.content == "::x"));
+        assert_eq!(q.span.end as usize, input.len());
+    } else {
+        panic!("Expected quote block");
+    }
+}
+
 // ============================================================================
 // Figure Block Tests
 // ============================================================================
@@ -330,6 +771,91 @@ fn test_parse_figure() {
     }
 }
 
+#[test]
+fn test_parse_figure_extra_attrs() {
+    let input = "::figure src=a.png width=640 scale=0.5 inline\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Figure(f) = &doc.blocks[0] {
+        assert_eq!(f.src.as_ref(), "a.png");
+        assert!(f.alt.is_empty());
+        assert_eq!(
+            f.attrs,
+            vec![
+                ("src".into(), AttrValue::Str("a.png".into())),
+                ("width".into(), AttrValue::Int(640)),
+                ("scale".into(), AttrValue::Float(0.5)),
+                ("inline".into(), AttrValue::Bool(true)),
+            ]
+        );
+    } else {
+        panic!("Expected figure block");
+    }
+}
+
+#[test]
+fn test_parse_block_opener_attrs() {
+    let input = "::quote cite=\"Hamlet, 3.1\"\nText\n::\n\n\
+                 ::list ordered start=3 compact\n- a\n::\n\n\
+                 ::table id=t1 tags=[a, \"b c\"]\n| x |\n::\n\n\
+                 ::math display label=eq1\nx\n::\n\n\
+                 ::aside role=note\nText\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let result = parser.parse_with_recovery(input);
+    let blocks = &result.document.blocks;
+    assert_eq!(blocks.len(), 5);
+
+    let attrs = |i: usize| match &blocks[i] {
+        Block::Quote(q) => q.attrs.clone(),
+        Block::List(l) => l.attrs.clone(),
+        Block::Table(t) => t.attrs.clone(),
+        Block::Math(m) => m.attrs.clone(),
+        Block::Raw(r) => r.attrs.clone(),
+        other => panic!("Unexpected block {:?}", other),
+    };
+    assert_eq!(
+        attrs(0),
+        vec![("cite".into(), AttrValue::Str("Hamlet, 3.1".into()))]
+    );
+    assert_eq!(
+        attrs(1),
+        vec![
+            ("ordered".into(), AttrValue::Bool(true)),
+            ("start".into(), AttrValue::Int(3)),
+            ("compact".into(), AttrValue::Bool(true)),
+        ]
+    );
+    if let Block::List(l) = &blocks[1] {
+        assert_eq!(l.kind, ListKind::Ordered);
+        assert_eq!(l.start, Some(3));
+    }
+    assert_eq!(
+        attrs(2),
+        vec![
+            ("id".into(), AttrValue::Str("t1".into())),
+            (
+                "tags".into(),
+                AttrValue::List(vec![
+                    AttrValue::Str("a".into()),
+                    AttrValue::Str("b c".into())
+                ])
+            ),
+        ]
+    );
+    assert_eq!(
+        attrs(3),
+        vec![
+            ("display".into(), AttrValue::Bool(true)),
+            ("label".into(), AttrValue::Str("eq1".into())),
+        ]
+    );
+    assert_eq!(
+        attrs(4),
+        vec![("role".into(), AttrValue::Str("note".into()))]
+    );
+}
+
 // ============================================================================
 // Table Block Tests
 // ============================================================================
@@ -349,63 +875,228 @@ fn test_parse_table() {
     }
 }
 
-// ============================================================================
-// Footnotes Block Tests
-// ============================================================================
-
 #[test]
-fn test_parse_footnotes() {
-    let input = "::footnotes\n[^1]: First footnote.\n[^2]: Second footnote.\n::";
+fn test_parse_table_alignment() {
+    let input = "::table\n| L | C | R | N |\n|:--|:-:|--:|---|\n| 1 | 2 | 3 | 4 |\n::";
     let mut parser = Parser::new(Profile::Litedoc);
     let doc = parser.parse(input).unwrap();
 
-    if let Block::Footnotes(f) = &doc.blocks[0] {
-        assert_eq!(f.defs.len(), 2);
-        assert_eq!(f.defs[0].label.as_ref(), "1");
-        assert_eq!(f.defs[1].label.as_ref(), "2");
+    if let Block::Table(t) = &doc.blocks[0] {
+        assert_eq!(
+            t.alignments,
+            vec![
+                Alignment::Left,
+                Alignment::Center,
+                Alignment::Right,
+                Alignment::None
+            ]
+        );
     } else {
-        panic!("Expected footnotes block");
+        panic!("Expected table block");
     }
 }
 
-// ============================================================================
-// Math Block Tests
-// ============================================================================
-
 #[test]
-fn test_parse_math_block() {
-    let input = "::math display\nE = mc^2\n::";
+fn test_parse_table_empty_cells() {
+    let input = "::table\n| a |  | c |\n| --- | --- | --- |\n|  | b \\| x |  |\n::";
     let mut parser = Parser::new(Profile::Litedoc);
     let doc = parser.parse(input).unwrap();
 
-    if let Block::Math(m) = &doc.blocks[0] {
-        assert!(m.display);
-        assert!(m.content.contains("E = mc^2"));
+    if let Block::Table(t) = &doc.blocks[0] {
+        let header = &t.rows[0];
+        assert_eq!(header.cells.len(), 3);
+        assert!(header.cells[1].content.is_empty());
+        let span = header.cells[2].span;
+        assert_eq!(&input[span.start as usize..span.end as usize], " c ");
+
+        let row = &t.rows[1];
+        assert_eq!(row.cells.len(), 3);
+        assert!(row.cells[0].content.is_empty());
+        assert!(!row.cells[1].content.is_empty());
+        assert!(row.cells[2].content.is_empty());
     } else {
-        panic!("Expected math block");
+        panic!("Expected table block");
     }
 }
 
 #[test]
-fn test_parse_math_inline() {
-    let input = "::math\nx + y = z\n::";
+fn test_parse_table_column_count_mismatch() {
+    let input = "::table\n| a | b |\n| --- | --- | --- |\n| 1 | 2 |\n| 1 |\n::";
     let mut parser = Parser::new(Profile::Litedoc);
-    let doc = parser.parse(input).unwrap();
+    let result = parser.parse_with_recovery(input);
 
-    if let Block::Math(m) = &doc.blocks[0] {
-        assert!(!m.display);
+    if let Block::Table(t) = &result.document.blocks[0] {
+        assert_eq!(t.rows.len(), 3);
+        assert_eq!(t.rows[2].cells.len(), 1);
     } else {
-        panic!("Expected math block");
+        panic!("Expected table block");
     }
+
+    let errors: Vec<_> = result.errors.iter().collect();
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .all(|e| e.kind == ParseErrorKind::InvalidSyntax));
+    assert_eq!(
+        errors[1].message,
+        "invalid syntax in table row (expected 2 cells, found 1)"
+    );
+    let span = errors[1].span.unwrap();
+    assert_eq!(&input[span.start as usize..span.end as usize], "| 1 |");
 }
 
 // ============================================================================
-// Thematic Break Tests
+// Footnotes Block Tests
 // ============================================================================
 
 #[test]
-fn test_parse_thematic_break() {
-    let input = "Paragraph one.\n\n---\n\nParagraph two.";
+fn test_parse_footnotes() {
+    let input = "::footnotes\n[^1]: First footnote.\n[^2]: Second footnote.\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Footnotes(f) = &doc.blocks[0] {
+        assert_eq!(f.defs.len(), 2);
+        assert_eq!(f.defs[0].label.as_ref(), "1");
+        assert_eq!(f.defs[1].label.as_ref(), "2");
+    } else {
+        panic!("Expected footnotes block");
+    }
+}
+
+// ============================================================================
+// Sources and Citation Tests
+// ============================================================================
+
+#[test]
+fn test_parse_sources_and_citations() {
+    let input = "Warming is unequivocal [@ipcc2023].\n\n::sources\n\
+                 - ipcc2023 title=\"AR6 Synthesis Report\" url=https://ipcc.ch/ar6 year=2023\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    let Block::Paragraph(p) = &doc.blocks[0] else {
+        panic!("Expected paragraph");
+    };
+    let Inline::Citation(c) = &p.content[1] else {
+        panic!("Expected citation, got {:?}", p.content[1]);
+    };
+    assert_eq!(c.key.as_ref(), "ipcc2023");
+    assert_eq!(
+        &input[c.span.start as usize..c.span.end as usize],
+        "[@ipcc2023]"
+    );
+
+    let Block::Sources(s) = &doc.blocks[1] else {
+        panic!("Expected sources block");
+    };
+    assert_eq!(s.entries.len(), 1);
+    let entry = &s.entries[0];
+    assert_eq!(entry.id.as_ref(), "ipcc2023");
+    assert_eq!(entry.title.as_deref(), Some("AR6 Synthesis Report"));
+    assert_eq!(entry.url.as_deref(), Some("https://ipcc.ch/ar6"));
+    assert!(entry
+        .attrs
+        .iter()
+        .any(|(k, v)| k == "year" && matches!(v, AttrValue::Int(2023))));
+}
+
+#[test]
+fn test_citation_needs_closing_bracket() {
+    let input = "Mail [@ team] or [@key more].";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    let Block::Paragraph(p) = &doc.blocks[0] else {
+        panic!("Expected paragraph");
+    };
+    assert!(!p.content.iter().any(|i| matches!(i, Inline::Citation(_))));
+}
+
+#[test]
+fn test_resolve_citations() {
+    let input = "See [@a] and [@missing].\n\n::sources\n- a\n- unused\n- a title=Again\n::";
+    let mut parser = Parser::new(Profile::Litedoc).with_reference_checks(true);
+    let result = parser.parse_with_recovery(input);
+
+    let errors: Vec<_> = result.errors.iter().collect();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(errors[0].kind, ParseErrorKind::InvalidSyntax);
+    assert!(errors[0].message.contains("duplicate @a"));
+    assert_eq!(errors[1].kind, ParseErrorKind::UnresolvedReference);
+    assert_eq!(errors[1].message, "unresolved reference: @missing");
+    assert_eq!(errors[2].kind, ParseErrorKind::UnusedDefinition);
+    assert_eq!(errors[2].message, "unused definition: @unused");
+    let span = errors[2].span.unwrap();
+    assert_eq!(&input[span.start as usize..span.end as usize], "- unused");
+}
+
+#[test]
+fn test_unresolved_citations_parse_by_default() {
+    let input = "Per [@missing].\n\n::sources\n- uncited title=\"Spare\"\n::";
+    let doc = Parser::new(Profile::Litedoc).parse(input).unwrap();
+    assert_eq!(doc.blocks.len(), 2);
+
+    let errors = resolve_references(&doc);
+    let kinds: Vec<_> = errors.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            ParseErrorKind::UnresolvedReference,
+            ParseErrorKind::UnusedDefinition
+        ]
+    );
+}
+
+#[test]
+fn test_malformed_sources_entries() {
+    let input = "::sources\n- ok\n- bad!id\nnot an entry\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let result = parser.parse_with_recovery(input);
+
+    let kinds: Vec<_> = result.errors.iter().map(|e| e.kind).collect();
+    assert!(kinds.contains(&ParseErrorKind::InvalidSyntax));
+    assert!(kinds.contains(&ParseErrorKind::UnclosedDelimiter));
+}
+
+// ============================================================================
+// Math Block Tests
+// ============================================================================
+
+#[test]
+fn test_parse_math_block() {
+    let input = "::math display\nE = mc^2\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Math(m) = &doc.blocks[0] {
+        assert!(m.display);
+        assert!(m.content.contains("E = mc^2"));
+    } else {
+        panic!("Expected math block");
+    }
+}
+
+#[test]
+fn test_parse_math_inline() {
+    let input = "::math\nx + y = z\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Math(m) = &doc.blocks[0] {
+        assert!(!m.display);
+    } else {
+        panic!("Expected math block");
+    }
+}
+
+// ============================================================================
+// Thematic Break Tests
+// ============================================================================
+
+#[test]
+fn test_parse_thematic_break() {
+    let input = "Paragraph one.\n\n---\n\nParagraph two.";
     let mut parser = Parser::new(Profile::Litedoc);
     let doc = parser.parse(input).unwrap();
 
@@ -465,6 +1156,59 @@ fn test_parse_inline_code_span() {
     }
 }
 
+#[test]
+fn test_parse_multi_backtick_code_spans() {
+    let input = "Run ``echo `date` *now*`` or `` `x` `` or ```a``b```.";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    let Block::Paragraph(p) = &doc.blocks[0] else {
+        panic!("Expected paragraph");
+    };
+    let spans: Vec<_> = p
+        .content
+        .iter()
+        .filter_map(|i| match i {
+            Inline::CodeSpan(c) => Some((c.content.as_ref(), c.span)),
+            _ => None,
+        })
+        .collect();
+    let contents: Vec<_> = spans.iter().map(|(c, _)| *c).collect();
+    // One space of padding is stripped from each side.
+    assert_eq!(contents, ["echo `date` *now*", "`x`", "a``b"]);
+    let (_, span) = spans[0];
+    assert_eq!(
+        &input[span.start as usize..span.end as usize],
+        "``echo `date` *now*``"
+    );
+}
+
+#[test]
+fn test_code_span_fence_must_match() {
+    let input = "A ``lone fence and `one` here, ` ` and `  a  `.";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    let Block::Paragraph(p) = &doc.blocks[0] else {
+        panic!("Expected paragraph");
+    };
+    // The unmatched `` is text; single backticks still pair up, an
+    // all-space span is kept as is, and only one space is stripped.
+    let Inline::Text(t) = &p.content[0] else {
+        panic!("Expected text");
+    };
+    assert_eq!(t.content.as_ref(), "A ``lone fence and ");
+    let contents: Vec<_> = p
+        .content
+        .iter()
+        .filter_map(|i| match i {
+            Inline::CodeSpan(c) => Some(c.content.as_ref()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(contents, ["one", " ", " a "]);
+}
+
 #[test]
 fn test_parse_inline_link() {
     let input = "Visit [[Example|https://example.com]] for more.";
@@ -485,6 +1229,76 @@ fn test_parse_inline_link() {
     }
 }
 
+#[test]
+fn test_parse_link_label_and_title() {
+    let input =
+        "See [[**bold** docs|https://x.y/docs \"The \\\"docs\\\"\"]] and [[#intro]].\n\n# Intro";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        if let Inline::Link(l) = &p.content[1] {
+            assert!(matches!(&l.label[0], Inline::Strong(_)));
+            assert!(matches!(&l.label[1], Inline::Text(t) if t.content == " docs"));
+            assert_eq!(l.url.as_ref(), "https://x.y/docs");
+            assert_eq!(l.kind, LinkKind::Absolute);
+            assert_eq!(l.title.as_deref(), Some("The \"docs\""));
+        } else {
+            panic!("Expected link");
+        }
+        if let Inline::Link(l) = &p.content[3] {
+            // Implicit labels are the URL itself.
+            assert!(matches!(&l.label[..], [Inline::Text(t)] if t.content == "#intro"));
+            assert_eq!(l.kind, LinkKind::Fragment);
+            assert_eq!(l.title, None);
+        } else {
+            panic!("Expected link");
+        }
+    } else {
+        panic!("Expected paragraph");
+    }
+}
+
+#[test]
+fn test_link_kinds() {
+    assert_eq!(LinkKind::of("https://example.com"), LinkKind::Absolute);
+    assert_eq!(
+        LinkKind::of("ftp://files.example.com/a"),
+        LinkKind::Absolute
+    );
+    assert_eq!(LinkKind::of("guide/intro.ld"), LinkKind::Relative);
+    assert_eq!(LinkKind::of("../up.ld#part"), LinkKind::Relative);
+    assert_eq!(LinkKind::of("C:/docs/file.ld"), LinkKind::Relative);
+    assert_eq!(LinkKind::of("#install"), LinkKind::Fragment);
+    assert_eq!(LinkKind::of("MAILTO:team@example.com"), LinkKind::Email);
+}
+
+#[test]
+fn test_malformed_link_urls() {
+    let input = "[[a|]] [[b|has space]] [[c|#]] [[d|mailto:nobody]] [[e|https://]] \
+                 [[f|file:///tmp/x]] [[g|https://ok.example/a?b#c]]";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let result = parser.parse_with_recovery(input);
+
+    let messages: Vec<_> = result.errors.iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "invalid syntax in link URL (empty)",
+            "invalid syntax in link URL (contains whitespace)",
+            "invalid syntax in link URL (empty fragment)",
+            "invalid syntax in link URL (missing email address)",
+            "invalid syntax in link URL (missing host)",
+        ]
+    );
+    assert!(result
+        .errors
+        .iter()
+        .all(|e| e.kind == ParseErrorKind::InvalidSyntax));
+    let first = result.errors.iter().next().unwrap().span.unwrap();
+    assert_eq!(&input[first.start as usize..first.end as usize], "[[a|]]");
+}
+
 #[test]
 fn test_parse_inline_autolink() {
     let input = "Check <https://example.com> for details.";
@@ -536,6 +1350,266 @@ fn test_parse_inline_footnote_ref() {
     }
 }
 
+#[test]
+fn test_parse_inline_math() {
+    let input = "@modules math\n\nLet \\(a*b*c \\\\ `x`\\) hold.";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        assert_eq!(p.content.len(), 3);
+        if let Inline::Math(m) = &p.content[1] {
+            // Contents are raw: no emphasis, code span or escape handling.
+            assert_eq!(m.content.as_ref(), "a*b*c \\\\ `x`");
+            let start = input.find("\\(").unwrap() as u32;
+            assert_eq!(m.span.start, start);
+            assert_eq!(m.span.end as usize, input.find(" hold").unwrap());
+        } else {
+            panic!("Expected inline math");
+        }
+    } else {
+        panic!("Expected paragraph");
+    }
+}
+
+#[test]
+fn test_inline_math_requires_math_module() {
+    // Without the math module, `\(` is an escaped parenthesis.
+    let input = "Let \\(a*b*c\\) hold.";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        assert!(!p.content.iter().any(|i| matches!(i, Inline::Math(_))));
+        assert!(p.content.iter().any(|i| matches!(i, Inline::Emphasis(_))));
+    } else {
+        panic!("Expected paragraph");
+    }
+
+    // An unclosed delimiter stays text even with the module.
+    let mut parser = Parser::new(Profile::Litedoc).with_modules([Module::Math]);
+    let doc = parser.parse("Cost \\(5").unwrap();
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        assert!(!p.content.iter().any(|i| matches!(i, Inline::Math(_))));
+    }
+}
+
+#[test]
+fn test_parse_mentions() {
+    let input = "@modules mentions\n\nAsk <@agent:summarizer-v2> to close <@task:task_8f3a2b>.";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    let Block::Paragraph(p) = &doc.blocks[0] else {
+        panic!("Expected paragraph");
+    };
+    assert_eq!(p.content.len(), 5);
+    let Inline::Mention(m) = &p.content[1] else {
+        panic!("Expected mention, got {:?}", p.content[1]);
+    };
+    assert_eq!(m.kind.as_ref(), "agent");
+    assert_eq!(m.id.as_ref(), "summarizer-v2");
+    assert_eq!(
+        &input[m.span.start as usize..m.span.end as usize],
+        "<@agent:summarizer-v2>"
+    );
+
+    // Malformed mentions stay text.
+    let doc = parser
+        .parse("@modules mentions\n\n<@agent> <@:x> <@Agent:x> <@agent:a b>")
+        .unwrap();
+    assert!(doc.mentions().is_empty());
+}
+
+#[test]
+fn test_mentions_require_mentions_module() {
+    let input = "Ask <@agent:planner>.";
+    let mut parser = Parser::new(Profile::Litedoc).with_module_enforcement(true);
+    let doc = parser.parse(input).unwrap();
+    assert!(doc.mentions().is_empty());
+
+    let mut parser = Parser::new(Profile::Litedoc).with_modules([Module::Mentions]);
+    let doc = parser.parse(input).unwrap();
+    assert_eq!(doc.mentions().len(), 1);
+}
+
+#[test]
+fn test_mention_kinds() {
+    let input = "@modules mentions\n\n<@agent:a> <@user:alice>";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let result = parser.parse_with_recovery(input);
+
+    // Unknown kinds are reported but still parsed.
+    assert_eq!(result.errors.len(), 1);
+    let error = result.errors.iter().next().unwrap();
+    assert_eq!(error.kind, ParseErrorKind::InvalidSyntax);
+    assert!(error.message.contains("unknown user"));
+    assert_eq!(result.document.mentions().len(), 2);
+
+    let mut parser = Parser::new(Profile::Litedoc).with_mention_kinds(["agent", "user"]);
+    assert!(parser.parse(input).is_ok());
+}
+
+#[test]
+fn test_document_mentions() {
+    let input = "@modules mentions, tables\n\n# Handoff to <@agent:writer>\n\n\
+                 - **<@tool:web.search>** then [[<@task:t1>|#handoff-to-writer]]\n\n\
+                 ::table\n| <@agent:reviewer> |\n::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    let mentions: Vec<_> = doc
+        .mentions()
+        .iter()
+        .map(|m| format!("{}:{}", m.kind, m.id))
+        .collect();
+    assert_eq!(
+        mentions,
+        [
+            "agent:writer",
+            "tool:web.search",
+            "task:t1",
+            "agent:reviewer"
+        ]
+    );
+}
+
+#[test]
+fn test_parse_escapes() {
+    let input = "\\*not emphasis\\* and C:\\path with \\`tick\\` and \\\\ slash";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        assert_eq!(p.content.len(), 1);
+        if let Inline::Text(t) = &p.content[0] {
+            // Only punctuation is escapable; `\p` stays literal.
+            assert_eq!(
+                t.content.as_ref(),
+                "*not emphasis* and C:\\path with `tick` and \\ slash"
+            );
+            assert!(matches!(t.content, Cow::Owned(_)));
+            assert_eq!(t.span.end as usize, input.len());
+        } else {
+            panic!("Expected text");
+        }
+    } else {
+        panic!("Expected paragraph");
+    }
+
+    // Text without an escape still borrows from the input.
+    let doc = parser.parse("plain C:\\path text").unwrap();
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        if let Inline::Text(t) = &p.content[0] {
+            assert_eq!(t.content.as_ref(), "plain C:\\path text");
+            assert!(matches!(t.content, Cow::Borrowed(_)));
+        }
+    }
+}
+
+#[test]
+fn test_escaped_closers_do_not_close() {
+    let input = "*a \\* b* and [[x\\|y|https://e.com]]";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        if let Inline::Emphasis(e) = &p.content[0] {
+            assert!(matches!(&e.content[..], [Inline::Text(t)] if t.content == "a * b"));
+        } else {
+            panic!("Expected emphasis");
+        }
+        if let Inline::Link(l) = &p.content[2] {
+            assert!(matches!(&l.label[..], [Inline::Text(t)] if t.content == "x|y"));
+            assert_eq!(l.url.as_ref(), "https://e.com");
+        } else {
+            panic!("Expected link");
+        }
+    } else {
+        panic!("Expected paragraph");
+    }
+}
+
+#[test]
+fn test_escapes_in_container_paragraph() {
+    // Continuation prefixes are removed from escaped text as well.
+    let input = "> one \\*\n> two \\[";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Quote(q) = &doc.blocks[0] {
+        if let Block::Paragraph(p) = &q.blocks[0] {
+            assert!(matches!(
+                &p.content[..],
+                [Inline::Text(a), Inline::SoftBreak(_), Inline::Text(b)]
+                    if a.content == "one *" && b.content == "two ["
+            ));
+        } else {
+            panic!("Expected paragraph");
+        }
+    } else {
+        panic!("Expected quote");
+    }
+}
+
+#[test]
+fn test_parse_line_breaks() {
+    let input = "one  \n  two\\\nthree \\\\\nfour";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        let texts: Vec<_> = p
+            .content
+            .iter()
+            .filter_map(|i| match i {
+                Inline::Text(t) => Some(t.content.as_ref()),
+                _ => None,
+            })
+            .collect();
+        // Spaces around line ends are dropped; an escaped backslash is text.
+        assert_eq!(texts, ["one", "two", "three \\", "four"]);
+
+        let breaks: Vec<_> = p
+            .content
+            .iter()
+            .filter_map(|i| match i {
+                Inline::SoftBreak(s) => Some((false, *s)),
+                Inline::HardBreak(s) => Some((true, *s)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(breaks.len(), 3);
+        assert!(!breaks[0].0 && !breaks[2].0);
+        assert!(breaks[1].0);
+        let (_, hard) = breaks[1];
+        assert_eq!(&input[hard.start as usize..hard.end as usize], "\\\n");
+        let (_, soft) = breaks[0];
+        assert_eq!(&input[soft.start as usize..soft.end as usize], "\n");
+    } else {
+        panic!("Expected paragraph");
+    }
+}
+
+#[test]
+fn test_md_trailing_space_hard_break() {
+    let input = "one  \ntwo";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Paragraph(p) = &doc.blocks[0] {
+        if let [Inline::Text(a), Inline::HardBreak(span), Inline::Text(b)] = &p.content[..] {
+            assert_eq!(a.content.as_ref(), "one");
+            assert_eq!(b.content.as_ref(), "two");
+            assert_eq!(&input[span.start as usize..span.end as usize], "  \n");
+        } else {
+            panic!("Expected a hard break, got {:?}", p.content);
+        }
+    } else {
+        panic!("Expected paragraph");
+    }
+}
+
 #[test]
 fn test_parse_nested_inline() {
     let input = "This is **bold with *italic* inside**.";
@@ -558,6 +1632,331 @@ fn test_parse_nested_inline() {
     }
 }
 
+// ============================================================================
+// Markdown Profile Tests
+// ============================================================================
+
+#[test]
+fn test_md_bullet_list() {
+    let input = "- one\n- two\n  continued\n  - nested\n- three";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 1);
+    if let Block::List(l) = &doc.blocks[0] {
+        assert_eq!(l.kind, ListKind::Unordered);
+        assert_eq!(l.items.len(), 3);
+
+        let second = &l.items[1];
+        assert_eq!(second.blocks.len(), 2);
+        if let Block::Paragraph(p) = &second.blocks[0] {
+            assert!(matches!(
+                &p.content[..],
+                [Inline::Text(a), Inline::SoftBreak(_), Inline::Text(b)]
+                    if a.content == "two" && b.content == "continued"
+            ));
+        } else {
+            panic!("Expected paragraph in list item");
+        }
+        assert!(matches!(&second.blocks[1], Block::List(_)));
+    } else {
+        panic!("Expected list block");
+    }
+}
+
+#[test]
+fn test_md_task_list() {
+    let input = "- [x] shipped\n- [ ] pending";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::List(l) = &doc.blocks[0] {
+        assert_eq!(l.items[0].checked, Some(true));
+        assert_eq!(l.items[1].checked, Some(false));
+    } else {
+        panic!("Expected list block");
+    }
+}
+
+#[test]
+fn test_md_ordered_list() {
+    let input = "3. three\n4. four";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::List(l) = &doc.blocks[0] {
+        assert_eq!(l.kind, ListKind::Ordered);
+        assert_eq!(l.start, Some(3));
+        assert_eq!(l.items.len(), 2);
+    } else {
+        panic!("Expected list block");
+    }
+}
+
+#[test]
+fn test_md_block_quote() {
+    let input = "> quoted\nlazy\n>\n> - item";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 1);
+    if let Block::Quote(q) = &doc.blocks[0] {
+        assert_eq!(q.blocks.len(), 2);
+        assert!(matches!(&q.blocks[0], Block::Paragraph(_)));
+        assert!(matches!(&q.blocks[1], Block::List(_)));
+    } else {
+        panic!("Expected quote block");
+    }
+}
+
+#[test]
+fn test_md_setext_headings() {
+    let input = "Title\n=====\n\nSection\n---";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 2);
+    assert!(matches!(&doc.blocks[0], Block::Heading(h) if h.level == 1));
+    assert!(matches!(&doc.blocks[1], Block::Heading(h) if h.level == 2));
+}
+
+#[test]
+fn test_md_indented_code() {
+    let input = "Para.\n\n    let x = 1;\n\n    let y = 2;\n\nAfter.";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 3);
+    if let Block::CodeBlock(c) = &doc.blocks[1] {
+        assert_eq!(c.content.as_ref(), "let x = 1;\n\nlet y = 2;");
+    } else {
+        panic!("Expected code block");
+    }
+}
+
+#[test]
+fn test_md_indented_paragraph() {
+    fn texts(block: &Block) -> Vec<String> {
+        let Block::Paragraph(p) = block else {
+            panic!("Expected paragraph, got {block:?}");
+        };
+        p.content
+            .iter()
+            .filter_map(|i| match i {
+                Inline::Text(t) => Some(t.content.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse("  hello\n   world").unwrap();
+    assert_eq!(texts(&doc.blocks[0]), ["hello", "world"]);
+
+    // Inside containers, indentation past the item's content or the quote
+    // marker belongs to the paragraph and is dropped as well.
+    let doc = parser.parse("- a\n\n    code\n\n> x\n>   y").unwrap();
+    let Block::List(list) = &doc.blocks[0] else {
+        panic!("Expected list");
+    };
+    assert_eq!(texts(&list.items[0].blocks[1]), ["code"]);
+    let Block::Quote(quote) = &doc.blocks[1] else {
+        panic!("Expected quote");
+    };
+    assert_eq!(texts(&quote.blocks[0]), ["x", "y"]);
+
+    let doc = parser.parse("  Title\n  =====").unwrap();
+    let Block::Heading(h) = &doc.blocks[0] else {
+        panic!("Expected heading");
+    };
+    assert_eq!(h.id.as_deref(), Some("title"));
+    assert!(matches!(&h.content[..], [Inline::Text(t)] if t.content == "Title"));
+}
+
+#[test]
+fn test_md_pipe_table() {
+    let input = "| Name | Age |\n|:-----|----:|\n| Alice | 30 |";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    if let Block::Table(t) = &doc.blocks[0] {
+        assert_eq!(t.rows.len(), 2);
+        assert!(t.rows[0].header);
+        assert_eq!(t.rows[1].cells.len(), 2);
+        assert_eq!(t.alignments, vec![Alignment::Left, Alignment::Right]);
+    } else {
+        panic!("Expected table block");
+    }
+
+    // Pipe tables are a GFM extension, not CommonMark core.
+    let mut strict = Parser::new(Profile::MdStrict);
+    let doc = strict.parse(input).unwrap();
+    assert!(matches!(&doc.blocks[0], Block::Paragraph(_)));
+}
+
+#[test]
+fn test_md_profile_directive_enables_md_syntax() {
+    let input = "@profile md\n\n- a\n- b";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+    assert!(matches!(&doc.blocks[0], Block::List(_)));
+}
+
+#[test]
+fn test_litedoc_profile_ignores_md_syntax() {
+    let input = "- a\n- b\n> quote";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 1);
+    assert!(matches!(&doc.blocks[0], Block::Paragraph(_)));
+}
+
+#[test]
+fn test_md_nested_spans_point_into_input() {
+    let input = "- outer\n  - inner";
+    let mut parser = Parser::new(Profile::Md);
+    let doc = parser.parse(input).unwrap();
+
+    let Block::List(outer) = &doc.blocks[0] else {
+        panic!("Expected list block");
+    };
+    let Block::List(inner) = &outer.items[0].blocks[1] else {
+        panic!("Expected nested list");
+    };
+    let Block::Paragraph(p) = &inner.items[0].blocks[0] else {
+        panic!("Expected paragraph");
+    };
+    let span = p.span;
+    assert_eq!(&input[span.start as usize..span.end as usize], "inner");
+}
+
+// ============================================================================
+// Custom Block Tests
+// ============================================================================
+
+struct Plan;
+
+impl BlockHandler for Plan {}
+
+struct ToolCall;
+
+impl BlockHandler for ToolCall {
+    fn body(&self) -> BodyKind {
+        BodyKind::Content
+    }
+}
+
+struct Verdict;
+
+impl BlockHandler for Verdict {
+    fn body(&self) -> BodyKind {
+        BodyKind::Content
+    }
+
+    fn parse<'a>(&self, ctx: &mut BlockContext<'a, '_>) -> Result<CustomBody<'a>, String> {
+        if !ctx.attrs.iter().any(|(k, _)| k == "value") {
+            return Err("missing value".to_string());
+        }
+        Ok(CustomBody::Content(ctx.content()))
+    }
+}
+
+#[test]
+fn test_custom_block_unregistered() {
+    let input = "::plan
+- step
+::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let result = parser.parse_with_recovery(input);
+
+    assert!(matches!(&result.document.blocks[0], Block::Raw(_)));
+    let errors: Vec<_> = result.errors.iter().collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::UnknownDirective);
+}
+
+#[test]
+fn test_custom_block_nested_body() {
+    let input = "::plan id=p1
+# Steps
+
+::list
+- one
+::
+::
+
+After";
+    let mut parser = Parser::new(Profile::Litedoc);
+    parser.register_block("plan", Plan);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 2);
+    if let Block::Custom(c) = &doc.blocks[0] {
+        assert_eq!(c.name.as_ref(), "plan");
+        assert_eq!(c.attrs, vec![("id".into(), AttrValue::Str("p1".into()))]);
+        assert_eq!(c.span.end as usize, input.find("\n\nAfter").unwrap());
+        let CustomBody::Blocks(blocks) = &c.body else {
+            panic!("Expected nested blocks");
+        };
+        assert_eq!(blocks.len(), 2);
+        assert!(matches!(&blocks[0], Block::Heading(_)));
+        assert!(matches!(&blocks[1], Block::List(_)));
+    } else {
+        panic!("Expected custom block");
+    }
+}
+
+#[test]
+fn test_custom_block_content_body() {
+    let input = "::callout
+::tool_call name=search
+{\"q\": \"::\"}
+::list
+::
+Text
+::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    parser.register_block("tool_call", ToolCall);
+    let doc = parser.parse(input).unwrap();
+
+    let Block::Callout(callout) = &doc.blocks[0] else {
+        panic!("Expected callout block");
+    };
+    assert_eq!(callout.blocks.len(), 2);
+    if let Block::Custom(c) = &callout.blocks[0] {
+        assert_eq!(c.name.as_ref(), "tool_call");
+        assert_eq!(
+            c.body,
+            CustomBody::Content("{\"q\": \"::\"}\n::list".into())
+        );
+    } else {
+        panic!("Expected custom block");
+    }
+    assert!(matches!(&callout.blocks[1], Block::Paragraph(_)));
+}
+
+#[test]
+fn test_custom_block_handler_error() {
+    let input = "::verdict
+pass
+::
+
+::verdict value=pass
+::";
+    let mut parser = Parser::new(Profile::Litedoc);
+    parser.register_block("verdict", Verdict);
+    let result = parser.parse_with_recovery(input);
+
+    let blocks = &result.document.blocks;
+    assert!(matches!(&blocks[0], Block::Raw(r) if r.content == "pass"));
+    assert!(matches!(&blocks[1], Block::Custom(c) if c.body == CustomBody::Content("".into())));
+    let errors: Vec<_> = result.errors.iter().collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::InvalidSyntax);
+    assert!(errors[0].message.contains("verdict"));
+}
+
 // ============================================================================
 // Edge Cases and Error Handling
 // ============================================================================
@@ -584,11 +1983,16 @@ fn test_parse_whitespace_only() {
 fn test_parse_unclosed_code_block() {
     let input = "```rust\nfn main() {}";
     let mut parser = Parser::new(Profile::Litedoc);
-    let doc = parser.parse(input).unwrap();
+    let result = parser.parse_with_recovery(input);
 
-    // Parser should still produce a code block
+    // Parser should still produce a code block, and report the missing fence
+    let doc = &result.document;
     assert_eq!(doc.blocks.len(), 1);
     assert!(matches!(&doc.blocks[0], Block::CodeBlock(_)));
+    let errors: Vec<_> = result.errors.iter().collect();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ParseErrorKind::UnclosedDelimiter);
+    assert_eq!(errors[0].span, Some(litedoc_core::span::Span::new(0, 7)));
 }
 
 #[test]
@@ -601,6 +2005,16 @@ fn test_parse_unclosed_fenced_block() {
     assert_eq!(doc.blocks.len(), 1);
 }
 
+#[test]
+fn test_parse_line_starting_with_single_colon() {
+    let input = ":not a fence\nnext line";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let doc = parser.parse(input).unwrap();
+
+    assert_eq!(doc.blocks.len(), 1);
+    assert!(matches!(&doc.blocks[0], Block::Paragraph(_)));
+}
+
 #[test]
 fn test_span_tracking() {
     let input = "# Hello";
@@ -662,3 +2076,26 @@ Final paragraph with a [[link|https://example.com]].
     assert!(doc.metadata.is_some());
     assert!(doc.blocks.len() >= 7);
 }
+
+#[test]
+fn test_document_into_owned() {
+    let input = "--- meta\ntitle: \"Owned\"\ntags: [a, b]\n---\n\n# Title\n\n\
+                 Text with **bold \\*** and [[a link|https://example.com \"t\"]].\n\n\
+                 ::callout type=note\n- item `code`\n::\n\n\
+                 ::sources\n- k title=K\n::\n\nCited [@k].";
+    let mut parser = Parser::new(Profile::Litedoc);
+    let expected = parser.parse(input).unwrap();
+
+    let buffer = input.to_string();
+    let owned = parser.parse(&buffer).unwrap().into_owned();
+    drop(buffer);
+
+    // The owned document outlives its input and can move across threads.
+    let owned = std::thread::spawn(move || owned).join().unwrap();
+    assert_eq!(owned, expected);
+    if let Block::Heading(h) = &owned.blocks[0] {
+        assert!(matches!(h.id, Some(Cow::Owned(_))));
+    } else {
+        panic!("Expected heading");
+    }
+}
@@ -0,0 +1,215 @@
+//! Integration tests for selector queries
+
+use litedoc_core::query::{Node, Selector};
+use litedoc_core::span::Span;
+use litedoc_core::{Block, BlockHandler, BodyKind, Document, ParseErrorKind, Parser, Profile};
+
+const REPORT: &str = "@modules tables, tasks, mentions\n\n\
+# Report\n\n\
+## Summary\n\n\
+Ask <@agent:triage> about [[the logs|https://ci.example/logs]].\n\n\
+::callout type=warning title=\"Flaky\"\n\
+Retried **three** times.\n\
+::\n\n\
+## Source Analysis\n\n\
+::table\n| File | Lines |\n| --- | --- |\n| parser.rs | 900 |\n| lexer.rs | 300 |\n::\n\n\
+::list\n- [x] Parse\n- [ ] Format\n|\n| ::callout type=note\n| Later.\n| ::\n::\n\n\
+::callout type=warning\nDisk *almost* full.\n::\n";
+
+fn parse(input: &str) -> Document<'_> {
+    Parser::new(Profile::Litedoc).parse(input).unwrap()
+}
+
+/// Name and text of each node.
+fn describe<'d>(nodes: &[Node<'d>]) -> Vec<(&'d str, String)> {
+    nodes.iter().map(|n| (n.name(), n.text())).collect()
+}
+
+// ============================================================================
+// Selection Tests
+// ============================================================================
+
+#[test]
+fn test_select_by_type_and_attribute() {
+    let doc = parse(REPORT);
+
+    let warnings = doc.select("callout[type=warning]").unwrap();
+    assert_eq!(
+        describe(&warnings),
+        [
+            ("callout", "Retried three times.".to_string()),
+            ("callout", "Disk almost full.".to_string())
+        ]
+    );
+    let span = warnings[0].span();
+    let source = &REPORT[span.start as usize..span.end as usize];
+    assert!(source.starts_with("::callout type=warning"));
+
+    let titled = doc.select("callout[title]").unwrap();
+    assert_eq!(titled.len(), 1);
+    let others = doc.select("callout[type!=warning]").unwrap();
+    assert_eq!(describe(&others), [("callout", "Later.".to_string())]);
+
+    let headings = doc.select("heading[level=2]").unwrap();
+    assert_eq!(headings.len(), 2);
+    assert_eq!(doc.select("heading[id^=source]").unwrap().len(), 1);
+    assert_eq!(doc.select("link[url$=\"/logs\"]").unwrap().len(), 1);
+    assert_eq!(doc.select("mention[kind=agent][id*=ria]").unwrap().len(), 1);
+    assert_eq!(doc.select("item[checked=true]").unwrap().len(), 1);
+    assert!(doc.select("emoji").unwrap().is_empty());
+}
+
+#[test]
+fn test_select_heading_then_table() {
+    let doc = parse(REPORT);
+    let tables = doc
+        .select(r#"heading[level=2]:contains("Source Analysis") + table"#)
+        .unwrap();
+    assert_eq!(tables.len(), 1);
+    let Node::Block(Block::Table(table)) = tables[0] else {
+        panic!("Expected table");
+    };
+    assert_eq!(table.rows.len(), 3);
+
+    // Adjacent means immediately after: the summary is followed by a paragraph.
+    assert!(doc
+        .select(r#"heading:contains(Summary) + table"#)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        doc.select(r#"heading:contains(Summary) ~ table"#)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_select_child_and_descendant() {
+    let doc = parse(REPORT);
+
+    assert_eq!(doc.select("list > item").unwrap().len(), 2);
+    assert!(doc.select("list > callout").unwrap().is_empty());
+    assert_eq!(
+        describe(&doc.select("list callout").unwrap()),
+        [("callout", "Later.".to_string())]
+    );
+    assert_eq!(
+        describe(&doc.select("callout strong").unwrap()),
+        [("strong", "three".to_string())]
+    );
+
+    let cells = doc
+        .select("table row[header=false] > cell:first-child")
+        .unwrap();
+    assert_eq!(
+        describe(&cells),
+        [
+            ("cell", "parser.rs".to_string()),
+            ("cell", "lexer.rs".to_string())
+        ]
+    );
+    let last = doc.select("row:last-child cell:nth-child(2)").unwrap();
+    assert_eq!(describe(&last), [("cell", "300".to_string())]);
+}
+
+#[test]
+fn test_select_list_in_document_order() {
+    let doc = parse(REPORT);
+    let nodes = doc
+        .select("table, heading, callout[type=warning], *:contains(Report)")
+        .unwrap();
+    let names: Vec<_> = nodes.iter().map(|n| n.name()).collect();
+    assert_eq!(
+        names,
+        ["heading", "text", "heading", "callout", "heading", "table", "callout"]
+    );
+
+    // Spans increase, and nothing is reported twice.
+    assert!(nodes
+        .windows(2)
+        .all(|w| w[0].span().start <= w[1].span().start && w[0] != w[1]));
+}
+
+#[test]
+fn test_select_custom_blocks() {
+    struct ToolCall;
+
+    impl BlockHandler for ToolCall {
+        fn body(&self) -> BodyKind {
+            BodyKind::Content
+        }
+    }
+
+    let mut parser = Parser::new(Profile::Litedoc);
+    parser.register_block("tool_call", ToolCall);
+    let doc = parser
+        .parse("::tool_call name=search\n{\"q\": \"x\"}\n::\n\n::tool_call name=fetch\n{}\n::")
+        .unwrap();
+    let calls = doc.select("tool_call[name=fetch]").unwrap();
+    assert_eq!(describe(&calls), [("tool_call", "{}".to_string())]);
+}
+
+#[test]
+fn test_select_long_selector_on_deep_document() {
+    // Every quote is an ancestor candidate for every compound; matching must
+    // not try each way of assigning them.
+    let input = format!("{}x", "> ".repeat(60));
+    let doc = Parser::new(Profile::Md).parse(&input).unwrap();
+    let quotes = "quote ".repeat(20);
+
+    assert!(doc
+        .select(&format!("heading {quotes}paragraph"))
+        .unwrap()
+        .is_empty());
+    assert_eq!(doc.select(&format!("{quotes}paragraph")).unwrap().len(), 1);
+    assert_eq!(doc.select(&format!("{quotes}quote")).unwrap().len(), 40);
+    assert!(doc
+        .select(&format!("{quotes}quote ~ paragraph"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_selector_reuse() {
+    let selector = Selector::parse("heading:first-child").unwrap();
+    for input in ["# One\n\nText.", "Text.\n\n# Two"] {
+        let doc = parse(input);
+        let expected = usize::from(input.starts_with('#'));
+        assert_eq!(selector.select(&doc).len(), expected);
+    }
+}
+
+// ============================================================================
+// Selector Error Tests
+// ============================================================================
+
+#[test]
+fn test_selector_errors() {
+    let cases = [
+        ("", Span::new(0, 0)),
+        ("list >", Span::new(6, 6)),
+        ("list >> item", Span::new(6, 7)),
+        ("callout[type=warning", Span::new(20, 20)),
+        ("callout[=warning]", Span::new(8, 9)),
+        ("callout[type=\"warning]", Span::new(13, 22)),
+        ("heading:contains()", Span::new(17, 18)),
+        ("heading:nth-child(0)", Span::new(18, 19)),
+        ("heading:hover", Span::new(7, 13)),
+        ("table,", Span::new(6, 6)),
+        ("table)", Span::new(5, 6)),
+    ];
+    for (selector, span) in cases {
+        let err = Selector::parse(selector).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::InvalidSyntax, "{selector}");
+        assert_eq!(err.span, Some(span), "{selector}: {err}");
+        assert!(err.message.starts_with("invalid selector: "));
+    }
+
+    let doc = parse("# Title");
+    let err = doc.select("heading:hover").unwrap_err();
+    assert_eq!(
+        err.message,
+        "invalid selector: unknown pseudo-class `:hover`"
+    );
+}
@@ -1,3 +1,5 @@
+use litedoc_core::ast::List;
+use litedoc_core::visit::{walk_block, walk_list, Visit};
 use litedoc_core::{Block, Parser, Profile};
 use pulldown_cmark::{Event, Options, Parser as MdParser, Tag, TagEnd};
 use std::fs;
@@ -44,39 +46,50 @@ struct Case<'a> {
     realistic_markdown: MutationList,
 }
 
-fn count_litedoc_blocks(blocks: &[Block], counts: &mut Counts, in_list: bool) {
-    for block in blocks {
+/// Counts blocks the way pulldown-cmark reports them, at every nesting depth.
+#[derive(Default)]
+struct LitedocCounter {
+    counts: Counts,
+    list_depth: u32,
+}
+
+impl<'ast> Visit<'ast> for LitedocCounter {
+    #[allow(clippy::collapsible_match)]
+    fn visit_block(&mut self, block: &'ast Block<'ast>) {
         match block {
-            Block::Heading(_) => counts.headings += 1,
+            Block::Heading(_) => self.counts.headings += 1,
             Block::Paragraph(_) => {
-                if !in_list {
-                    counts.paragraphs += 1;
+                if self.list_depth == 0 {
+                    self.counts.paragraphs += 1;
                 }
             }
             Block::List(list) => {
-                counts.lists += 1;
-                counts.list_items += list.items.len() as u32;
-                for item in &list.items {
-                    count_litedoc_blocks(&item.blocks, counts, true);
-                }
+                self.counts.lists += 1;
+                self.counts.list_items += list.items.len() as u32;
             }
-            Block::CodeBlock(_) => counts.code_blocks += 1,
-            Block::Table(_) => counts.tables += 1,
-            Block::Callout(callout) => count_litedoc_blocks(&callout.blocks, counts, in_list),
-            Block::Quote(quote) => count_litedoc_blocks(&quote.blocks, counts, in_list),
+            Block::CodeBlock(_) => self.counts.code_blocks += 1,
+            Block::Table(_) => self.counts.tables += 1,
             _ => {}
         }
+        walk_block(self, block);
+    }
+
+    fn visit_list(&mut self, list: &'ast List<'ast>) {
+        self.list_depth += 1;
+        walk_list(self, list);
+        self.list_depth -= 1;
     }
 }
 
 fn count_litedoc(input: &str) -> Counts {
     let mut parser = Parser::new(Profile::Litedoc);
     let result = parser.parse_with_recovery(input);
-    let mut counts = Counts::default();
-    count_litedoc_blocks(&result.document.blocks, &mut counts, false);
-    counts
+    let mut counter = LitedocCounter::default();
+    counter.visit_document(&result.document);
+    counter.counts
 }
 
+#[allow(clippy::collapsible_match)]
 fn count_markdown(input: &str) -> Counts {
     let mut counts = Counts::default();
     let parser = MdParser::new_ext(input, Options::all());
@@ -0,0 +1,151 @@
+//! Integration tests for the `serde` feature (canonical JSON shape)
+
+#![cfg(feature = "serde")]
+
+use litedoc_core::{BlockHandler, BodyKind, Document, Parser, Profile};
+use serde_json::json;
+
+const DOC: &str = "@modules tables, footnotes, math, mentions\n\n\
+--- meta ---\ntitle: \"Report\"\ndraft: true\nversion: 3\nscore: 0.5\ntags: [a, 2]\n---\n\n\
+# Title {#top}\n\n\
+::list ordered start=3\n- [[site|https://example.com \"Home\"]] and <@agent:planner>\n::\n\n\
+::table\n| a | b |\n| :-- | --: |\n| **1** | `2` |\n::\n\n\
+---\n\n\
+Line\\\nbreak[^1] [@src] \\(x\\)\n\n\
+::footnotes\n[^1]: Note.\n::\n\n\
+::sources\n- src title=\"Source\"\n::\n";
+
+// ============================================================================
+// Round-trip Tests
+// ============================================================================
+
+#[test]
+fn test_serde_round_trip() {
+    let doc = Parser::new(Profile::Litedoc).parse(DOC).unwrap();
+    let json = serde_json::to_string(&doc).unwrap();
+
+    // Loaded documents own their text and outlive the JSON they came from.
+    let loaded: Document<'static> = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded, doc);
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+}
+
+#[test]
+fn test_serde_round_trip_examples() {
+    for entry in std::fs::read_dir("../../examples").unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "ld") {
+            continue;
+        }
+        let input = std::fs::read_to_string(&path).unwrap();
+        let doc = Parser::new(Profile::Litedoc)
+            .parse_with_recovery(&input)
+            .document;
+        let json = serde_json::to_string(&doc).unwrap();
+        let loaded: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, doc, "{}", path.display());
+    }
+}
+
+// ============================================================================
+// Shape Tests
+// ============================================================================
+
+#[test]
+fn test_serde_document_shape() {
+    let doc = Parser::new(Profile::Md).parse("Hi\n").unwrap();
+    assert_eq!(
+        serde_json::to_value(&doc).unwrap(),
+        json!({
+            "profile": "md",
+            "modules": [],
+            "metadata": null,
+            "blocks": [{
+                "type": "Paragraph",
+                "content": [{"type": "Text", "content": "Hi", "span": [0, 2]}],
+                "span": [0, 2]
+            }],
+            "span": [0, 3]
+        })
+    );
+}
+
+#[test]
+fn test_serde_node_shapes() {
+    let doc = Parser::new(Profile::Litedoc).parse(DOC).unwrap();
+    let value = serde_json::to_value(&doc).unwrap();
+
+    assert_eq!(
+        value["modules"],
+        json!(["tables", "footnotes", "math", "mentions"])
+    );
+    assert_eq!(
+        value["metadata"]["entries"],
+        json!([
+            ["title", "Report"],
+            ["draft", true],
+            ["version", 3],
+            ["score", 0.5],
+            ["tags", ["a", 2]]
+        ])
+    );
+
+    let blocks = &value["blocks"];
+    assert_eq!(blocks[0]["type"], "Heading");
+    assert_eq!(blocks[0]["id"], "top");
+
+    let list = &blocks[1];
+    assert_eq!(list["kind"], "ordered");
+    assert_eq!(list["start"], 3);
+    assert_eq!(list["attrs"], json!([["ordered", true], ["start", 3]]));
+    let item = &list["items"][0];
+    assert_eq!(item["checked"], json!(null));
+    let inlines = &item["blocks"][0]["content"];
+    assert_eq!(inlines[0]["type"], "Link");
+    assert_eq!(inlines[0]["kind"], "absolute");
+    assert_eq!(inlines[0]["title"], "Home");
+    assert_eq!(inlines[2]["type"], "Mention");
+    assert_eq!(inlines[2]["kind"], "agent");
+
+    assert_eq!(blocks[2]["alignments"], json!(["left", "right"]));
+    assert_eq!(blocks[2]["rows"][0]["header"], true);
+    assert_eq!(blocks[3]["type"], "ThematicBreak");
+    assert!(blocks[3]["span"].is_array());
+
+    let para = &blocks[4]["content"];
+    assert_eq!(para[1]["type"], "HardBreak");
+    assert!(para[1]["span"].is_array());
+    assert_eq!(para[3]["type"], "FootnoteRef");
+    assert_eq!(para[5]["type"], "Citation");
+    assert_eq!(para[7]["type"], "Math");
+}
+
+#[test]
+fn test_serde_custom_body_shape() {
+    struct ToolCall;
+
+    impl BlockHandler for ToolCall {
+        fn body(&self) -> BodyKind {
+            BodyKind::Content
+        }
+    }
+
+    let mut parser = Parser::new(Profile::Litedoc);
+    parser.register_block("tool_call", ToolCall);
+    let doc = parser.parse("::tool_call name=x\nargs\n::\n").unwrap();
+    let value = serde_json::to_value(&doc).unwrap();
+    assert_eq!(value["blocks"][0]["type"], "Custom");
+    assert_eq!(value["blocks"][0]["body"], json!({"content": "args"}));
+}
+
+#[test]
+fn test_serde_rejects_wrong_shape() {
+    let bad = r#"{"profile":"litedoc","modules":[],"metadata":null,
+        "blocks":[{"type":"Heading","level":1,"id":null,"content":[],"span":{"start":0,"end":1}}],
+        "span":[0,1]}"#;
+    assert!(serde_json::from_str::<Document>(bad).is_err());
+
+    let unknown =
+        r#"{"profile":"litedoc","modules":["emoji"],"metadata":null,"blocks":[],"span":[0,0]}"#;
+    assert!(serde_json::from_str::<Document>(unknown).is_err());
+}
@@ -0,0 +1,79 @@
+//! Integration tests for line/column conversion of spans
+
+use litedoc_core::span::{LineCol, LineIndex, Span};
+use litedoc_core::{ParseError, Parser, Profile};
+
+fn pos(line: u32, byte_column: u32, char_column: u32, utf16_column: u32) -> LineCol {
+    LineCol {
+        line,
+        byte_column,
+        char_column,
+        utf16_column,
+    }
+}
+
+// ============================================================================
+// LineIndex Tests
+// ============================================================================
+
+#[test]
+fn test_line_index_lines() {
+    let index = LineIndex::new("ab\ncd\n\nef");
+    assert_eq!(index.line_count(), 4);
+    assert_eq!(index.line_col(0), pos(1, 1, 1, 1));
+    assert_eq!(index.line_col(2), pos(1, 3, 3, 3));
+    assert_eq!(index.line_col(3), pos(2, 1, 1, 1));
+    assert_eq!(index.line_col(6), pos(3, 1, 1, 1));
+    assert_eq!(index.line_col(8), pos(4, 2, 2, 2));
+    assert_eq!(index.line_col(9), pos(4, 3, 3, 3));
+
+    assert_eq!(LineIndex::new("").line_count(), 1);
+    assert_eq!(LineIndex::new("a\n").line_count(), 2);
+    assert_eq!(LineIndex::new("a\n").line_col(2), pos(2, 1, 1, 1));
+}
+
+#[test]
+fn test_line_index_unicode_columns() {
+    // é is 2 bytes and 1 UTF-16 unit; 🦀 is 4 bytes and 2 UTF-16 units.
+    let input = "x\né🦀=1";
+    let index = LineIndex::new(input);
+    let eq = input.find('=').unwrap() as u32;
+    assert_eq!(index.line_col(eq), pos(2, 7, 3, 4));
+
+    // Offsets inside a character point at the character.
+    assert_eq!(index.line_col(eq - 1), pos(2, 3, 2, 2));
+    assert_eq!(index.line_col(3), pos(2, 1, 1, 1));
+
+    // CRLF line endings leave the `\r` at the end of the line.
+    let index = LineIndex::new("a\r\nb");
+    assert_eq!(index.line_col(1), pos(1, 2, 2, 2));
+    assert_eq!(index.line_col(3), pos(2, 1, 1, 1));
+}
+
+#[test]
+fn test_line_index_range_past_end() {
+    let index = LineIndex::new("one\ntwo");
+    let (start, end) = index.range(Span::new(4, 100));
+    assert_eq!(start, pos(2, 1, 1, 1));
+    assert_eq!(end, pos(2, 4, 4, 4));
+    assert_eq!(index.range(Span::SYNTHETIC), (end, end));
+}
+
+// ============================================================================
+// Error Description Tests
+// ============================================================================
+
+#[test]
+fn test_error_describe() {
+    let input = "# Café\n\n::table\n| a | b |\n| --- | --- |\n| 1 |\n::\n";
+    let result = Parser::new(Profile::Litedoc).parse_with_recovery(input);
+    let error = result.errors.iter().next().unwrap();
+    let index = LineIndex::new(input);
+    assert_eq!(
+        error.describe(&index),
+        "invalid syntax in table row (expected 2 cells, found 1) at line 6, column 1"
+    );
+
+    let unplaced = ParseError::new("oops", None);
+    assert_eq!(unplaced.describe(&index), "oops");
+}
@@ -0,0 +1,151 @@
+//! Integration tests for AST traversal
+
+use std::collections::BTreeMap;
+
+use litedoc_core::ast::{Link, Text};
+use litedoc_core::visit::{
+    walk_block, walk_inline, walk_inline_mut, walk_link, walk_link_mut, Visit, VisitMut,
+};
+use litedoc_core::{format_document, Block, FormatOptions, Inline, Parser, Profile};
+
+const NESTED: &str = "@modules tables, footnotes, math, mentions\n\n\
+# Title with [[a link|#title-with-a-link]]\n\n\
+::list\n- item with **<@agent:planner>**\n|\n| ::quote\n| Quoted [[q|https://q.example]].\n| ::\n::\n\n\
+::table\n| *cell* [@src] |\n::\n\n\
+Text[^1] with \\(x\\) and `code`.\n\n\
+::footnotes\n[^1]: Note with [[n|https://n.example]].\n::\n\n\
+::sources\n- src\n::\n";
+
+/// Counts every visited block and inline by variant name.
+#[derive(Default)]
+struct Census(BTreeMap<String, usize>);
+
+impl<'ast> Visit<'ast> for Census {
+    fn visit_block(&mut self, block: &'ast Block<'ast>) {
+        let name = format!("{block:?}");
+        *self.0.entry(variant(&name)).or_default() += 1;
+        walk_block(self, block);
+    }
+
+    fn visit_inline(&mut self, inline: &'ast Inline<'ast>) {
+        let name = format!("{inline:?}");
+        *self.0.entry(variant(&name)).or_default() += 1;
+        walk_inline(self, inline);
+    }
+}
+
+fn variant(debug: &str) -> String {
+    debug.split('(').next().unwrap().to_string()
+}
+
+// ============================================================================
+// Visit Tests
+// ============================================================================
+
+#[test]
+fn test_visit_reaches_nested_nodes() {
+    let doc = Parser::new(Profile::Litedoc).parse(NESTED).unwrap();
+    let mut census = Census::default();
+    census.visit_document(&doc);
+    let counts = census.0;
+
+    // Paragraphs inside list items, quotes and footnote definitions.
+    assert_eq!(counts["Paragraph"], 4);
+    assert_eq!(counts["Quote"], 1);
+    assert_eq!(counts["Link"], 3);
+    assert_eq!(counts["Mention"], 1);
+    assert_eq!(counts["Citation"], 1);
+    assert_eq!(counts["Emphasis"], 1);
+    assert_eq!(counts["Math"], 1);
+    assert_eq!(counts["CodeSpan"], 1);
+    assert_eq!(counts["FootnoteRef"], 1);
+}
+
+#[test]
+fn test_visit_collects_references() {
+    #[derive(Default)]
+    struct Urls<'ast>(Vec<&'ast str>);
+
+    impl<'ast> Visit<'ast> for Urls<'ast> {
+        fn visit_link(&mut self, link: &'ast Link<'ast>) {
+            self.0.push(&link.url);
+            walk_link(self, link);
+        }
+    }
+
+    let doc = Parser::new(Profile::Litedoc).parse(NESTED).unwrap();
+    let mut urls = Urls::default();
+    urls.visit_document(&doc);
+    assert_eq!(
+        urls.0,
+        [
+            "#title-with-a-link",
+            "https://q.example",
+            "https://n.example"
+        ]
+    );
+}
+
+// ============================================================================
+// VisitMut Tests
+// ============================================================================
+
+#[test]
+fn test_visit_mut_rewrites_links() {
+    struct Proxy;
+
+    impl<'a> VisitMut<'a> for Proxy {
+        fn visit_link_mut(&mut self, link: &mut Link<'a>) {
+            if let Some(rest) = link.url.strip_prefix("https://") {
+                link.url = format!("https://proxy.example/{rest}").into();
+            }
+            walk_link_mut(self, link);
+        }
+
+        fn visit_text_mut(&mut self, text: &mut Text<'a>) {
+            text.content = text.content.to_uppercase().into();
+        }
+    }
+
+    let mut doc = Parser::new(Profile::Litedoc).parse(NESTED).unwrap();
+    Proxy.visit_document_mut(&mut doc);
+    let options = FormatOptions {
+        emit_directives: false,
+        ..FormatOptions::default()
+    };
+    let out = format_document(&doc, &options);
+    assert!(out.contains("| QUOTED [[Q|https://proxy.example/q.example]].\n"));
+    assert!(out.contains("[^1]: NOTE WITH [[N|https://proxy.example/n.example]].\n"));
+    assert!(out.contains("[[A LINK|#title-with-a-link]]"));
+}
+
+#[test]
+fn test_visit_mut_replaces_inlines() {
+    // Replace soft breaks with spaces by rewriting whole inline nodes.
+    struct Unwrap;
+
+    impl<'a> VisitMut<'a> for Unwrap {
+        fn visit_inline_mut(&mut self, inline: &mut Inline<'a>) {
+            if let Inline::SoftBreak(span) = *inline {
+                *inline = Inline::Text(Text {
+                    content: " ".into(),
+                    span,
+                });
+            }
+            walk_inline_mut(self, inline);
+        }
+    }
+
+    let mut doc = Parser::new(Profile::Litedoc)
+        .parse("one\ntwo *three\nfour*")
+        .unwrap();
+    Unwrap.visit_document_mut(&mut doc);
+    let out = format_document(
+        &doc,
+        &FormatOptions {
+            emit_directives: false,
+            ..FormatOptions::default()
+        },
+    );
+    assert_eq!(out, "one two *three four*\n");
+}
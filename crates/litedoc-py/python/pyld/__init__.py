@@ -16,6 +16,7 @@ from pyld.pyld import (
     Profile,
     ModuleKind,
     Span,
+    LineCol,
     # Document
     Document,
     ParseResult,
@@ -33,14 +34,18 @@ from pyld.pyld import (
     Quote,
     Figure,
     Table,
+    Alignment,
     TableRow,
     TableCell,
     Footnotes,
     FootnoteDef,
+    Sources,
+    Source,
     MathBlock,
     ThematicBreak,
     HtmlBlock,
     RawBlock,
+    CustomBlock,
     # Inlines
     Text,
     Emphasis,
@@ -48,8 +53,12 @@ from pyld.pyld import (
     Strikethrough,
     CodeSpan,
     Link,
+    LinkKind,
     AutoLink,
     FootnoteRef,
+    Citation,
+    Mention,
+    InlineMath,
     HardBreak,
     SoftBreak,
 )
@@ -62,6 +71,7 @@ __all__ = [
     "Profile",
     "ModuleKind",
     "Span",
+    "LineCol",
     # Document
     "Document",
     "ParseResult",
@@ -79,14 +89,18 @@ __all__ = [
     "Quote",
     "Figure",
     "Table",
+    "Alignment",
     "TableRow",
     "TableCell",
     "Footnotes",
     "FootnoteDef",
+    "Sources",
+    "Source",
     "MathBlock",
     "ThematicBreak",
     "HtmlBlock",
     "RawBlock",
+    "CustomBlock",
     # Inlines
     "Text",
     "Emphasis",
@@ -94,8 +108,12 @@ __all__ = [
     "Strikethrough",
     "CodeSpan",
     "Link",
+    "LinkKind",
     "AutoLink",
     "FootnoteRef",
+    "Citation",
+    "Mention",
+    "InlineMath",
     "HardBreak",
     "SoftBreak",
 ]
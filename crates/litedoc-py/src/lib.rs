@@ -1,9 +1,13 @@
 //! Python bindings for LiteDoc parser.
 
 use litedoc_core::{
-    ast::{AttrValue, Block, Document, Inline, Metadata, Module},
+    ast::{
+        Alignment, AttrValue, Block, CowStr, CustomBody, Document, FootnoteDef, Inline, LinkKind,
+        ListItem, Metadata, Module, Source, TableCell, TableRow,
+    },
     error::{ParseError as CoreParseError, ParseErrorKind as CoreParseErrorKind},
-    span::Span as CoreSpan,
+    query::{Node, Selector},
+    span::{LineCol, LineIndex, Span as CoreSpan},
     ParseResult as CoreParseResult, Parser as CoreParser, Profile as CoreProfile,
 };
 use pyo3::prelude::*;
@@ -43,6 +47,37 @@ impl From<CoreSpan> for PySpan {
     }
 }
 
+/// Line and column of a position in the input, all counted from 1.
+///
+/// `char_column` counts characters, as Python string indices do;
+/// `utf16_column` counts UTF-16 code units, as JavaScript and LSP do.
+#[pyclass(frozen, get_all, name = "LineCol")]
+#[derive(Clone)]
+pub struct PyLineCol {
+    pub line: u32,
+    pub byte_column: u32,
+    pub char_column: u32,
+    pub utf16_column: u32,
+}
+
+#[pymethods]
+impl PyLineCol {
+    fn __repr__(&self) -> String {
+        format!("LineCol(line={}, column={})", self.line, self.char_column)
+    }
+}
+
+impl From<LineCol> for PyLineCol {
+    fn from(p: LineCol) -> Self {
+        PyLineCol {
+            line: p.line,
+            byte_column: p.byte_column,
+            char_column: p.char_column,
+            utf16_column: p.utf16_column,
+        }
+    }
+}
+
 // ============================================================================
 // Enums
 // ============================================================================
@@ -87,6 +122,7 @@ pub enum PyModuleKind {
     Strikethrough,
     Autolink,
     Html,
+    Mentions,
 }
 
 impl From<Module> for PyModuleKind {
@@ -99,6 +135,7 @@ impl From<Module> for PyModuleKind {
             Module::Strikethrough => PyModuleKind::Strikethrough,
             Module::Autolink => PyModuleKind::Autolink,
             Module::Html => PyModuleKind::Html,
+            Module::Mentions => PyModuleKind::Mentions,
         }
     }
 }
@@ -112,6 +149,9 @@ pub enum PyParseErrorKind {
     InvalidSyntax,
     UnknownDirective,
     InvalidMetadata,
+    DisabledModule,
+    UnresolvedReference,
+    UnusedDefinition,
     Other,
 }
 
@@ -123,6 +163,9 @@ impl From<CoreParseErrorKind> for PyParseErrorKind {
             CoreParseErrorKind::InvalidSyntax => PyParseErrorKind::InvalidSyntax,
             CoreParseErrorKind::UnknownDirective => PyParseErrorKind::UnknownDirective,
             CoreParseErrorKind::InvalidMetadata => PyParseErrorKind::InvalidMetadata,
+            CoreParseErrorKind::DisabledModule => PyParseErrorKind::DisabledModule,
+            CoreParseErrorKind::UnresolvedReference => PyParseErrorKind::UnresolvedReference,
+            CoreParseErrorKind::UnusedDefinition => PyParseErrorKind::UnusedDefinition,
             CoreParseErrorKind::Other => PyParseErrorKind::Other,
         }
     }
@@ -134,6 +177,8 @@ impl From<CoreParseErrorKind> for PyParseErrorKind {
 pub struct PyParseError {
     pub message: String,
     pub span: Option<PySpan>,
+    pub start: Option<PyLineCol>,
+    pub end: Option<PyLineCol>,
     pub kind: PyParseErrorKind,
     pub recoverable: bool,
 }
@@ -145,18 +190,25 @@ impl PyParseError {
     }
 
     fn __str__(&self) -> String {
-        match &self.span {
-            Some(s) => format!("{} at bytes {}..{}", self.message, s.start, s.end),
+        match &self.start {
+            Some(p) => format!(
+                "{} at line {}, column {}",
+                self.message, p.line, p.char_column
+            ),
             None => self.message.clone(),
         }
     }
 }
 
-impl From<CoreParseError> for PyParseError {
-    fn from(e: CoreParseError) -> Self {
+impl PyParseError {
+    /// Convert an error, locating its span in the input `index` was built from.
+    fn new(e: CoreParseError, index: &LineIndex<'_>) -> Self {
+        let range = e.span.map(|s| index.range(s));
         PyParseError {
             message: e.message,
             span: e.span.map(PySpan::from),
+            start: range.map(|(start, _)| start.into()),
+            end: range.map(|(_, end)| end.into()),
             kind: e.kind.into(),
             recoverable: e.recoverable,
         }
@@ -171,6 +223,7 @@ impl From<CoreParseError> for PyParseError {
 #[pyclass(frozen, get_all, name = "Heading")]
 pub struct PyHeading {
     pub level: u8,
+    pub id: Option<String>,
     pub content: PyObject,
     pub span: PySpan,
 }
@@ -190,9 +243,20 @@ pub enum PyListKind {
     Unordered,
 }
 
+/// Link destination kind.
+#[pyclass(frozen, eq, eq_int, name = "LinkKind")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PyLinkKind {
+    Absolute,
+    Relative,
+    Fragment,
+    Email,
+}
+
 /// List item.
 #[pyclass(frozen, get_all, name = "ListItem")]
 pub struct PyListItem {
+    pub checked: Option<bool>,
     pub blocks: PyObject,
     pub span: PySpan,
 }
@@ -202,6 +266,7 @@ pub struct PyListItem {
 pub struct PyList_ {
     pub kind: PyListKind,
     pub start: Option<u64>,
+    pub attrs: PyObject,
     pub items: PyObject,
     pub span: PySpan,
 }
@@ -210,6 +275,7 @@ pub struct PyList_ {
 #[pyclass(frozen, get_all, name = "CodeBlock")]
 pub struct PyCodeBlock {
     pub lang: String,
+    pub attrs: PyObject,
     pub content: String,
     pub span: PySpan,
 }
@@ -219,6 +285,7 @@ pub struct PyCodeBlock {
 pub struct PyCallout {
     pub kind: String,
     pub title: Option<String>,
+    pub attrs: PyObject,
     pub blocks: PyObject,
     pub span: PySpan,
 }
@@ -226,6 +293,7 @@ pub struct PyCallout {
 /// Block quote.
 #[pyclass(frozen, get_all, name = "Quote")]
 pub struct PyQuote {
+    pub attrs: PyObject,
     pub blocks: PyObject,
     pub span: PySpan,
 }
@@ -236,9 +304,31 @@ pub struct PyFigure {
     pub src: String,
     pub alt: String,
     pub caption: Option<String>,
+    pub attrs: PyObject,
     pub span: PySpan,
 }
 
+/// Table column alignment.
+#[pyclass(frozen, eq, eq_int, name = "Alignment")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PyAlignment {
+    None,
+    Left,
+    Center,
+    Right,
+}
+
+impl From<Alignment> for PyAlignment {
+    fn from(a: Alignment) -> Self {
+        match a {
+            Alignment::None => PyAlignment::None,
+            Alignment::Left => PyAlignment::Left,
+            Alignment::Center => PyAlignment::Center,
+            Alignment::Right => PyAlignment::Right,
+        }
+    }
+}
+
 /// Table cell.
 #[pyclass(frozen, get_all, name = "TableCell")]
 pub struct PyTableCell {
@@ -257,6 +347,8 @@ pub struct PyTableRow {
 /// Data table.
 #[pyclass(frozen, get_all, name = "Table")]
 pub struct PyTable {
+    pub alignments: Vec<PyAlignment>,
+    pub attrs: PyObject,
     pub rows: PyObject,
     pub span: PySpan,
 }
@@ -276,10 +368,29 @@ pub struct PyFootnotes {
     pub span: PySpan,
 }
 
+/// Source entry.
+#[pyclass(frozen, get_all, name = "Source")]
+pub struct PySource {
+    pub id: String,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub attrs: PyObject,
+    pub span: PySpan,
+}
+
+/// Sources block.
+#[pyclass(frozen, get_all, name = "Sources")]
+pub struct PySources {
+    pub attrs: PyObject,
+    pub entries: PyObject,
+    pub span: PySpan,
+}
+
 /// Math block.
 #[pyclass(frozen, get_all, name = "MathBlock")]
 pub struct PyMathBlock {
     pub display: bool,
+    pub attrs: PyObject,
     pub content: String,
     pub span: PySpan,
 }
@@ -300,10 +411,22 @@ pub struct PyHtmlBlock {
 /// Unparsed block (error recovery).
 #[pyclass(frozen, get_all, name = "RawBlock")]
 pub struct PyRawBlock {
+    pub name: String,
+    pub attrs: PyObject,
     pub content: String,
     pub span: PySpan,
 }
 
+/// User-defined block. Exactly one of `blocks` and `content` is set.
+#[pyclass(frozen, get_all, name = "CustomBlock")]
+pub struct PyCustomBlock {
+    pub name: String,
+    pub attrs: PyObject,
+    pub blocks: Option<PyObject>,
+    pub content: Option<String>,
+    pub span: PySpan,
+}
+
 // ============================================================================
 // Inline types
 // ============================================================================
@@ -348,6 +471,7 @@ pub struct PyCodeSpan {
 pub struct PyLink {
     pub label: PyObject,
     pub url: String,
+    pub kind: PyLinkKind,
     pub title: Option<String>,
     pub span: PySpan,
 }
@@ -366,6 +490,28 @@ pub struct PyFootnoteRef {
     pub span: PySpan,
 }
 
+/// Citation of a source.
+#[pyclass(frozen, get_all, name = "Citation")]
+pub struct PyCitation {
+    pub key: String,
+    pub span: PySpan,
+}
+
+/// Mention of an agent, task, tool or other entity.
+#[pyclass(frozen, get_all, name = "Mention")]
+pub struct PyMention {
+    pub kind: String,
+    pub id: String,
+    pub span: PySpan,
+}
+
+/// Inline math.
+#[pyclass(frozen, get_all, name = "InlineMath")]
+pub struct PyInlineMath {
+    pub content: String,
+    pub span: PySpan,
+}
+
 /// Hard line break.
 #[pyclass(frozen, get_all, name = "HardBreak")]
 pub struct PyHardBreak {
@@ -442,6 +588,12 @@ fn convert_inline(py: Python<'_>, inline: Inline) -> PyObject {
             PyLink {
                 label: convert_inlines(py, l.label),
                 url: l.url.into_owned(),
+                kind: match l.kind {
+                    LinkKind::Absolute => PyLinkKind::Absolute,
+                    LinkKind::Relative => PyLinkKind::Relative,
+                    LinkKind::Fragment => PyLinkKind::Fragment,
+                    LinkKind::Email => PyLinkKind::Email,
+                },
                 title: l.title.map(|t| t.into_owned()),
                 span: l.span.into(),
             },
@@ -466,6 +618,34 @@ fn convert_inline(py: Python<'_>, inline: Inline) -> PyObject {
         )
         .unwrap()
         .into_any(),
+        Inline::Citation(c) => Py::new(
+            py,
+            PyCitation {
+                key: c.key.into_owned(),
+                span: c.span.into(),
+            },
+        )
+        .unwrap()
+        .into_any(),
+        Inline::Mention(m) => Py::new(
+            py,
+            PyMention {
+                kind: m.kind.into_owned(),
+                id: m.id.into_owned(),
+                span: m.span.into(),
+            },
+        )
+        .unwrap()
+        .into_any(),
+        Inline::Math(m) => Py::new(
+            py,
+            PyInlineMath {
+                content: m.content.into_owned(),
+                span: m.span.into(),
+            },
+        )
+        .unwrap()
+        .into_any(),
         Inline::HardBreak(span) => Py::new(py, PyHardBreak { span: span.into() })
             .unwrap()
             .into_any(),
@@ -490,6 +670,7 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
             py,
             PyHeading {
                 level: h.level,
+                id: h.id.map(|id| id.into_owned()),
                 content: convert_inlines(py, h.content),
                 span: h.span.into(),
             },
@@ -508,15 +689,7 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
         Block::List(l) => {
             let items = PyList::empty(py);
             for item in l.items {
-                let li = Py::new(
-                    py,
-                    PyListItem {
-                        blocks: convert_blocks(py, item.blocks),
-                        span: item.span.into(),
-                    },
-                )
-                .unwrap();
-                items.append(li).unwrap();
+                items.append(convert_list_item(py, item)).unwrap();
             }
             Py::new(
                 py,
@@ -526,6 +699,7 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
                         CoreListKind::Unordered => PyListKind::Unordered,
                     },
                     start: l.start,
+                    attrs: convert_attrs(py, l.attrs),
                     items: items.into(),
                     span: l.span.into(),
                 },
@@ -537,6 +711,7 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
             py,
             PyCodeBlock {
                 lang: c.lang.into_owned(),
+                attrs: convert_attrs(py, c.attrs),
                 content: c.content.into_owned(),
                 span: c.span.into(),
             },
@@ -548,6 +723,7 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
             PyCallout {
                 kind: c.kind.into_owned(),
                 title: c.title.map(|t| t.into_owned()),
+                attrs: convert_attrs(py, c.attrs),
                 blocks: convert_blocks(py, c.blocks),
                 span: c.span.into(),
             },
@@ -557,6 +733,7 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
         Block::Quote(q) => Py::new(
             py,
             PyQuote {
+                attrs: convert_attrs(py, q.attrs),
                 blocks: convert_blocks(py, q.blocks),
                 span: q.span.into(),
             },
@@ -569,6 +746,7 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
                 src: f.src.into_owned(),
                 alt: f.alt.into_owned(),
                 caption: f.caption.map(|c| c.into_owned()),
+                attrs: convert_attrs(py, f.attrs),
                 span: f.span.into(),
             },
         )
@@ -577,32 +755,13 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
         Block::Table(t) => {
             let rows = PyList::empty(py);
             for row in t.rows {
-                let cells = PyList::empty(py);
-                for cell in row.cells {
-                    let tc = Py::new(
-                        py,
-                        PyTableCell {
-                            content: convert_inlines(py, cell.content),
-                            span: cell.span.into(),
-                        },
-                    )
-                    .unwrap();
-                    cells.append(tc).unwrap();
-                }
-                let tr = Py::new(
-                    py,
-                    PyTableRow {
-                        cells: cells.into(),
-                        header: row.header,
-                        span: row.span.into(),
-                    },
-                )
-                .unwrap();
-                rows.append(tr).unwrap();
+                rows.append(convert_table_row(py, row)).unwrap();
             }
             Py::new(
                 py,
                 PyTable {
+                    alignments: t.alignments.into_iter().map(PyAlignment::from).collect(),
+                    attrs: convert_attrs(py, t.attrs),
                     rows: rows.into(),
                     span: t.span.into(),
                 },
@@ -613,16 +772,7 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
         Block::Footnotes(f) => {
             let defs = PyList::empty(py);
             for def in f.defs {
-                let fd = Py::new(
-                    py,
-                    PyFootnoteDef {
-                        label: def.label.into_owned(),
-                        blocks: convert_blocks(py, def.blocks),
-                        span: def.span.into(),
-                    },
-                )
-                .unwrap();
-                defs.append(fd).unwrap();
+                defs.append(convert_footnote_def(py, def)).unwrap();
             }
             Py::new(
                 py,
@@ -634,10 +784,27 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
             .unwrap()
             .into_any()
         }
+        Block::Sources(s) => {
+            let entries = PyList::empty(py);
+            for entry in s.entries {
+                entries.append(convert_source(py, entry)).unwrap();
+            }
+            Py::new(
+                py,
+                PySources {
+                    attrs: convert_attrs(py, s.attrs),
+                    entries: entries.into(),
+                    span: s.span.into(),
+                },
+            )
+            .unwrap()
+            .into_any()
+        }
         Block::Math(m) => Py::new(
             py,
             PyMathBlock {
                 display: m.display,
+                attrs: convert_attrs(py, m.attrs),
                 content: m.content.into_owned(),
                 span: m.span.into(),
             },
@@ -659,12 +826,110 @@ fn convert_block(py: Python<'_>, block: Block) -> PyObject {
         Block::Raw(r) => Py::new(
             py,
             PyRawBlock {
+                name: r.name.into_owned(),
+                attrs: convert_attrs(py, r.attrs),
                 content: r.content.into_owned(),
                 span: r.span.into(),
             },
         )
         .unwrap()
         .into_any(),
+        Block::Custom(c) => {
+            let (blocks, content) = match c.body {
+                CustomBody::Blocks(blocks) => (Some(convert_blocks(py, blocks)), None),
+                CustomBody::Content(content) => (None, Some(content.into_owned())),
+            };
+            Py::new(
+                py,
+                PyCustomBlock {
+                    name: c.name.into_owned(),
+                    attrs: convert_attrs(py, c.attrs),
+                    blocks,
+                    content,
+                    span: c.span.into(),
+                },
+            )
+            .unwrap()
+            .into_any()
+        }
+    }
+}
+
+fn convert_list_item(py: Python<'_>, item: ListItem) -> Py<PyListItem> {
+    Py::new(
+        py,
+        PyListItem {
+            checked: item.checked,
+            blocks: convert_blocks(py, item.blocks),
+            span: item.span.into(),
+        },
+    )
+    .unwrap()
+}
+
+fn convert_table_row(py: Python<'_>, row: TableRow) -> Py<PyTableRow> {
+    let cells = PyList::empty(py);
+    for cell in row.cells {
+        cells.append(convert_table_cell(py, cell)).unwrap();
+    }
+    Py::new(
+        py,
+        PyTableRow {
+            cells: cells.into(),
+            header: row.header,
+            span: row.span.into(),
+        },
+    )
+    .unwrap()
+}
+
+fn convert_table_cell(py: Python<'_>, cell: TableCell) -> Py<PyTableCell> {
+    Py::new(
+        py,
+        PyTableCell {
+            content: convert_inlines(py, cell.content),
+            span: cell.span.into(),
+        },
+    )
+    .unwrap()
+}
+
+fn convert_footnote_def(py: Python<'_>, def: FootnoteDef) -> Py<PyFootnoteDef> {
+    Py::new(
+        py,
+        PyFootnoteDef {
+            label: def.label.into_owned(),
+            blocks: convert_blocks(py, def.blocks),
+            span: def.span.into(),
+        },
+    )
+    .unwrap()
+}
+
+fn convert_source(py: Python<'_>, source: Source) -> Py<PySource> {
+    Py::new(
+        py,
+        PySource {
+            id: source.id.into_owned(),
+            title: source.title.map(|t| t.into_owned()),
+            url: source.url.map(|u| u.into_owned()),
+            attrs: convert_attrs(py, source.attrs),
+            span: source.span.into(),
+        },
+    )
+    .unwrap()
+}
+
+/// Convert a copy of a selected node.
+fn convert_node(py: Python<'_>, node: Node) -> PyObject {
+    match node {
+        Node::Block(block) => convert_block(py, block.clone()),
+        Node::ListItem(item) => convert_list_item(py, item.clone()).into_any(),
+        Node::TableRow(row) => convert_table_row(py, row.clone()).into_any(),
+        Node::TableCell(cell) => convert_table_cell(py, cell.clone()).into_any(),
+        Node::FootnoteDef(def) => convert_footnote_def(py, def.clone()).into_any(),
+        Node::Source(source) => convert_source(py, source.clone()).into_any(),
+        Node::Inline(inline) => convert_inline(py, inline.clone()),
     }
 }
 
@@ -684,6 +949,15 @@ fn convert_attr_value(py: Python<'_>, v: AttrValue) -> PyObject {
     }
 }
 
+fn convert_attrs(py: Python<'_>, attrs: Vec<(CowStr, AttrValue)>) -> PyObject {
+    let dict = PyDict::new(py);
+    for (k, v) in attrs {
+        dict.set_item(k.into_owned(), convert_attr_value(py, v))
+            .unwrap();
+    }
+    dict.into()
+}
+
 // ============================================================================
 // PyDocument
 // ============================================================================
@@ -701,10 +975,46 @@ pub struct PyDocument {
     pub blocks: PyObject,
     #[pyo3(get)]
     pub span: PySpan,
+    mentions: PyObject,
+    /// Source text and the parser that read it, parsed again by `select`.
+    source: String,
+    parser: PyParser,
 }
 
 #[pymethods]
 impl PyDocument {
+    /// All mentions in the document, in source order.
+    fn mentions(&self, py: Python<'_>) -> PyObject {
+        self.mentions.clone_ref(py)
+    }
+
+    /// Nodes matching a selector, in document order.
+    ///
+    /// Selectors are CSS-like, e.g. `callout[type=warning]`,
+    /// `heading[level=2]:contains("Findings") + table` or `list > item`.
+    /// Each call parses the document again and returns new node objects,
+    /// equal in content to those in `blocks`; match them up by `span`.
+    ///
+    /// Raises:
+    ///     ValueError: If the selector is malformed
+    #[pyo3(text_signature = "(self, selector)")]
+    fn select(&self, py: Python<'_>, selector: &str) -> PyResult<PyObject> {
+        let selector = Selector::parse(selector)
+            .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?;
+        // The same text and settings always give the tree behind `blocks`.
+        let tree = self
+            .parser
+            .core_parser()
+            .parse_with_recovery(&self.source)
+            .document;
+        let nodes = selector.select(&tree);
+        let list = PyList::empty(py);
+        for node in nodes {
+            list.append(convert_node(py, node)).unwrap();
+        }
+        Ok(list.into())
+    }
+
     fn __repr__(&self, py: Python<'_>) -> String {
         let blocks: &Bound<'_, PyList> = self.blocks.downcast_bound(py).unwrap();
         format!(
@@ -721,15 +1031,16 @@ impl PyDocument {
     }
 }
 
-fn convert_document(py: Python<'_>, doc: Document) -> PyDocument {
-    let metadata = doc.metadata.map(|Metadata { entries, span: _ }| {
-        let dict = PyDict::new(py);
-        for (k, v) in entries {
-            dict.set_item(k.into_owned(), convert_attr_value(py, v))
-                .unwrap();
-        }
-        dict.into()
-    });
+fn convert_document(py: Python<'_>, doc: Document, source: &str, parser: &PyParser) -> PyDocument {
+    let mentions = doc
+        .mentions()
+        .into_iter()
+        .map(|m| Inline::Mention(m.clone()))
+        .collect();
+    let mentions = convert_inlines(py, mentions);
+    let metadata = doc
+        .metadata
+        .map(|Metadata { entries, span: _ }| convert_attrs(py, entries));
 
     PyDocument {
         profile: doc.profile.into(),
@@ -737,6 +1048,9 @@ fn convert_document(py: Python<'_>, doc: Document) -> PyDocument {
         metadata,
         blocks: convert_blocks(py, doc.blocks),
         span: doc.span.into(),
+        mentions,
+        source: source.to_owned(),
+        parser: parser.clone(),
     }
 }
 
@@ -780,39 +1094,61 @@ impl PyParseResult {
 ///
 /// Args:
 ///     profile: Profile.Litedoc (default), Profile.Md, or Profile.MdStrict
+///     mention_kinds: Entity kinds accepted in `<@kind:id>` mentions
+///         (default: agent, task, tool)
+///     check_references: Report internal links and citations that do not
+///         resolve, and sources never cited, as errors (default: False)
 #[pyclass(name = "Parser")]
+#[derive(Clone)]
 pub struct PyParser {
     profile: CoreProfile,
+    mention_kinds: Option<Vec<String>>,
+    check_references: bool,
 }
 
 #[pymethods]
 impl PyParser {
     #[new]
-    #[pyo3(signature = (profile=None), text_signature = "(profile=None)")]
-    fn new(profile: Option<PyProfile>) -> Self {
+    #[pyo3(
+        signature = (profile=None, mention_kinds=None, check_references=false),
+        text_signature = "(profile=None, mention_kinds=None, check_references=False)"
+    )]
+    fn new(
+        profile: Option<PyProfile>,
+        mention_kinds: Option<Vec<String>>,
+        check_references: bool,
+    ) -> Self {
         PyParser {
             profile: profile.unwrap_or(PyProfile::Litedoc).into(),
+            mention_kinds,
+            check_references,
         }
     }
 
     /// Parse a LiteDoc string. Raises ValueError on error.
     #[pyo3(text_signature = "(self, input)")]
     fn parse(&self, py: Python<'_>, input: &str) -> PyResult<PyDocument> {
-        let mut parser = CoreParser::new(self.profile);
+        let mut parser = self.core_parser();
         match parser.parse(input) {
-            Ok(doc) => Ok(convert_document(py, doc)),
-            Err(e) => Err(pyo3::exceptions::PyValueError::new_err(e.to_string())),
+            Ok(doc) => Ok(convert_document(py, doc, input, self)),
+            Err(e) => Err(pyo3::exceptions::PyValueError::new_err(
+                e.describe(&LineIndex::new(input)),
+            )),
         }
     }
 
     /// Parse with error recovery. Always returns a result.
     #[pyo3(text_signature = "(self, input)")]
     fn parse_with_recovery(&self, py: Python<'_>, input: &str) -> PyParseResult {
-        let mut parser = CoreParser::new(self.profile);
+        let mut parser = self.core_parser();
         let CoreParseResult { document, errors } = parser.parse_with_recovery(input);
+        let index = LineIndex::new(input);
         PyParseResult {
-            document: Py::new(py, convert_document(py, document)).unwrap(),
-            errors: errors.into_iter().map(PyParseError::from).collect(),
+            document: Py::new(py, convert_document(py, document, input, self)).unwrap(),
+            errors: errors
+                .into_iter()
+                .map(|e| PyParseError::new(e, &index))
+                .collect(),
         }
     }
 
@@ -821,6 +1157,16 @@ impl PyParser {
     }
 }
 
+impl PyParser {
+    fn core_parser(&self) -> CoreParser {
+        let parser = CoreParser::new(self.profile).with_reference_checks(self.check_references);
+        match &self.mention_kinds {
+            Some(kinds) => parser.with_mention_kinds(kinds.iter().cloned()),
+            None => parser,
+        }
+    }
+}
+
 // ============================================================================
 // Module functions
 // ============================================================================
@@ -839,7 +1185,7 @@ impl PyParser {
 #[pyfunction]
 #[pyo3(signature = (input, profile=None), text_signature = "(input, profile=None)")]
 fn parse(py: Python<'_>, input: &str, profile: Option<PyProfile>) -> PyResult<PyDocument> {
-    let p = PyParser::new(profile);
+    let p = PyParser::new(profile, None, false);
     p.parse(py, input)
 }
 
@@ -854,7 +1200,7 @@ fn parse(py: Python<'_>, input: &str, profile: Option<PyProfile>) -> PyResult<Py
 #[pyfunction]
 #[pyo3(signature = (input, profile=None), text_signature = "(input, profile=None)")]
 fn parse_with_recovery(py: Python<'_>, input: &str, profile: Option<PyProfile>) -> PyParseResult {
-    let p = PyParser::new(profile);
+    let p = PyParser::new(profile, None, false);
     p.parse_with_recovery(py, input)
 }
 
@@ -866,6 +1212,7 @@ fn parse_with_recovery(py: Python<'_>, input: &str, profile: Option<PyProfile>)
 #[pymodule]
 fn pyld(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PySpan>()?;
+    m.add_class::<PyLineCol>()?;
     m.add_class::<PyProfile>()?;
     m.add_class::<PyModuleKind>()?;
     m.add_class::<PyParser>()?;
@@ -883,22 +1230,30 @@ fn pyld(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_class::<PyQuote>()?;
     m.add_class::<PyFigure>()?;
     m.add_class::<PyTable>()?;
+    m.add_class::<PyAlignment>()?;
     m.add_class::<PyTableRow>()?;
     m.add_class::<PyTableCell>()?;
     m.add_class::<PyFootnotes>()?;
     m.add_class::<PyFootnoteDef>()?;
+    m.add_class::<PySources>()?;
+    m.add_class::<PySource>()?;
     m.add_class::<PyMathBlock>()?;
     m.add_class::<PyThematicBreak>()?;
     m.add_class::<PyHtmlBlock>()?;
     m.add_class::<PyRawBlock>()?;
+    m.add_class::<PyCustomBlock>()?;
     m.add_class::<PyText>()?;
     m.add_class::<PyEmphasis>()?;
     m.add_class::<PyStrong>()?;
     m.add_class::<PyStrikethrough>()?;
     m.add_class::<PyCodeSpan>()?;
     m.add_class::<PyLink>()?;
+    m.add_class::<PyLinkKind>()?;
     m.add_class::<PyAutoLink>()?;
     m.add_class::<PyFootnoteRef>()?;
+    m.add_class::<PyCitation>()?;
+    m.add_class::<PyMention>()?;
+    m.add_class::<PyInlineMath>()?;
     m.add_class::<PyHardBreak>()?;
     m.add_class::<PySoftBreak>()?;
     m.add_function(wrap_pyfunction!(parse, m)?)?;
@@ -69,6 +69,13 @@ def test_list_block():
     assert len(lst.items) == 3
 
 
+def test_task_list_items():
+    """Test task list checkbox state on list items."""
+    doc = pyld.parse("@modules tasks\n\n::list\n- [ ] todo\n- [x] done\n- plain\n::")
+    lst = doc.blocks[0]
+    assert [item.checked for item in lst.items] == [False, True, None]
+
+
 def test_code_block():
     """Test parsing a code block."""
     doc = pyld.parse("```python\nprint('hello')\n```")
@@ -79,6 +86,15 @@ def test_code_block():
     assert "print" in code.content
 
 
+def test_code_block_attrs():
+    """Test info-string attributes on a code block."""
+    doc = pyld.parse('````python title="x.py" lines=3-5 linenos\n```\n````')
+    code = doc.blocks[0]
+    assert code.lang == "python"
+    assert code.attrs == {"title": "x.py", "lines": "3-5", "linenos": True}
+    assert code.content == "```"
+
+
 def test_inline_formatting():
     """Test inline formatting parsing."""
     doc = pyld.parse("This is *emphasis* and **strong**.")
@@ -136,6 +152,33 @@ def test_parse_error_on_invalid():
         pass  # Expected
 
 
+def test_parse_error_positions():
+    """Test line and column positions on parse errors."""
+    text = "# Café 🦀\n\n::table\n| a | b |\n| --- | --- |\n| 1 |\n::\n\n[[x|#café-🦀-nope]]"
+    result = pyld.Parser(check_references=True).parse_with_recovery(text)
+    assert len(result.errors) == 2
+
+    row = result.errors[0]
+    assert (row.start.line, row.start.char_column) == (6, 1)
+    assert row.end.line == 6
+    assert str(row).endswith("at line 6, column 1")
+
+    link = result.errors[1]
+    line = text.splitlines()[link.start.line - 1]
+    assert line[link.start.char_column - 1 :].startswith("[[x|")
+    assert link.start.byte_column == link.start.char_column
+    assert link.end.char_column == len(line) + 1
+    assert link.end.utf16_column == len(line.encode("utf-16-le")) // 2 + 1
+    assert link.end.byte_column == len(line.encode()) + 1
+    assert isinstance(link.start, pyld.LineCol)
+
+    try:
+        pyld.parse("::table\n| a |\n| --- |\n| 1 | 2 |\n::")
+        assert False, "expected ValueError"
+    except ValueError as e:
+        assert "at line 4, column 1" in str(e)
+
+
 def test_callout():
     """Test callout block parsing."""
     doc = pyld.parse("::callout type=note\nThis is a note.\n::")
@@ -145,6 +188,14 @@ def test_callout():
     assert callout.kind == "note"
 
 
+def test_block_attrs():
+    """Test that fenced block openers keep all attributes."""
+    doc = pyld.parse("::callout type=warning id=w1 severity=high\nText\n::")
+    callout = doc.blocks[0]
+    assert callout.kind == "warning"
+    assert callout.attrs == {"type": "warning", "id": "w1", "severity": "high"}
+
+
 def test_quote():
     """Test quote block parsing."""
     doc = pyld.parse("::quote\nQuoted text.\n::")
@@ -192,6 +243,155 @@ def test_code_span():
     assert has_code
 
 
+def test_multi_backtick_code_span():
+    """Test that a longer fence lets a code span contain backticks."""
+    doc = pyld.parse("Run `` echo `date` `` now")
+    code = doc.blocks[0].content[1]
+    assert isinstance(code, pyld.CodeSpan)
+    assert code.content == "echo `date`"
+
+
+def test_inline_math():
+    """Test inline math with the math module enabled."""
+    doc = pyld.parse("@modules math\n\nLet \\(a*b*c\\) hold.")
+    math = doc.blocks[0].content[1]
+    assert isinstance(math, pyld.InlineMath)
+    assert math.content == "a*b*c"
+    assert math.span.start == 19
+
+
+def test_escapes():
+    """Test that backslash escapes are removed from text."""
+    doc = pyld.parse("\\*not emphasis\\* C:\\path")
+    text = doc.blocks[0].content[0]
+    assert isinstance(text, pyld.Text)
+    assert text.content == "*not emphasis* C:\\path"
+
+
+def test_line_breaks():
+    """Test soft and hard line breaks in a paragraph."""
+    doc = pyld.parse("one\ntwo\\\nthree")
+    content = doc.blocks[0].content
+    assert [type(i).__name__ for i in content] == [
+        "Text",
+        "SoftBreak",
+        "Text",
+        "HardBreak",
+        "Text",
+    ]
+    assert content[3].span.start == 7
+
+
+def test_link_title_and_kind():
+    """Test link labels, titles and URL kinds."""
+    doc = pyld.parse('[[*docs*|guide.ld "Guide"]] [[mailto:a@b.c]]')
+    link = doc.blocks[0].content[0]
+    assert isinstance(link, pyld.Link)
+    assert isinstance(link.label[0], pyld.Emphasis)
+    assert link.url == "guide.ld"
+    assert link.title == "Guide"
+    assert link.kind == pyld.LinkKind.Relative
+    assert doc.blocks[0].content[2].kind == pyld.LinkKind.Email
+
+
+def test_heading_ids_and_references():
+    """Test heading anchor ids and unresolved internal links."""
+    text = "# Key Findings {#findings}\n\n## Next Steps\n\n[[#findings]] [[#missing]]"
+    doc = pyld.parse(text)
+    assert doc.blocks[0].id == "findings"
+    assert doc.blocks[1].id == "next-steps"
+
+    result = pyld.Parser(check_references=True).parse_with_recovery(text)
+    assert len(result.errors) == 1
+    assert result.errors[0].kind == pyld.ParseErrorKind.UnresolvedReference
+
+
+def test_citations_and_sources():
+    """Test citations, the sources block and unused sources."""
+    result = pyld.Parser(check_references=True).parse_with_recovery(
+        "Per [@ipcc2023].\n\n::sources\n"
+        '- ipcc2023 title="AR6" url=https://ipcc.ch year=2023\n- spare\n::'
+    )
+    citation = result.document.blocks[0].content[1]
+    assert isinstance(citation, pyld.Citation)
+    assert citation.key == "ipcc2023"
+    sources = result.document.blocks[1]
+    assert isinstance(sources, pyld.Sources)
+    entry = sources.entries[0]
+    assert entry.id == "ipcc2023"
+    assert entry.title == "AR6"
+    assert entry.url == "https://ipcc.ch"
+    assert entry.attrs["year"] == 2023
+    assert len(result.errors) == 1
+    assert result.errors[0].kind == pyld.ParseErrorKind.UnusedDefinition
+
+    # An uncited source is a diagnostic, not a parse failure.
+    doc = pyld.parse("Per [@a].\n\n::sources\n- a\n- spare\n::")
+    assert len(doc.blocks[1].entries) == 2
+
+
+def test_mentions():
+    """Test mention nodes, Document.mentions() and custom mention kinds."""
+    text = "@modules mentions\n\nAsk <@agent:summarizer-v2> about *<@task:t1>*."
+    doc = pyld.parse(text)
+    mentions = doc.mentions()
+    assert [(m.kind, m.id) for m in mentions] == [
+        ("agent", "summarizer-v2"),
+        ("task", "t1"),
+    ]
+    assert isinstance(doc.blocks[0].content[1], pyld.Mention)
+    assert pyld.ModuleKind.Mentions in doc.modules
+
+    result = pyld.parse_with_recovery("@modules mentions\n\n<@user:alice>")
+    assert len(result.errors) == 1
+    parser = pyld.Parser(mention_kinds=["user"])
+    assert parser.parse_with_recovery("@modules mentions\n\n<@user:alice>").ok
+
+
+def test_select():
+    """Test Document.select() with selectors."""
+    text = (
+        "@modules tables\n\n"
+        "## Findings\n\n"
+        "::table\n| a | b |\n| --- | --- |\n| 1 | 2 |\n::\n\n"
+        "::callout type=warning\nDisk *full*.\n::\n\n"
+        "::list\n- one\n- two\n::"
+    )
+    doc = pyld.parse(text)
+
+    tables = doc.select('heading[level=2]:contains("Findings") + table')
+    assert len(tables) == 1
+    assert isinstance(tables[0], pyld.Table)
+    assert tables[0].span.start == doc.blocks[1].span.start
+
+    warnings = doc.select("callout[type=warning]")
+    assert [w.kind for w in warnings] == ["warning"]
+    items = doc.select("list > item")
+    assert all(isinstance(i, pyld.ListItem) for i in items)
+    assert [i.blocks[0].content[0].content for i in items] == ["one", "two"]
+    assert doc.select("emphasis text")[0].content == "full"
+    assert doc.select("figure") == []
+
+    try:
+        doc.select("callout[type=warning")
+        assert False, "expected ValueError"
+    except ValueError as e:
+        assert "invalid selector" in str(e)
+
+
+def test_select_uses_parser_settings():
+    """Test that select() reads the document as its parser did."""
+    parser = pyld.Parser(profile=pyld.Profile.Md, mention_kinds=["user"])
+    doc = parser.parse("@modules mentions\n\n- ask <@user:alice>\n- done")
+
+    mentions = doc.select("item mention")
+    assert [(m.kind, m.id) for m in mentions] == [("user", "alice")]
+    selected = doc.select("list")[0]
+    assert selected is not doc.blocks[0]
+    assert selected.span.start == doc.blocks[0].span.start
+    assert len(selected.items) == len(doc.blocks[0].items)
+
+
 def test_module_function_parse():
     """Test module-level parse function."""
     doc = pyld.parse("# Test", profile=pyld.Profile.Litedoc)